@@ -0,0 +1,41 @@
+use super::store::{remove_attachment_files, save_attachment};
+use crate::storage::database::AttachmentRecord;
+use crate::AppState;
+
+/// Save a pasted or dropped file for a session. `data` is base64 or a
+/// base64 data URL; `name` is the original file name, if any.
+#[tauri::command]
+pub async fn attachment_save(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+    data: String,
+    name: Option<String>,
+) -> Result<AttachmentRecord, String> {
+    save_attachment(&state, &session_id, &data, name.as_deref())
+}
+
+/// A session's attachments, or every attachment if no session is given
+#[tauri::command]
+pub async fn attachment_list(
+    state: tauri::State<'_, AppState>,
+    session_id: Option<String>,
+) -> Result<Vec<AttachmentRecord>, String> {
+    state
+        .db
+        .list_attachments(session_id.as_deref())
+        .map_err(|e| format!("Failed to list attachments: {}", e))
+}
+
+/// Delete an attachment before its session ends
+#[tauri::command]
+pub async fn attachment_delete(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<bool, String> {
+    let paths = state
+        .db
+        .remove_attachment(&id)
+        .map_err(|e| format!("Failed to delete attachment: {}", e))?;
+    remove_attachment_files(&paths);
+    Ok(!paths.is_empty())
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod store;
+
+pub use commands::*;
+pub use store::*;
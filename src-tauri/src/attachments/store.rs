@@ -0,0 +1,305 @@
+use crate::config::effective_preferences;
+use crate::storage::database::AttachmentRecord;
+use crate::AppState;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+
+const BYTES_PER_MB: usize = 1024 * 1024;
+/// Longest file name kept from the caller (extension included)
+const MAX_NAME_CHARS: usize = 100;
+/// Bytes inspected to tell text from binary
+const TEXT_SNIFF_BYTES: usize = 8192;
+
+/// Private directory holding one subdirectory per attachment. The cache dir
+/// has no spaces in its path on macOS, unlike Application Support.
+pub fn attachments_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("wiz-term")
+        .join("attachments")
+}
+
+/// MIME type and usual extension, from the file's leading bytes
+pub fn detect_mime(bytes: &[u8]) -> (&'static str, &'static str) {
+    const SIGNATURES: &[(&[u8], &str, &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png", "png"),
+        (b"\xff\xd8\xff", "image/jpeg", "jpg"),
+        (b"GIF87a", "image/gif", "gif"),
+        (b"GIF89a", "image/gif", "gif"),
+        (b"II*\0", "image/tiff", "tiff"),
+        (b"MM\0*", "image/tiff", "tiff"),
+        (b"\0\0\x01\0", "image/x-icon", "ico"),
+        (b"%PDF-", "application/pdf", "pdf"),
+        (b"PK\x03\x04", "application/zip", "zip"),
+        (b"\x1f\x8b", "application/gzip", "gz"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed", "7z"),
+    ];
+    if let Some((_, mime, ext)) = SIGNATURES
+        .iter()
+        .find(|(magic, _, _)| bytes.starts_with(magic))
+    {
+        return (mime, ext);
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return ("image/webp", "webp");
+    }
+    // ISO base media (`....ftyp<brand>`): HEIC, AVIF or MP4
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"heic" | b"heix" | b"mif1" => ("image/heic", "heic"),
+            b"avif" => ("image/avif", "avif"),
+            b"qt  " => ("video/quicktime", "mov"),
+            _ => ("video/mp4", "mp4"),
+        };
+    }
+
+    let head = &bytes[..bytes.len().min(TEXT_SNIFF_BYTES)];
+    let is_text = !head.contains(&0)
+        && match std::str::from_utf8(head) {
+            Ok(_) => true,
+            // Cut off mid-character by the sniff limit
+            Err(e) => e.error_len().is_none(),
+        };
+    if !is_text {
+        return ("application/octet-stream", "bin");
+    }
+    let text = String::from_utf8_lossy(head);
+    let text = text.trim_start();
+    if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+        ("image/svg+xml", "svg")
+    } else {
+        ("text/plain", "txt")
+    }
+}
+
+/// Name to save under: the caller's name reduced to `[A-Za-z0-9._-]` so the
+/// path can be typed into a shell unquoted, with the detected extension
+/// added if it has none
+fn sanitize_file_name(name: Option<&str>, extension: &str) -> String {
+    let base = name
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    let cleaned: String = cleaned.chars().take(MAX_NAME_CHARS).collect();
+
+    if cleaned.is_empty() {
+        return format!("attachment.{}", extension);
+    }
+    let has_extension = cleaned
+        .rsplit_once('.')
+        .is_some_and(|(stem, ext)| !stem.is_empty() && !ext.is_empty());
+    if has_extension {
+        cleaned
+    } else {
+        format!("{}.{}", cleaned.trim_end_matches('.'), extension)
+    }
+}
+
+/// Accepts raw base64 or a `data:<type>;base64,<data>` URL. The declared
+/// type is ignored; the contents decide.
+fn decode_data(data: &str, max_bytes: Option<usize>) -> Result<Vec<u8>, String> {
+    let encoded = match data.strip_prefix("data:") {
+        Some(url) => {
+            let (header, body) = url
+                .split_once(',')
+                .ok_or_else(|| "Invalid data URL format".to_string())?;
+            if !header.ends_with(";base64") {
+                return Err("Only base64 data URLs are supported".to_string());
+            }
+            body
+        }
+        None => data,
+    };
+    let too_large = |max: usize| format!("Attachment is over the {} MB limit", max / BYTES_PER_MB);
+    // Checked before decoding so an oversized paste isn't held twice
+    if let Some(max) = max_bytes.filter(|max| encoded.len() / 4 * 3 > *max + 3) {
+        return Err(too_large(max));
+    }
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+    if let Some(max) = max_bytes.filter(|max| bytes.len() > *max) {
+        return Err(too_large(max));
+    }
+    Ok(bytes)
+}
+
+/// Create a directory only the current user can read
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        if !dir.exists() {
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)?;
+        }
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+    }
+    #[cfg(not(unix))]
+    {
+        std::fs::create_dir_all(dir)
+    }
+}
+
+/// Write a new file, failing rather than replacing anything already there
+fn write_new_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Save a pasted or dropped file for `session_id` and record it
+pub fn save_attachment(
+    state: &AppState,
+    session_id: &str,
+    data: &str,
+    name: Option<&str>,
+) -> Result<AttachmentRecord, String> {
+    let session_exists = state
+        .pty_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock PTY manager: {}", e))?
+        .get_session(session_id)
+        .is_some();
+    if !session_exists {
+        return Err(format!("Session not found: {}", session_id));
+    }
+
+    let prefs = effective_preferences(state)?;
+    let max_bytes = usize::try_from(prefs.attachment_max_mb)
+        .ok()
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb * BYTES_PER_MB);
+    let bytes = decode_data(data, max_bytes)?;
+    let (mime_type, extension) = detect_mime(&bytes);
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let root = attachments_dir();
+    let dir = root.join(&id);
+    let file_name = sanitize_file_name(name, extension);
+    let path = dir.join(&file_name);
+    create_private_dir(&root)
+        .and_then(|_| create_private_dir(&dir))
+        .and_then(|_| write_new_file(&path, &bytes))
+        .map_err(|e| {
+            let _ = std::fs::remove_dir_all(&dir);
+            format!("Failed to save attachment: {}", e)
+        })?;
+
+    let record = AttachmentRecord {
+        id,
+        session_id: session_id.to_string(),
+        file_name,
+        mime_type: mime_type.to_string(),
+        path: path.to_string_lossy().to_string(),
+        size_bytes: bytes.len() as i64,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    if let Err(e) = state.db.insert_attachment(&record) {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(format!("Failed to record attachment: {}", e));
+    }
+    info!(
+        "Saved attachment {} ({}, {} bytes)",
+        record.path, record.mime_type, record.size_bytes
+    );
+    Ok(record)
+}
+
+/// Delete attachment files and their directories. Paths outside the
+/// attachments directory are never touched.
+pub(crate) fn remove_attachment_files(paths: &[String]) -> usize {
+    let root = attachments_dir();
+    let mut removed = 0;
+    for path in paths {
+        let Some(dir) = Path::new(path)
+            .parent()
+            .filter(|dir| dir.parent() == Some(root.as_path()))
+        else {
+            warn!(
+                "Not removing attachment outside {}: {}",
+                root.display(),
+                path
+            );
+            continue;
+        };
+        match std::fs::remove_dir_all(dir) {
+            Ok(()) => removed += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove attachment {}: {}", path, e),
+        }
+    }
+    removed
+}
+
+/// Drop the attachments of a session that has ended
+pub fn release_session_attachments(state: &AppState, session_id: &str) {
+    match state.db.remove_session_attachments(session_id) {
+        Ok(paths) => {
+            remove_attachment_files(&paths);
+        }
+        Err(e) => error!("Failed to clean up attachments: {}", e),
+    }
+}
+
+/// Remove attachments left by sessions that ended while the app wasn't
+/// running, and directories no row refers to (a crash mid-save)
+pub fn remove_stale_attachments(state: &AppState) {
+    match state.db.remove_orphaned_attachments() {
+        Ok(paths) => {
+            let removed = remove_attachment_files(&paths);
+            if removed > 0 {
+                info!("Removed {} attachments of ended sessions", removed);
+            }
+        }
+        Err(e) => error!("Failed to clean up attachments: {}", e),
+    }
+
+    let known: Vec<String> = match state.db.list_attachments(None) {
+        Ok(records) => records.into_iter().map(|record| record.id).collect(),
+        Err(e) => {
+            error!("Failed to list attachments: {}", e);
+            return;
+        }
+    };
+    let Ok(entries) = std::fs::read_dir(attachments_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if known.contains(&name) {
+            continue;
+        }
+        let path = entry.path();
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        if let Err(e) = result {
+            warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use super::osc52::respond_to_clipboard;
+use tauri::AppHandle;
+
+/// Answer a `clipboard-prompt`: carry out the request or refuse it
+#[tauri::command]
+pub async fn clipboard_respond(app: AppHandle, id: String, allow: bool) -> Result<(), String> {
+    respond_to_clipboard(&app, &id, allow)
+}
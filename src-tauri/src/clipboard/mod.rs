@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod osc52;
+
+pub use commands::*;
+pub use osc52::*;
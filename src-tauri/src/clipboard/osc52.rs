@@ -0,0 +1,509 @@
+use crate::config::effective_preferences;
+use crate::pty::{OscDecision, OscHandler};
+use crate::AppState;
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tracing::{error, info, warn};
+
+/// Clipboard request: `ESC ] 52 ; <selection> ; <base64 | ?> BEL`
+const OSC52_PREFIX: &[u8] = b"52;";
+/// A payload of `?` asks for the clipboard contents instead of setting them
+const QUERY: &[u8] = b"?";
+const BYTES_PER_KB: usize = 1024;
+/// Characters of the text shown when asking before a write
+const PREVIEW_CHARS: usize = 120;
+/// Selection used when a request names none
+const DEFAULT_SELECTION: &str = "c";
+/// A prompt nobody answers is dropped after this long
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Unanswered prompts per session; further requests are refused
+const MAX_PENDING_PER_SESSION: usize = 4;
+
+/// `clipboard_write` / `clipboard_read` values
+pub const CLIPBOARD_POLICIES: &[&str] = &["allow", "ask", "deny"];
+
+/// Senders often leave out the padding
+const LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardAccess {
+    Read,
+    Write,
+}
+
+/// Policy in effect for one session: its profile's settings over the
+/// preferences
+#[derive(Debug, Clone)]
+struct ClipboardPolicy {
+    write: String,
+    read: String,
+    max_bytes: Option<usize>,
+}
+
+/// A request waiting on a `clipboard-prompt`
+struct PendingRequest {
+    session_id: String,
+    window: String,
+    selection: String,
+    access: ClipboardAccess,
+    /// Text to copy, for writes
+    text: Option<String>,
+    max_bytes: Option<usize>,
+    asked: Instant,
+}
+
+/// Requests waiting for the user, shared by session reader threads and the
+/// `clipboard_respond` command
+#[derive(Default)]
+pub struct ClipboardRegistry {
+    pending: HashMap<String, PendingRequest>,
+}
+
+impl ClipboardRegistry {
+    /// Remove the requests matching `remove`, returning them
+    fn take(&mut self, remove: impl Fn(&PendingRequest) -> bool) -> Vec<(String, PendingRequest)> {
+        let ids: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, request)| remove(request))
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| self.pending.remove(&id).map(|request| (id, request)))
+            .collect()
+    }
+}
+
+/// Sent to the session's window when the policy for a request is `ask`
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardPrompt {
+    pub id: String,
+    pub session_id: String,
+    pub access: ClipboardAccess,
+    /// Size of the text to copy, for writes
+    pub size_bytes: Option<usize>,
+    /// Start of the text to copy, for writes
+    pub preview: Option<String>,
+}
+
+/// Sent after a program set the clipboard
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardChange {
+    pub session_id: String,
+    pub size_bytes: usize,
+}
+
+/// Sent when a request was refused by policy, was too large, or arrived
+/// while too many others were waiting
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardBlocked {
+    pub session_id: String,
+    pub access: ClipboardAccess,
+    /// "denied", "too_large" or "too_many"
+    pub reason: String,
+}
+
+/// Sent when a prompt can no longer be answered: it expired or its session
+/// ended
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardPromptDismissed {
+    pub id: String,
+    pub session_id: String,
+}
+
+fn session_policy(state: &AppState, profile: Option<&str>) -> Result<ClipboardPolicy, String> {
+    let prefs = effective_preferences(state)?;
+    let mut policy = ClipboardPolicy {
+        write: prefs.clipboard_write,
+        read: prefs.clipboard_read,
+        max_bytes: usize::try_from(prefs.clipboard_max_kb)
+            .ok()
+            .filter(|kb| *kb > 0)
+            .map(|kb| kb * BYTES_PER_KB),
+    };
+
+    let Some(name) = profile else {
+        return Ok(policy);
+    };
+    let config = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?;
+    if let Some(profile) = config
+        .file
+        .as_ref()
+        .and_then(|file| file.profiles.get(name))
+    {
+        if let Some(write) = &profile.clipboard_write {
+            policy.write = write.clone();
+        }
+        if let Some(read) = &profile.clipboard_read {
+            policy.read = read.clone();
+        }
+    }
+    Ok(policy)
+}
+
+fn emit_blocked(
+    app: &AppHandle,
+    window: &str,
+    session_id: &str,
+    access: ClipboardAccess,
+    reason: &str,
+) {
+    info!(
+        "Clipboard {:?} from session {} {}",
+        access, session_id, reason
+    );
+    let blocked = ClipboardBlocked {
+        session_id: session_id.to_string(),
+        access,
+        reason: reason.to_string(),
+    };
+    if let Err(e) = app.emit_to(window, "clipboard-blocked", blocked) {
+        error!("Failed to emit clipboard event: {}", e);
+    }
+}
+
+fn write_clipboard(app: &AppHandle, window: &str, session_id: &str, text: String) {
+    let size_bytes = text.len();
+    if let Err(e) = app.clipboard().write_text(text) {
+        error!("Failed to set clipboard: {}", e);
+        return;
+    }
+    let change = ClipboardChange {
+        session_id: session_id.to_string(),
+        size_bytes,
+    };
+    if let Err(e) = app.emit_to(window, "clipboard-changed", change) {
+        error!("Failed to emit clipboard event: {}", e);
+    }
+}
+
+/// Answer a query by writing the clipboard contents back to the session
+fn reply_with_clipboard(
+    app: &AppHandle,
+    window: &str,
+    session_id: &str,
+    selection: &str,
+    max_bytes: Option<usize>,
+) {
+    let text = match app.clipboard().read_text() {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Failed to read clipboard: {}", e);
+            String::new()
+        }
+    };
+    if max_bytes.is_some_and(|max| text.len() > max) {
+        emit_blocked(app, window, session_id, ClipboardAccess::Read, "too_large");
+        return;
+    }
+
+    let reply = format!("\x1b]52;{};{}\x07", selection, STANDARD.encode(text));
+    let state = app.state::<AppState>();
+    let result = state
+        .pty_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock PTY manager: {}", e))
+        .and_then(|manager| manager.write_to_session(session_id, reply.as_bytes()));
+    if let Err(e) = result {
+        error!("Failed to answer clipboard query: {}", e);
+    }
+}
+
+fn perform(app: &AppHandle, request: PendingRequest) {
+    match request.access {
+        ClipboardAccess::Write => write_clipboard(
+            app,
+            &request.window,
+            &request.session_id,
+            request.text.unwrap_or_default(),
+        ),
+        ClipboardAccess::Read => reply_with_clipboard(
+            app,
+            &request.window,
+            &request.session_id,
+            &request.selection,
+            request.max_bytes,
+        ),
+    }
+}
+
+fn dismiss(app: &AppHandle, dismissed: Vec<(String, PendingRequest)>) {
+    for (id, request) in dismissed {
+        let event = ClipboardPromptDismissed {
+            id,
+            session_id: request.session_id,
+        };
+        if let Err(e) = app.emit_to(request.window.as_str(), "clipboard-prompt-dismissed", event) {
+            error!("Failed to emit clipboard event: {}", e);
+        }
+    }
+}
+
+/// Drop a session's unanswered requests. Called when the session ends.
+pub fn discard_session_clipboard_requests(app: &AppHandle, session_id: &str) {
+    let state = app.state::<AppState>();
+    let dismissed = match state.clipboard.lock() {
+        Ok(mut registry) => registry.take(|request| request.session_id == session_id),
+        Err(e) => {
+            error!("Failed to lock clipboard requests: {}", e);
+            return;
+        }
+    };
+    dismiss(app, dismissed);
+}
+
+/// Allow or refuse a prompted clipboard request
+pub fn respond_to_clipboard(app: &AppHandle, id: &str, allow: bool) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let request = state
+        .clipboard
+        .lock()
+        .map_err(|e| format!("Failed to lock clipboard requests: {}", e))?
+        .pending
+        .remove(id)
+        .ok_or_else(|| format!("Clipboard request not found: {}", id))?;
+    if request.asked.elapsed() > PROMPT_TIMEOUT {
+        return Err(format!("Clipboard request expired: {}", id));
+    }
+
+    if allow {
+        perform(app, request);
+    } else {
+        info!(
+            "Clipboard {:?} from session {} refused",
+            request.access, request.session_id
+        );
+    }
+    Ok(())
+}
+
+/// An OSC 52 sequence being received
+struct Osc52Request {
+    selection: String,
+    payload: Vec<u8>,
+    /// Longest base64 payload accepted
+    max_encoded: Option<usize>,
+    too_large: bool,
+}
+
+/// xterm's selection targets: clipboard, primary, secondary, select and cut
+/// buffers 0-7. The selection is echoed back in query replies, so nothing else
+/// is accepted.
+fn valid_selection(selection: &[u8]) -> bool {
+    selection
+        .iter()
+        .all(|b| matches!(b, b'c' | b'p' | b'q' | b's' | b'0'..=b'7'))
+}
+
+/// Handles OSC 52 clipboard requests in a session's output according to the
+/// session's clipboard policy
+pub struct ClipboardInterceptor {
+    app: AppHandle,
+    session_id: String,
+    window: String,
+    profile: Option<String>,
+    request: Option<Osc52Request>,
+}
+
+impl ClipboardInterceptor {
+    pub fn new(
+        app: AppHandle,
+        session_id: String,
+        window: String,
+        profile: Option<String>,
+    ) -> Self {
+        Self {
+            app,
+            session_id,
+            window,
+            profile,
+            request: None,
+        }
+    }
+
+    fn policy(&self) -> Option<ClipboardPolicy> {
+        let state = self.app.state::<AppState>();
+        session_policy(&state, self.profile.as_deref())
+            .map_err(|e| error!("{}", e))
+            .ok()
+    }
+
+    fn handle(&self, access: ClipboardAccess, selection: String, text: Option<String>) {
+        let Some(policy) = self.policy() else {
+            return;
+        };
+        let setting = match access {
+            ClipboardAccess::Write => policy.write.as_str(),
+            ClipboardAccess::Read => policy.read.as_str(),
+        };
+        let request = PendingRequest {
+            session_id: self.session_id.clone(),
+            window: self.window.clone(),
+            selection,
+            access,
+            text,
+            max_bytes: policy.max_bytes,
+            asked: Instant::now(),
+        };
+
+        match setting {
+            "allow" => perform(&self.app, request),
+            "ask" => {
+                let id = uuid::Uuid::new_v4().to_string();
+                let prompt = ClipboardPrompt {
+                    id: id.clone(),
+                    session_id: self.session_id.clone(),
+                    access,
+                    size_bytes: request.text.as_ref().map(String::len),
+                    preview: request
+                        .text
+                        .as_ref()
+                        .map(|text| text.chars().take(PREVIEW_CHARS).collect()),
+                };
+                let state = self.app.state::<AppState>();
+                let Ok(mut registry) = state.clipboard.lock() else {
+                    return;
+                };
+                let expired = registry.take(|request| request.asked.elapsed() > PROMPT_TIMEOUT);
+                let waiting = registry
+                    .pending
+                    .values()
+                    .filter(|request| request.session_id == self.session_id)
+                    .count();
+                if waiting >= MAX_PENDING_PER_SESSION {
+                    drop(registry);
+                    dismiss(&self.app, expired);
+                    emit_blocked(
+                        &self.app,
+                        &self.window,
+                        &self.session_id,
+                        access,
+                        "too_many",
+                    );
+                    return;
+                }
+                registry.pending.insert(id, request);
+                drop(registry);
+                dismiss(&self.app, expired);
+                if let Err(e) = self
+                    .app
+                    .emit_to(self.window.as_str(), "clipboard-prompt", prompt)
+                {
+                    error!("Failed to emit clipboard prompt: {}", e);
+                }
+            }
+            _ => emit_blocked(&self.app, &self.window, &self.session_id, access, "denied"),
+        }
+    }
+}
+
+impl OscHandler for ClipboardInterceptor {
+    fn classify(&mut self, prefix: &[u8]) -> OscDecision {
+        if !prefix.starts_with(OSC52_PREFIX) {
+            return if OSC52_PREFIX.starts_with(prefix) {
+                OscDecision::Undecided
+            } else {
+                OscDecision::Pass
+            };
+        }
+        let Some(separator) = prefix[OSC52_PREFIX.len()..]
+            .iter()
+            .position(|&b| b == b';')
+            .map(|offset| OSC52_PREFIX.len() + offset)
+        else {
+            return OscDecision::Undecided;
+        };
+
+        // Still intercepted, so the terminal doesn't act on it either
+        let selection = &prefix[OSC52_PREFIX.len()..separator];
+        if !valid_selection(selection) {
+            warn!("Ignoring OSC 52 request with invalid selection");
+            self.request = None;
+            return OscDecision::Intercept {
+                body_start: separator + 1,
+            };
+        }
+
+        let max_encoded = self
+            .policy()
+            .and_then(|policy| policy.max_bytes)
+            .map(|max| max.div_ceil(3) * 4);
+        self.request = Some(Osc52Request {
+            selection: if selection.is_empty() {
+                DEFAULT_SELECTION.to_string()
+            } else {
+                String::from_utf8_lossy(selection).to_string()
+            },
+            payload: Vec::new(),
+            max_encoded,
+            too_large: false,
+        });
+        OscDecision::Intercept {
+            body_start: separator + 1,
+        }
+    }
+
+    fn data(&mut self, data: &[u8]) {
+        let Some(request) = &mut self.request else {
+            return;
+        };
+        if request.too_large {
+            return;
+        }
+        request
+            .payload
+            .extend(data.iter().filter(|b| !b.is_ascii_whitespace()));
+        if request
+            .max_encoded
+            .is_some_and(|max| request.payload.len() > max)
+        {
+            request.too_large = true;
+            request.payload = Vec::new();
+        }
+    }
+
+    fn end(&mut self, complete: bool) {
+        let Some(request) = self.request.take() else {
+            return;
+        };
+        if !complete {
+            return;
+        }
+        if request.too_large {
+            emit_blocked(
+                &self.app,
+                &self.window,
+                &self.session_id,
+                ClipboardAccess::Write,
+                "too_large",
+            );
+            return;
+        }
+
+        if request.payload == QUERY {
+            self.handle(ClipboardAccess::Read, request.selection, None);
+            return;
+        }
+        match LENIENT_BASE64.decode(&request.payload) {
+            Ok(bytes) => {
+                let text = String::from_utf8_lossy(&bytes).to_string();
+                self.handle(ClipboardAccess::Write, request.selection, Some(text));
+            }
+            Err(e) => warn!("Ignoring OSC 52 request with invalid base64: {}", e),
+        }
+    }
+}
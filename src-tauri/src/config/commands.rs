@@ -0,0 +1,103 @@
+use super::file::ConfigFile;
+use crate::keybindings::apply_keybindings;
+use crate::pty::apply_stats_interval;
+use crate::storage::database::TerminalPreferences;
+use crate::AppState;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Config file status for the settings panel
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigStatus {
+    pub path: String,
+    pub exists: bool,
+    /// Last load/validation error, if the file on disk is currently rejected
+    pub error: Option<String>,
+    /// Paths in the file missing on this machine (the file is still used)
+    pub warnings: Vec<String>,
+    /// Preference fields set by the config file (read-only in the UI)
+    pub overrides: Vec<String>,
+}
+
+/// Preferences from SQLite with the config file merged over them
+pub(crate) fn effective_preferences(state: &AppState) -> Result<TerminalPreferences, String> {
+    let prefs = state
+        .db
+        .get_terminal_preferences()
+        .map_err(|e| format!("Failed to get preferences: {}", e))?;
+
+    let config = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?;
+
+    Ok(config.merge(prefs))
+}
+
+/// Send the current effective preferences to every window and apply the
+/// ones the backend acts on
+pub(crate) fn broadcast_preferences(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let prefs = effective_preferences(state)?;
+    apply_stats_interval(app);
+    app.emit("preferences-changed", prefs)
+        .map_err(|e| format!("Failed to emit preferences: {}", e))
+}
+
+fn config_status(state: &AppState) -> Result<ConfigStatus, String> {
+    let config = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?;
+
+    Ok(ConfigStatus {
+        path: config.path.to_string_lossy().to_string(),
+        exists: config.path.exists(),
+        error: config.error.clone(),
+        warnings: config
+            .file
+            .as_ref()
+            .map(ConfigFile::warnings)
+            .unwrap_or_default(),
+        overrides: config
+            .file
+            .as_ref()
+            .map(|file| {
+                file.overridden_fields()
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+#[tauri::command]
+pub async fn config_get_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<ConfigStatus, String> {
+    config_status(&state)
+}
+
+/// Re-read the config file now (it is also watched for changes)
+#[tauri::command]
+pub async fn config_reload(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ConfigStatus, String> {
+    let result = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .reload();
+
+    match result {
+        Ok(()) => {
+            broadcast_preferences(&app, &state)?;
+            apply_keybindings(&app, &state)?;
+        }
+        Err(message) => {
+            let _ = app.emit("config-error", message);
+        }
+    }
+    config_status(&state)
+}
@@ -0,0 +1,713 @@
+use crate::clipboard::CLIPBOARD_POLICIES;
+use crate::downloads::DOWNLOAD_POLICIES;
+use crate::keybindings::find_action;
+use crate::pty::{validate_shell, MIN_STATS_INTERVAL_MS};
+use crate::storage::database::TerminalPreferences;
+use crate::webview::{check_user_agent, validate_profile_name};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::warn;
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+/// Where a settings import puts its config when config.toml already exists
+pub const IMPORTED_CONFIG_FILE_NAME: &str = "config.imported.toml";
+
+/// Modifier names accepted in key chords (matches Tauri accelerator syntax)
+const CHORD_MODIFIERS: &[&str] = &[
+    "cmd",
+    "command",
+    "ctrl",
+    "control",
+    "cmdorctrl",
+    "commandorcontrol",
+    "alt",
+    "option",
+    "shift",
+    "super",
+    "meta",
+];
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Failed to parse {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error("Invalid settings in {path}:\n  - {}", .problems.join("\n  - "))]
+    Invalid { path: String, problems: Vec<String> },
+}
+
+/// User config file (`~/.config/wiz-term/config.toml`). Every field is
+/// optional; anything set here overrides the preferences stored in SQLite.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Name of the color scheme to use (in dark mode, or always if
+    /// `theme_light` is unset)
+    pub theme: Option<String>,
+    /// Color scheme used when the system appearance is light
+    pub theme_light: Option<String>,
+    #[serde(default)]
+    pub font: FontConfig,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub shell: ShellConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub downloads: DownloadsConfig,
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    #[serde(default)]
+    pub attachments: AttachmentsConfig,
+    #[serde(default)]
+    pub browser: BrowserConfig,
+    /// Action name -> key chord, e.g. `split_horizontal = "CmdOrCtrl+D"`
+    /// (an empty string unbinds the action)
+    #[serde(default)]
+    pub keybindings: BTreeMap<String, String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FontConfig {
+    pub family: Option<String>,
+    pub size: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerminalConfig {
+    pub scrollback: Option<i32>,
+    pub cursor_blink: Option<bool>,
+    pub use_webgl: Option<bool>,
+    pub minimap_refresh_ms: Option<i32>,
+    /// Per-session CPU/memory sampling interval; 0 disables it
+    pub session_stats_interval_ms: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShellConfig {
+    pub path: Option<String>,
+}
+
+/// Retention and database upkeep; 0 keeps data forever / disables the task
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaintenanceConfig {
+    pub session_retention_days: Option<i32>,
+    pub temp_file_retention_days: Option<i32>,
+    pub interval_hours: Option<i32>,
+}
+
+/// OSC 1337 file transfers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DownloadsConfig {
+    pub dir: Option<String>,
+    /// 0 for no limit
+    pub max_mb: Option<i32>,
+    /// "ask", "accept" or "deny"
+    pub policy: Option<String>,
+}
+
+/// OSC 52 clipboard access by programs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClipboardConfig {
+    /// "allow", "ask" or "deny"
+    pub write: Option<String>,
+    /// "allow", "ask" or "deny"
+    pub read: Option<String>,
+    /// 0 for no limit
+    pub max_kb: Option<i32>,
+}
+
+/// Files pasted or dropped into a terminal
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttachmentsConfig {
+    /// 0 for no limit
+    pub max_mb: Option<i32>,
+}
+
+/// Webview panes. User agents are "chrome", "native" (the engine's own) or
+/// a literal user agent string. Domains match their subdomains too.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BrowserConfig {
+    pub user_agent: Option<String>,
+    /// Allow developer tools in release builds
+    pub devtools: Option<bool>,
+    /// Append page console messages to the webview console log
+    pub console_log: Option<bool>,
+    /// Let panes load localhost and loopback addresses
+    pub allow_localhost: Option<bool>,
+    /// Let panes load `file:` URLs
+    pub allow_file: Option<bool>,
+    /// If set, the only domains panes may load
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Domains panes may never load
+    #[serde(default)]
+    pub blocked_domains: Vec<String>,
+    /// Domain -> user agent for pages on it and its subdomains, overriding
+    /// profiles
+    #[serde(default)]
+    pub user_agents: BTreeMap<String, String>,
+    /// Browser profile name -> settings for webviews using it
+    #[serde(default)]
+    pub profiles: BTreeMap<String, BrowserProfileConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BrowserProfileConfig {
+    pub user_agent: Option<String>,
+}
+
+/// Named launch profile (command, arguments, working directory, environment)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Clipboard policy for sessions started with this profile, overriding
+    /// `[clipboard]`
+    pub clipboard_write: Option<String>,
+    pub clipboard_read: Option<String>,
+}
+
+/// Location of the config file: `$XDG_CONFIG_HOME/wiz-term/config.toml`,
+/// falling back to `~/.config` (the platform config dir on Windows)
+pub fn config_path() -> PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            if cfg!(windows) {
+                dirs::config_dir()
+            } else {
+                dirs::home_dir().map(|home| home.join(".config"))
+            }
+        })
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    base.join("wiz-term").join(CONFIG_FILE_NAME)
+}
+
+impl ConfigFile {
+    /// Read and validate the config file. A missing file is not an error.
+    pub fn load(path: &Path) -> Result<Option<Self>, ConfigError> {
+        let display = path.display().to_string();
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(ConfigError::Read {
+                    path: display,
+                    source,
+                })
+            }
+        };
+
+        let config: ConfigFile = toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: display.clone(),
+            source,
+        })?;
+
+        let problems = config.problems();
+        if !problems.is_empty() {
+            return Err(ConfigError::Invalid {
+                path: display,
+                problems,
+            });
+        }
+        Ok(Some(config))
+    }
+
+    /// Semantic checks that TOML parsing can't express
+    pub(crate) fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Some(size) = self.font.size {
+            if !(6..=72).contains(&size) {
+                problems.push(format!("font.size must be between 6 and 72 (got {})", size));
+            }
+        }
+        if let Some(family) = &self.font.family {
+            if family.trim().is_empty() {
+                problems.push("font.family cannot be empty".to_string());
+            }
+        }
+        if let Some(scrollback) = self.terminal.scrollback {
+            if !(0..=1_000_000).contains(&scrollback) {
+                problems.push(format!(
+                    "terminal.scrollback must be between 0 and 1000000 (got {})",
+                    scrollback
+                ));
+            }
+        }
+        if let Some(refresh) = self.terminal.minimap_refresh_ms {
+            if refresh < 16 {
+                problems.push(format!(
+                    "terminal.minimap_refresh_ms must be at least 16 (got {})",
+                    refresh
+                ));
+            }
+        }
+        if let Some(interval) = self.terminal.session_stats_interval_ms {
+            if interval != 0 && interval < MIN_STATS_INTERVAL_MS {
+                problems.push(format!(
+                    "terminal.session_stats_interval_ms must be 0 or at least {} (got {})",
+                    MIN_STATS_INTERVAL_MS, interval
+                ));
+            }
+        }
+        for (key, value) in [
+            ("maintenance.session_retention_days", self.maintenance.session_retention_days),
+            ("maintenance.temp_file_retention_days", self.maintenance.temp_file_retention_days),
+            ("maintenance.interval_hours", self.maintenance.interval_hours),
+            ("downloads.max_mb", self.downloads.max_mb),
+            ("clipboard.max_kb", self.clipboard.max_kb),
+            ("attachments.max_mb", self.attachments.max_mb),
+        ] {
+            if value.is_some_and(|v| v < 0) {
+                problems.push(format!("{} cannot be negative", key));
+            }
+        }
+        if let Some(policy) = &self.downloads.policy {
+            if !DOWNLOAD_POLICIES.contains(&policy.as_str()) {
+                problems.push(format!(
+                    "downloads.policy must be one of {} (got {:?})",
+                    DOWNLOAD_POLICIES.join(", "),
+                    policy
+                ));
+            }
+        }
+        for (key, policy) in [
+            ("clipboard.write", &self.clipboard.write),
+            ("clipboard.read", &self.clipboard.read),
+        ] {
+            if let Some(problem) = check_clipboard_policy(key, policy) {
+                problems.push(problem);
+            }
+        }
+        for (key, theme) in [("theme", &self.theme), ("theme_light", &self.theme_light)] {
+            if theme.as_ref().is_some_and(|name| name.trim().is_empty()) {
+                problems.push(format!("{} cannot be empty", key));
+            }
+        }
+        for (action, chord) in &self.keybindings {
+            if find_action(action).is_none() {
+                problems.push(format!("keybindings.{} is not a known action", action));
+            } else if !chord.trim().is_empty() {
+                if let Err(e) = validate_chord(chord) {
+                    problems.push(format!("keybindings.{}: {}", action, e));
+                }
+            }
+        }
+        if let Some(problem) =
+            check_user_agent_setting("browser.user_agent", &self.browser.user_agent)
+        {
+            problems.push(problem);
+        }
+        for (key, domains) in [
+            ("browser.allowed_domains", &self.browser.allowed_domains),
+            ("browser.blocked_domains", &self.browser.blocked_domains),
+        ] {
+            for domain in domains {
+                if let Some(problem) = check_domain(key, domain) {
+                    problems.push(problem);
+                }
+            }
+        }
+        for (domain, user_agent) in &self.browser.user_agents {
+            let key = format!("browser.user_agents.{:?}", domain);
+            if let Some(problem) = check_domain("browser.user_agents", domain) {
+                problems.push(problem);
+            }
+            if let Some(problem) = check_user_agent(user_agent) {
+                problems.push(format!("{} {}", key, problem));
+            }
+        }
+        for (name, profile) in &self.browser.profiles {
+            if let Err(e) = validate_profile_name(name) {
+                problems.push(format!("browser.profiles.{}: {}", name, e));
+            }
+            let key = format!("browser.profiles.{}.user_agent", name);
+            if let Some(problem) = check_user_agent_setting(&key, &profile.user_agent) {
+                problems.push(problem);
+            }
+        }
+        for (name, profile) in &self.profiles {
+            if let Some(command) = &profile.command {
+                if command.trim().is_empty() {
+                    problems.push(format!("profiles.{}.command cannot be empty", name));
+                }
+            }
+            for (key, policy) in [
+                ("clipboard_write", &profile.clipboard_write),
+                ("clipboard_read", &profile.clipboard_read),
+            ] {
+                let key = format!("profiles.{}.{}", name, key);
+                if let Some(problem) = check_clipboard_policy(&key, policy) {
+                    problems.push(problem);
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Paths that may only exist on some machines. A config shared between
+    /// machines stays valid without them; they're checked again when a
+    /// session starts.
+    pub(crate) fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let Some(shell) = &self.shell.path {
+            if !shell.trim().is_empty() {
+                if let Err(e) = validate_shell(&shellexpand::tilde(shell.trim())) {
+                    warnings.push(format!("shell.path: {}", e));
+                }
+            }
+        }
+        for (name, profile) in &self.profiles {
+            if let Some(cwd) = &profile.cwd {
+                let expanded = shellexpand::tilde(cwd).to_string();
+                if !Path::new(&expanded).is_dir() {
+                    warnings.push(format!(
+                        "profiles.{}.cwd {:?} is not a directory",
+                        name, cwd
+                    ));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Overlay the values set in this file onto preferences loaded from SQLite
+    pub fn apply_to(&self, prefs: &mut TerminalPreferences) {
+        if let Some(family) = &self.font.family {
+            prefs.font_family = family.clone();
+        }
+        if let Some(size) = self.font.size {
+            prefs.font_size = size;
+        }
+        if let Some(scrollback) = self.terminal.scrollback {
+            prefs.scrollback = scrollback;
+        }
+        if let Some(cursor_blink) = self.terminal.cursor_blink {
+            prefs.cursor_blink = cursor_blink;
+        }
+        if let Some(use_webgl) = self.terminal.use_webgl {
+            prefs.use_webgl = use_webgl;
+        }
+        if let Some(refresh) = self.terminal.minimap_refresh_ms {
+            prefs.minimap_refresh_ms = refresh;
+        }
+        if let Some(interval) = self.terminal.session_stats_interval_ms {
+            prefs.session_stats_interval_ms = interval;
+        }
+        if let Some(shell) = &self.shell.path {
+            prefs.shell_path = shellexpand::tilde(shell).to_string();
+        }
+        if let Some(days) = self.maintenance.session_retention_days {
+            prefs.session_retention_days = days;
+        }
+        if let Some(days) = self.maintenance.temp_file_retention_days {
+            prefs.temp_file_retention_days = days;
+        }
+        if let Some(hours) = self.maintenance.interval_hours {
+            prefs.maintenance_interval_hours = hours;
+        }
+        if let Some(dir) = &self.downloads.dir {
+            prefs.downloads_dir = shellexpand::tilde(dir).to_string();
+        }
+        if let Some(max_mb) = self.downloads.max_mb {
+            prefs.download_max_mb = max_mb;
+        }
+        if let Some(policy) = &self.downloads.policy {
+            prefs.download_policy = policy.clone();
+        }
+        if let Some(policy) = &self.clipboard.write {
+            prefs.clipboard_write = policy.clone();
+        }
+        if let Some(policy) = &self.clipboard.read {
+            prefs.clipboard_read = policy.clone();
+        }
+        if let Some(max_kb) = self.clipboard.max_kb {
+            prefs.clipboard_max_kb = max_kb;
+        }
+        if let Some(max_mb) = self.attachments.max_mb {
+            prefs.attachment_max_mb = max_mb;
+        }
+        if let Some(user_agent) = &self.browser.user_agent {
+            prefs.webview_user_agent = user_agent.clone();
+        }
+        if let Some(devtools) = self.browser.devtools {
+            prefs.webview_devtools = devtools;
+        }
+        if let Some(console_log) = self.browser.console_log {
+            prefs.webview_console_log = console_log;
+        }
+        if let Some(theme) = &self.theme {
+            prefs.color_scheme = theme.clone();
+        }
+        if let Some(theme) = &self.theme_light {
+            prefs.color_scheme_light = Some(theme.clone());
+        }
+    }
+
+    /// Overlay another config: values it sets win, tables are merged by key
+    pub fn merge_from(&mut self, other: ConfigFile) {
+        self.theme = other.theme.or(self.theme.take());
+        self.theme_light = other.theme_light.or(self.theme_light.take());
+        self.font.family = other.font.family.or(self.font.family.take());
+        self.font.size = other.font.size.or(self.font.size);
+        self.terminal.scrollback = other.terminal.scrollback.or(self.terminal.scrollback);
+        self.terminal.cursor_blink = other.terminal.cursor_blink.or(self.terminal.cursor_blink);
+        self.terminal.use_webgl = other.terminal.use_webgl.or(self.terminal.use_webgl);
+        self.terminal.minimap_refresh_ms = other
+            .terminal
+            .minimap_refresh_ms
+            .or(self.terminal.minimap_refresh_ms);
+        self.terminal.session_stats_interval_ms = other
+            .terminal
+            .session_stats_interval_ms
+            .or(self.terminal.session_stats_interval_ms);
+        self.shell.path = other.shell.path.or(self.shell.path.take());
+        self.maintenance.session_retention_days = other
+            .maintenance
+            .session_retention_days
+            .or(self.maintenance.session_retention_days);
+        self.maintenance.temp_file_retention_days = other
+            .maintenance
+            .temp_file_retention_days
+            .or(self.maintenance.temp_file_retention_days);
+        self.maintenance.interval_hours =
+            other.maintenance.interval_hours.or(self.maintenance.interval_hours);
+        self.downloads.dir = other.downloads.dir.or(self.downloads.dir.take());
+        self.downloads.max_mb = other.downloads.max_mb.or(self.downloads.max_mb);
+        self.downloads.policy = other.downloads.policy.or(self.downloads.policy.take());
+        self.clipboard.write = other.clipboard.write.or(self.clipboard.write.take());
+        self.clipboard.read = other.clipboard.read.or(self.clipboard.read.take());
+        self.clipboard.max_kb = other.clipboard.max_kb.or(self.clipboard.max_kb);
+        self.attachments.max_mb = other.attachments.max_mb.or(self.attachments.max_mb);
+        self.browser.user_agent = other.browser.user_agent.or(self.browser.user_agent.take());
+        self.browser.devtools = other.browser.devtools.or(self.browser.devtools);
+        self.browser.console_log = other.browser.console_log.or(self.browser.console_log);
+        self.browser.allow_localhost = other
+            .browser
+            .allow_localhost
+            .or(self.browser.allow_localhost);
+        self.browser.allow_file = other.browser.allow_file.or(self.browser.allow_file);
+        self.browser
+            .allowed_domains
+            .extend(other.browser.allowed_domains);
+        self.browser
+            .blocked_domains
+            .extend(other.browser.blocked_domains);
+        self.browser.user_agents.extend(other.browser.user_agents);
+        self.browser.profiles.extend(other.browser.profiles);
+        self.keybindings.extend(other.keybindings);
+        self.profiles.extend(other.profiles);
+    }
+
+    /// Write the file, keeping the previous version as `config.toml.bak`
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = toml::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        if path.exists() {
+            let backup = path.with_extension("toml.bak");
+            std::fs::copy(path, &backup)
+                .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+        }
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Preference fields controlled by this file (read-only in the UI)
+    pub fn overridden_fields(&self) -> Vec<&'static str> {
+        let fields = [
+            ("font_family", self.font.family.is_some()),
+            ("font_size", self.font.size.is_some()),
+            ("scrollback", self.terminal.scrollback.is_some()),
+            ("cursor_blink", self.terminal.cursor_blink.is_some()),
+            ("use_webgl", self.terminal.use_webgl.is_some()),
+            ("minimap_refresh_ms", self.terminal.minimap_refresh_ms.is_some()),
+            (
+                "session_stats_interval_ms",
+                self.terminal.session_stats_interval_ms.is_some(),
+            ),
+            ("shell_path", self.shell.path.is_some()),
+            ("color_scheme", self.theme.is_some()),
+            ("color_scheme_light", self.theme_light.is_some()),
+            (
+                "session_retention_days",
+                self.maintenance.session_retention_days.is_some(),
+            ),
+            (
+                "temp_file_retention_days",
+                self.maintenance.temp_file_retention_days.is_some(),
+            ),
+            (
+                "maintenance_interval_hours",
+                self.maintenance.interval_hours.is_some(),
+            ),
+            ("downloads_dir", self.downloads.dir.is_some()),
+            ("download_max_mb", self.downloads.max_mb.is_some()),
+            ("download_policy", self.downloads.policy.is_some()),
+            ("clipboard_write", self.clipboard.write.is_some()),
+            ("clipboard_read", self.clipboard.read.is_some()),
+            ("clipboard_max_kb", self.clipboard.max_kb.is_some()),
+            ("attachment_max_mb", self.attachments.max_mb.is_some()),
+            ("webview_user_agent", self.browser.user_agent.is_some()),
+            ("webview_devtools", self.browser.devtools.is_some()),
+            ("webview_console_log", self.browser.console_log.is_some()),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, set)| *set)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+fn check_clipboard_policy(key: &str, policy: &Option<String>) -> Option<String> {
+    let policy = policy.as_ref()?;
+    (!CLIPBOARD_POLICIES.contains(&policy.as_str())).then(|| {
+        format!(
+            "{} must be one of {} (got {:?})",
+            key,
+            CLIPBOARD_POLICIES.join(", "),
+            policy
+        )
+    })
+}
+
+fn check_domain(key: &str, domain: &str) -> Option<String> {
+    let bare = domain.trim_start_matches('.');
+    (bare.is_empty() || bare.contains(|c: char| c == '/' || c == ':' || c.is_whitespace()))
+        .then(|| format!("{}: {:?} is not a domain name", key, domain))
+}
+
+fn check_user_agent_setting(key: &str, user_agent: &Option<String>) -> Option<String> {
+    let problem = check_user_agent(user_agent.as_ref()?)?;
+    Some(format!("{} {}", key, problem))
+}
+
+/// Check a chord like `CmdOrCtrl+Shift+D`: known modifiers followed by one key
+pub fn validate_chord(chord: &str) -> Result<(), String> {
+    let parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+    let Some((key, modifiers)) = parts.split_last() else {
+        return Err("empty key chord".to_string());
+    };
+    if key.is_empty() {
+        return Err(format!("{:?} has no key after the modifiers", chord));
+    }
+    for modifier in modifiers {
+        if !CHORD_MODIFIERS.contains(&modifier.to_lowercase().as_str()) {
+            return Err(format!(
+                "unknown modifier {:?} in {:?} (expected one of Cmd, Ctrl, CmdOrCtrl, Alt, Shift, Super)",
+                modifier, chord
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Config file state held in `AppState`. On a failed reload the last good
+/// config stays in effect and the error is kept for the UI.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub path: PathBuf,
+    pub file: Option<ConfigFile>,
+    pub error: Option<String>,
+}
+
+impl LoadedConfig {
+    pub fn load(path: PathBuf) -> Self {
+        let mut loaded = Self {
+            path,
+            file: None,
+            error: None,
+        };
+        // The error is recorded on `loaded` for the UI to show
+        let _ = loaded.reload();
+        loaded
+    }
+
+    pub fn reload(&mut self) -> Result<(), String> {
+        match ConfigFile::load(&self.path) {
+            Ok(file) => {
+                for warning in file.iter().flat_map(ConfigFile::warnings) {
+                    warn!("{}: {}", self.path.display(), warning);
+                }
+                self.file = file;
+                self.error = None;
+                Ok(())
+            }
+            Err(e) => {
+                let message = e.to_string();
+                self.error = Some(message.clone());
+                Err(message)
+            }
+        }
+    }
+
+    /// DB preferences with config file values applied on top
+    pub fn merge(&self, mut prefs: TerminalPreferences) -> TerminalPreferences {
+        if let Some(file) = &self.file {
+            file.apply_to(&mut prefs);
+        }
+        prefs
+    }
+
+    /// Preferences to save from effective ones: fields this file overrides
+    /// are put back to their `stored` values, so config.toml settings aren't
+    /// copied into SQLite
+    pub fn unmerge(
+        &self,
+        prefs: TerminalPreferences,
+        stored: TerminalPreferences,
+    ) -> TerminalPreferences {
+        let Some(file) = &self.file else {
+            return prefs;
+        };
+        let (Ok(Value::Object(mut fields)), Ok(Value::Object(stored))) =
+            (serde_json::to_value(&prefs), serde_json::to_value(stored))
+        else {
+            return prefs;
+        };
+        for name in file.overridden_fields() {
+            if let Some(value) = stored.get(name) {
+                fields.insert(name.to_string(), value.clone());
+            }
+        }
+        serde_json::from_value(Value::Object(fields)).unwrap_or(prefs)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod file;
+pub mod watcher;
+
+pub use commands::*;
+pub use file::*;
+pub use watcher::*;
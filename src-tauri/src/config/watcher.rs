@@ -0,0 +1,91 @@
+use super::commands::broadcast_preferences;
+use super::file::CONFIG_FILE_NAME;
+use crate::keybindings::apply_keybindings;
+use crate::AppState;
+use notify::{Event, RecursiveMode, Watcher};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info};
+
+/// Editors save in several steps (write temp, rename, chmod); wait for quiet
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Watch the config directory and reload `config.toml` whenever it changes.
+/// The parent directory is watched so atomic-rename saves are picked up.
+pub fn watch_config(app: AppHandle) -> Result<(), String> {
+    let path = app
+        .state::<AppState>()
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .path
+        .clone();
+    let dir = path
+        .parent()
+        .ok_or("Config path has no parent directory")?
+        .to_path_buf();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let (tx, rx) = mpsc::channel::<Event>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+    info!("Watching {} for changes", path.display());
+
+    std::thread::spawn(move || {
+        // The watcher stops when dropped, so it lives as long as this thread
+        let _watcher = watcher;
+
+        while let Ok(event) = rx.recv() {
+            let touches_config = !event.kind.is_access()
+                && event
+                    .paths
+                    .iter()
+                    .any(|p| p.file_name().is_some_and(|name| name == CONFIG_FILE_NAME));
+            if !touches_config {
+                continue;
+            }
+
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+            reload_and_broadcast(&app);
+        }
+    });
+
+    Ok(())
+}
+
+fn reload_and_broadcast(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let result = match state.config.lock() {
+        Ok(mut config) => config.reload(),
+        Err(e) => Err(format!("Failed to lock config: {}", e)),
+    };
+
+    match result {
+        Ok(()) => {
+            info!("Reloaded config file");
+            if let Err(e) = broadcast_preferences(app, &state) {
+                error!("{}", e);
+            }
+            if let Err(e) = apply_keybindings(app, &state) {
+                error!("{}", e);
+            }
+        }
+        Err(message) => {
+            error!("{}", message);
+            if let Err(e) = app.emit("config-error", message) {
+                error!("Failed to emit config error: {}", e);
+            }
+        }
+    }
+}
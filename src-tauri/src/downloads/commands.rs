@@ -0,0 +1,25 @@
+use super::transfer::respond_to_download;
+use crate::storage::database::DownloadRecord;
+use crate::AppState;
+use tauri::AppHandle;
+
+/// Downloads listed when no limit is given
+const DEFAULT_LIST_LIMIT: i64 = 100;
+
+/// Answer a `download-prompt`: save the file or discard it
+#[tauri::command]
+pub async fn download_respond(app: AppHandle, id: String, accept: bool) -> Result<(), String> {
+    respond_to_download(&app, &id, accept)
+}
+
+/// Recent file transfers, newest first
+#[tauri::command]
+pub async fn download_list(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<DownloadRecord>, String> {
+    state
+        .db
+        .list_downloads(limit.unwrap_or(DEFAULT_LIST_LIMIT))
+        .map_err(|e| format!("Failed to list downloads: {}", e))
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod transfer;
+
+pub use commands::*;
+pub use transfer::*;
@@ -0,0 +1,677 @@
+use crate::config::effective_preferences;
+use crate::pty::{OscDecision, OscHandler};
+use crate::storage::database::{DownloadRecord, TerminalPreferences};
+use crate::AppState;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info, warn};
+
+/// iTerm2 file transfer: `ESC ] 1337 ; File = [args] : <base64> BEL`
+const FILE_PREFIX: &[u8] = b"1337;File=";
+/// Partial files sit next to their destination so finishing is a rename
+const PARTIAL_PREFIX: &str = ".wizterm-download-";
+const PARTIAL_SUFFIX: &str = ".part";
+const BYTES_PER_MB: u64 = 1024 * 1024;
+/// A prompt nobody answers is dropped after this long, with its partial file
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// `download_policy` values
+pub const DOWNLOAD_POLICIES: &[&str] = &["ask", "accept", "deny"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decision {
+    Pending,
+    Accepted,
+    Denied,
+    /// Not answered within `ANSWER_TIMEOUT`
+    Expired,
+}
+
+/// A download waiting for the transfer to finish, the user to answer the
+/// prompt, or both
+struct PendingDownload {
+    record: DownloadRecord,
+    window: String,
+    partial: PathBuf,
+    decision: Decision,
+    received: bool,
+    /// When a prompt goes unanswered for too long
+    answer_by: Option<Instant>,
+}
+
+impl PendingDownload {
+    /// Mark a prompt that outlived `answer_by` as expired; true if it has
+    fn check_expiry(&mut self) -> bool {
+        if self.decision == Decision::Pending
+            && self
+                .answer_by
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.decision = Decision::Expired;
+        }
+        self.decision == Decision::Expired
+    }
+}
+
+/// Downloads in flight, shared by session reader threads and the
+/// `download_respond` command
+#[derive(Default)]
+pub struct DownloadRegistry {
+    pending: HashMap<String, PendingDownload>,
+}
+
+/// Sent to the session's window when a program starts a transfer and the
+/// policy is `ask`
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadPrompt {
+    pub id: String,
+    pub session_id: String,
+    pub file_name: String,
+    /// Size announced by the sender, if any
+    pub size_bytes: Option<u64>,
+}
+
+/// Arguments before the `:` in a `File=` sequence
+#[derive(Debug, Default)]
+struct FileArgs {
+    name: Option<String>,
+    size: Option<u64>,
+    inline: bool,
+}
+
+fn parse_file_args(args: &[u8]) -> FileArgs {
+    let mut parsed = FileArgs::default();
+    for arg in String::from_utf8_lossy(args).split(';') {
+        let Some((key, value)) = arg.split_once('=') else {
+            continue;
+        };
+        match key {
+            "name" => {
+                parsed.name = STANDARD
+                    .decode(value)
+                    .ok()
+                    .map(|name| String::from_utf8_lossy(&name).to_string())
+            }
+            "size" => parsed.size = value.parse().ok(),
+            "inline" => parsed.inline = value == "1",
+            _ => {}
+        }
+    }
+    parsed
+}
+
+/// Base64 decoder fed in arbitrary chunks. Keeps at most three characters
+/// between calls.
+#[derive(Default)]
+struct Base64Stream {
+    carry: Vec<u8>,
+}
+
+impl Base64Stream {
+    fn feed(&mut self, data: &[u8]) -> Result<Vec<u8>, String> {
+        self.carry
+            .extend(data.iter().filter(|b| !b.is_ascii_whitespace()));
+        let whole = self.carry.len() / 4 * 4;
+        let decoded = STANDARD
+            .decode(&self.carry[..whole])
+            .map_err(|e| format!("Invalid base64 in file transfer: {}", e))?;
+        self.carry.drain(..whole);
+        Ok(decoded)
+    }
+
+    /// Decode whatever is left, tolerating missing padding
+    fn finish(&mut self) -> Result<Vec<u8>, String> {
+        if self.carry.is_empty() {
+            return Ok(Vec::new());
+        }
+        while !self.carry.len().is_multiple_of(4) {
+            self.carry.push(b'=');
+        }
+        let decoded = STANDARD
+            .decode(&self.carry)
+            .map_err(|e| format!("Invalid base64 in file transfer: {}", e))?;
+        self.carry.clear();
+        Ok(decoded)
+    }
+}
+
+/// Where downloads go: the preference, or the system Downloads folder
+pub fn downloads_dir(prefs: &TerminalPreferences) -> PathBuf {
+    let configured = prefs.downloads_dir.trim();
+    if !configured.is_empty() {
+        return PathBuf::from(shellexpand::tilde(configured).to_string());
+    }
+    dirs::download_dir()
+        .or_else(|| dirs::home_dir().map(|home| home.join("Downloads")))
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// File name from the sender with any directory parts, control characters
+/// and leading dots removed
+fn sanitize_file_name(name: Option<&str>) -> String {
+    let base = name
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .unwrap_or_default();
+    let cleaned: String = base.chars().filter(|c| !c.is_control()).collect();
+    let cleaned = cleaned.trim().trim_start_matches('.');
+    if cleaned.is_empty() {
+        "download".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// `dir/name`, or `dir/stem (n).ext` if that is taken
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (name, None),
+    };
+    (1..)
+        .map(|n| match ext {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|path| !path.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+fn partial_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}{}{}", PARTIAL_PREFIX, id, PARTIAL_SUFFIX))
+}
+
+/// Record the outcome and tell the window
+fn finish_download(app: &AppHandle, window: &str, mut record: DownloadRecord) {
+    record.completed_at = Some(chrono::Utc::now().timestamp());
+    let state = app.state::<AppState>();
+    if let Err(e) = state.db.update_download(&record) {
+        error!("Failed to record download: {}", e);
+    }
+    match record.status.as_str() {
+        "completed" => info!(
+            "Downloaded {} ({} bytes)",
+            record.path.as_deref().unwrap_or_default(),
+            record.size_bytes
+        ),
+        status => info!("Download {} {}", record.file_name, status),
+    }
+    if let Err(e) = app.emit_to(window, "download-finished", record) {
+        error!("Failed to emit download result: {}", e);
+    }
+}
+
+/// Move a fully received, accepted download into place
+fn complete(app: &AppHandle, window: &str, pending: PendingDownload) {
+    let mut record = pending.record;
+    let dir = pending
+        .partial
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let destination = unique_path(&dir, &record.file_name);
+    match std::fs::rename(&pending.partial, &destination) {
+        Ok(()) => {
+            record.status = "completed".to_string();
+            record.path = Some(destination.to_string_lossy().to_string());
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&pending.partial);
+            record.status = "failed".to_string();
+            record.path = None;
+            record.error = Some(format!("Failed to save {}: {}", destination.display(), e));
+        }
+    }
+    finish_download(app, window, record);
+}
+
+fn discard(
+    app: &AppHandle,
+    window: &str,
+    pending: PendingDownload,
+    status: &str,
+    error: Option<String>,
+) {
+    let _ = std::fs::remove_file(&pending.partial);
+    let mut record = pending.record;
+    record.status = status.to_string();
+    record.path = None;
+    record.error = error;
+    finish_download(app, window, record);
+}
+
+/// Accept or deny a prompted download. An accepted download that is still
+/// streaming is saved when the transfer ends.
+pub fn respond_to_download(app: &AppHandle, id: &str, accept: bool) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut registry = state
+        .downloads
+        .lock()
+        .map_err(|e| format!("Failed to lock downloads: {}", e))?;
+    let pending = registry
+        .pending
+        .get_mut(id)
+        .ok_or_else(|| format!("Download not found: {}", id))?;
+    if pending.check_expiry() {
+        drop(registry);
+        sweep_unanswered(app);
+        return Err(format!("Download prompt expired: {}", id));
+    }
+    if pending.decision != Decision::Pending {
+        return Err(format!("Download already answered: {}", id));
+    }
+    pending.decision = if accept {
+        Decision::Accepted
+    } else {
+        Decision::Denied
+    };
+
+    // While the transfer is still streaming, the reader thread acts on the
+    // answer when it next writes or when the transfer ends
+    if pending.received {
+        let pending = registry.pending.remove(id).expect("entry was just found");
+        drop(registry);
+        let window = pending.window.clone();
+        if accept {
+            complete(app, &window, pending);
+        } else {
+            discard(app, &window, pending, "denied", None);
+        }
+    }
+    Ok(())
+}
+
+fn unanswered_error() -> String {
+    format!(
+        "Not answered within {} minutes",
+        ANSWER_TIMEOUT.as_secs() / 60
+    )
+}
+
+/// Drop received downloads whose prompt expired, with their partial files.
+/// Expired ones still streaming are stopped by their reader thread at the
+/// next write or when they end.
+fn sweep_unanswered(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let expired: Vec<PendingDownload> = match state.downloads.lock() {
+        Ok(mut registry) => {
+            let ids: Vec<String> = registry
+                .pending
+                .iter_mut()
+                .filter_map(|(id, pending)| {
+                    (pending.check_expiry() && pending.received).then(|| id.clone())
+                })
+                .collect();
+            ids.iter()
+                .filter_map(|id| registry.pending.remove(id))
+                .collect()
+        }
+        Err(e) => {
+            error!("Failed to lock downloads: {}", e);
+            return;
+        }
+    };
+    for pending in expired {
+        warn!("Download {} was never answered", pending.record.file_name);
+        let window = pending.window.clone();
+        discard(app, &window, pending, "denied", Some(unanswered_error()));
+    }
+}
+
+/// Drop a session's downloads that are still in flight or waiting on an
+/// answer, deleting their partial files. Called when the session ends.
+pub fn discard_session_downloads(app: &AppHandle, session_id: &str) {
+    let state = app.state::<AppState>();
+    let orphaned: Vec<PendingDownload> = match state.downloads.lock() {
+        Ok(mut registry) => {
+            let ids: Vec<String> = registry
+                .pending
+                .iter()
+                .filter(|(_, pending)| pending.record.session_id == session_id)
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter()
+                .filter_map(|id| registry.pending.remove(id))
+                .collect()
+        }
+        Err(e) => {
+            error!("Failed to lock downloads: {}", e);
+            return;
+        }
+    };
+    for pending in orphaned {
+        let window = pending.window.clone();
+        discard(
+            app,
+            &window,
+            pending,
+            "failed",
+            Some("The session ended before the download finished".to_string()),
+        );
+    }
+}
+
+/// Clean up after a crash: partial files of downloads that never finished
+pub fn fail_interrupted_downloads(state: &AppState) {
+    match state.db.fail_pending_downloads() {
+        Ok(partials) => {
+            for partial in &partials {
+                let _ = std::fs::remove_file(partial);
+            }
+            if !partials.is_empty() {
+                warn!("Discarded {} interrupted downloads", partials.len());
+            }
+        }
+        Err(e) => error!("Failed to clean up downloads: {}", e),
+    }
+}
+
+/// The transfer currently being written by a session's reader thread
+enum Transfer {
+    /// Payload is dropped (denied by policy, over the size limit, or failed)
+    Discard,
+    Writing {
+        id: String,
+        file: BufWriter<File>,
+        decoder: Base64Stream,
+        written: u64,
+        limit: Option<u64>,
+    },
+}
+
+/// Pulls non-inline OSC 1337 file transfers out of a session's output and
+/// streams them to disk. Inline images are left for the terminal to draw.
+pub struct DownloadInterceptor {
+    app: AppHandle,
+    session_id: String,
+    window: String,
+    transfer: Option<Transfer>,
+}
+
+impl DownloadInterceptor {
+    pub fn new(app: AppHandle, session_id: String, window: String) -> Self {
+        Self {
+            app,
+            session_id,
+            window,
+            transfer: None,
+        }
+    }
+
+    fn new_record(&self, args: &FileArgs) -> DownloadRecord {
+        DownloadRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: self.session_id.clone(),
+            file_name: sanitize_file_name(args.name.as_deref()),
+            path: None,
+            size_bytes: 0,
+            status: "pending".to_string(),
+            error: None,
+            created_at: chrono::Utc::now().timestamp(),
+            completed_at: None,
+        }
+    }
+
+    /// Start receiving a file; returns how its payload is handled
+    fn start(&mut self, args: FileArgs) -> Transfer {
+        // Nothing wakes up for prompts that expire after their file arrived,
+        // so each new transfer clears them out
+        sweep_unanswered(&self.app);
+        let state = self.app.state::<AppState>();
+        let prefs = match effective_preferences(&state) {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                error!("{}", e);
+                return Transfer::Discard;
+            }
+        };
+        let mut record = self.new_record(&args);
+        let limit = u64::try_from(prefs.download_max_mb)
+            .ok()
+            .filter(|mb| *mb > 0)
+            .map(|mb| mb * BYTES_PER_MB);
+
+        let rejection = if prefs.download_policy == "deny" {
+            Some(("denied", None))
+        } else if let (Some(size), Some(limit)) = (args.size, limit) {
+            (size > limit).then(|| {
+                let error = format!(
+                    "{} bytes is over the {} MB limit",
+                    size, prefs.download_max_mb
+                );
+                ("too_large", Some(error))
+            })
+        } else {
+            None
+        };
+        if let Some((status, error)) = rejection {
+            record.status = status.to_string();
+            record.size_bytes = args.size.unwrap_or(0) as i64;
+            record.error = error;
+            if let Err(e) = state.db.insert_download(&record) {
+                error!("Failed to record download: {}", e);
+            }
+            finish_download(&self.app, &self.window, record);
+            return Transfer::Discard;
+        }
+
+        let dir = downloads_dir(&prefs);
+        let partial = partial_path(&dir, &record.id);
+        let file = std::fs::create_dir_all(&dir)
+            .and_then(|_| File::create(&partial))
+            .map_err(|e| format!("Failed to create {}: {}", partial.display(), e));
+        let file = match file {
+            Ok(file) => file,
+            Err(e) => {
+                record.status = "failed".to_string();
+                record.error = Some(e);
+                if let Err(e) = state.db.insert_download(&record) {
+                    error!("Failed to record download: {}", e);
+                }
+                finish_download(&self.app, &self.window, record);
+                return Transfer::Discard;
+            }
+        };
+
+        // The partial path is kept until the download finishes so a crash
+        // can be cleaned up on the next launch
+        record.path = Some(partial.to_string_lossy().to_string());
+        if let Err(e) = state.db.insert_download(&record) {
+            error!("Failed to record download: {}", e);
+        }
+
+        let ask = prefs.download_policy != "accept";
+        let prompt = DownloadPrompt {
+            id: record.id.clone(),
+            session_id: self.session_id.clone(),
+            file_name: record.file_name.clone(),
+            size_bytes: args.size,
+        };
+        let id = record.id.clone();
+        if let Ok(mut registry) = state.downloads.lock() {
+            registry.pending.insert(
+                id.clone(),
+                PendingDownload {
+                    record,
+                    window: self.window.clone(),
+                    partial,
+                    decision: if ask {
+                        Decision::Pending
+                    } else {
+                        Decision::Accepted
+                    },
+                    received: false,
+                    answer_by: ask.then(|| Instant::now() + ANSWER_TIMEOUT),
+                },
+            );
+        }
+        if ask {
+            if let Err(e) = self
+                .app
+                .emit_to(self.window.as_str(), "download-prompt", prompt)
+            {
+                error!("Failed to emit download prompt: {}", e);
+            }
+        }
+
+        Transfer::Writing {
+            id,
+            file: BufWriter::new(file),
+            decoder: Base64Stream::default(),
+            written: 0,
+            limit,
+        }
+    }
+
+    /// Stop a transfer early, deleting what was written
+    fn abort(&mut self, id: &str, status: &str, error: Option<String>) {
+        self.transfer = Some(Transfer::Discard);
+        let state = self.app.state::<AppState>();
+        let pending = state
+            .downloads
+            .lock()
+            .ok()
+            .and_then(|mut registry| registry.pending.remove(id));
+        if let Some(pending) = pending {
+            discard(&self.app, &self.window, pending, status, error);
+        }
+    }
+
+    /// The answer to a download that is streaming, if it is still tracked
+    fn decision(&self, id: &str) -> Option<Decision> {
+        let state = self.app.state::<AppState>();
+        let decision = state.downloads.lock().ok().and_then(|mut registry| {
+            let pending = registry.pending.get_mut(id)?;
+            pending.check_expiry();
+            Some(pending.decision)
+        });
+        decision
+    }
+}
+
+impl OscHandler for DownloadInterceptor {
+    fn classify(&mut self, prefix: &[u8]) -> OscDecision {
+        if !prefix.starts_with(FILE_PREFIX) {
+            return if FILE_PREFIX.starts_with(prefix) {
+                OscDecision::Undecided
+            } else {
+                OscDecision::Pass
+            };
+        }
+        let Some(colon) = prefix.iter().position(|&b| b == b':') else {
+            return OscDecision::Undecided;
+        };
+
+        let args = parse_file_args(&prefix[FILE_PREFIX.len()..colon]);
+        if args.inline {
+            return OscDecision::Pass;
+        }
+        self.transfer = Some(self.start(args));
+        OscDecision::Intercept {
+            body_start: colon + 1,
+        }
+    }
+
+    fn data(&mut self, data: &[u8]) {
+        let Some(Transfer::Writing { id, .. }) = &self.transfer else {
+            return;
+        };
+        let id = id.clone();
+        match self.decision(&id) {
+            Some(Decision::Denied) => return self.abort(&id, "denied", None),
+            Some(Decision::Expired) => return self.abort(&id, "denied", Some(unanswered_error())),
+            _ => {}
+        }
+
+        let Some(Transfer::Writing {
+            file,
+            decoder,
+            written,
+            limit,
+            ..
+        }) = &mut self.transfer
+        else {
+            return;
+        };
+        let bytes = match decoder.feed(data) {
+            Ok(bytes) => bytes,
+            Err(e) => return self.abort(&id, "failed", Some(e)),
+        };
+        *written += bytes.len() as u64;
+        if let Some(limit) = limit.filter(|limit| *written > *limit) {
+            let error = format!("Over the {} MB limit", limit / BYTES_PER_MB);
+            warn!("Download {} stopped: {}", id, error);
+            return self.abort(&id, "too_large", Some(error));
+        }
+        if let Err(e) = file.write_all(&bytes) {
+            self.abort(
+                &id,
+                "failed",
+                Some(format!("Failed to write download: {}", e)),
+            );
+        }
+    }
+
+    fn end(&mut self, complete_transfer: bool) {
+        let Some(Transfer::Writing {
+            id,
+            mut file,
+            mut decoder,
+            mut written,
+            ..
+        }) = self.transfer.take()
+        else {
+            return;
+        };
+
+        let result = if complete_transfer {
+            decoder.finish().and_then(|bytes| {
+                written += bytes.len() as u64;
+                file.write_all(&bytes)
+                    .and_then(|_| file.flush())
+                    .map_err(|e| format!("Failed to write download: {}", e))
+            })
+        } else {
+            Err("Transfer was interrupted".to_string())
+        };
+        drop(file);
+        if let Err(e) = result {
+            self.abort(&id, "failed", Some(e));
+            return;
+        }
+
+        let state = self.app.state::<AppState>();
+        let Ok(mut registry) = state.downloads.lock() else {
+            return;
+        };
+        let Some(pending) = registry.pending.get_mut(&id) else {
+            return;
+        };
+        pending.received = true;
+        pending.record.size_bytes = written as i64;
+        pending.check_expiry();
+        match pending.decision {
+            Decision::Pending => {}
+            Decision::Accepted => {
+                let pending = registry.pending.remove(&id).expect("entry was just found");
+                drop(registry);
+                complete(&self.app, &self.window, pending);
+            }
+            Decision::Denied | Decision::Expired => {
+                let pending = registry.pending.remove(&id).expect("entry was just found");
+                drop(registry);
+                let error = (pending.decision == Decision::Expired).then(unanswered_error);
+                discard(&self.app, &self.window, pending, "denied", error);
+            }
+        }
+    }
+}
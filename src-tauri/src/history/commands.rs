@@ -0,0 +1,101 @@
+use crate::storage::database::{SessionHistoryFilter, SessionStats, TerminalSessionRecord};
+use crate::AppState;
+use serde::Serialize;
+use std::collections::HashSet;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+/// Entries in the most-used directory/command lists
+const TOP_ENTRIES: i64 = 10;
+
+/// A past or running session, with what the history panel needs to relaunch it
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionHistoryEntry {
+    #[serde(flatten)]
+    pub record: TerminalSessionRecord,
+    /// Still running in this app instance (exited sessions the manager
+    /// hasn't removed yet don't count)
+    pub is_alive: bool,
+    /// Seconds from start to end (None while running)
+    pub duration_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionHistoryPage {
+    pub sessions: Vec<SessionHistoryEntry>,
+    /// Matching sessions across all pages
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+fn live_session_ids(state: &AppState) -> Result<HashSet<String>, String> {
+    Ok(state
+        .pty_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock PTY manager: {}", e))?
+        .running_session_ids())
+}
+
+fn to_entry(record: TerminalSessionRecord, live: &HashSet<String>) -> SessionHistoryEntry {
+    SessionHistoryEntry {
+        is_alive: record.ended_at.is_none() && live.contains(&record.id),
+        duration_secs: record.ended_at.map(|ended| ended - record.created_at),
+        record,
+    }
+}
+
+/// Page through session history, newest first
+#[tauri::command]
+pub async fn history_list_sessions(
+    state: tauri::State<'_, AppState>,
+    filter: Option<SessionHistoryFilter>,
+    offset: Option<i64>,
+    limit: Option<i64>,
+) -> Result<SessionHistoryPage, String> {
+    let filter = filter.unwrap_or_default();
+    let offset = offset.unwrap_or(0).max(0);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let (records, total) = state
+        .db
+        .list_terminal_sessions(&filter, limit, offset)
+        .map_err(|e| format!("Failed to list sessions: {}", e))?;
+
+    let live = live_session_ids(&state)?;
+    Ok(SessionHistoryPage {
+        sessions: records
+            .into_iter()
+            .map(|record| to_entry(record, &live))
+            .collect(),
+        total,
+        offset,
+        limit,
+    })
+}
+
+#[tauri::command]
+pub async fn history_get_session(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<Option<SessionHistoryEntry>, String> {
+    let record = state
+        .db
+        .get_terminal_session(&id)
+        .map_err(|e| format!("Failed to get session: {}", e))?;
+
+    let live = live_session_ids(&state)?;
+    Ok(record.map(|record| to_entry(record, &live)))
+}
+
+/// Sessions per day, average lifetime, most-used directories and commands
+#[tauri::command]
+pub async fn history_stats(
+    state: tauri::State<'_, AppState>,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<SessionStats, String> {
+    state
+        .db
+        .terminal_session_stats(&filter.unwrap_or_default(), TOP_ENTRIES)
+        .map_err(|e| format!("Failed to compute session stats: {}", e))
+}
@@ -0,0 +1,3 @@
+pub mod commands;
+
+pub use commands::*;
@@ -0,0 +1,111 @@
+use super::menu::build_menu;
+use super::registry::{find_action, Keymap};
+use crate::config::validate_chord;
+use crate::AppState;
+use tauri::{AppHandle, Emitter};
+
+/// Defaults with SQLite and config.toml bindings layered on top
+pub(crate) fn current_keymap(state: &AppState) -> Result<Keymap, String> {
+    let user = state
+        .db
+        .get_keybindings()
+        .map_err(|e| format!("Failed to get keybindings: {}", e))?;
+
+    let config = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?;
+    let config_bindings = config
+        .file
+        .as_ref()
+        .map(|file| file.keybindings.clone())
+        .unwrap_or_default();
+
+    Ok(Keymap::resolve(&user, &config_bindings))
+}
+
+pub(crate) fn log_conflicts(keymap: &Keymap) {
+    for conflict in &keymap.conflicts {
+        match &conflict.reserved_by {
+            Some(item) => tracing::warn!(
+                "Keybinding {} for {} is already used by {}",
+                conflict.chord,
+                conflict.actions.join(", "),
+                item
+            ),
+            None => tracing::warn!(
+                "Keybinding {} is bound to several actions: {}",
+                conflict.chord,
+                conflict.actions.join(", ")
+            ),
+        }
+    }
+}
+
+/// Rebuild the native menus from the current keymap and send it to every
+/// window (the frontend handles actions that have no menu item)
+pub(crate) fn apply_keybindings(app: &AppHandle, state: &AppState) -> Result<Keymap, String> {
+    let keymap = current_keymap(state)?;
+    log_conflicts(&keymap);
+
+    let menu = build_menu(app, &keymap).map_err(|e| format!("Failed to build menu: {}", e))?;
+    app.set_menu(menu)
+        .map_err(|e| format!("Failed to set menu: {}", e))?;
+
+    app.emit("keybindings-changed", &keymap)
+        .map_err(|e| format!("Failed to emit keybindings: {}", e))?;
+    Ok(keymap)
+}
+
+#[tauri::command]
+pub async fn keybindings_get(state: tauri::State<'_, AppState>) -> Result<Keymap, String> {
+    current_keymap(&state)
+}
+
+/// Bind an action to a chord. An empty chord unbinds it.
+/// Bindings in config.toml still take precedence.
+#[tauri::command]
+pub async fn keybindings_set(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    action: String,
+    chord: String,
+) -> Result<Keymap, String> {
+    if find_action(&action).is_none() {
+        return Err(format!("Unknown action: {}", action));
+    }
+    let chord = chord.trim();
+    if !chord.is_empty() {
+        validate_chord(chord)?;
+    }
+
+    state
+        .db
+        .save_keybinding(&action, chord)
+        .map_err(|e| format!("Failed to save keybinding: {}", e))?;
+
+    apply_keybindings(&app, &state)
+}
+
+/// Restore the default chord for one action, or for all actions if none given
+#[tauri::command]
+pub async fn keybindings_reset(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    action: Option<String>,
+) -> Result<Keymap, String> {
+    match action {
+        Some(action) => state
+            .db
+            .delete_keybinding(&action)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to reset keybinding: {}", e))?,
+        None => state
+            .db
+            .clear_keybindings()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to reset keybindings: {}", e))?,
+    }
+
+    apply_keybindings(&app, &state)
+}
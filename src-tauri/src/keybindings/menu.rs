@@ -0,0 +1,84 @@
+use super::registry::{find_action, Keymap};
+use tauri::menu::{Menu, MenuItem, MenuItemBuilder, SubmenuBuilder};
+use tauri::{AppHandle, Runtime};
+
+/// Menu item for an action, with its accelerator from the keymap
+fn action_item<R: Runtime>(
+    app: &AppHandle<R>,
+    keymap: &Keymap,
+    id: &str,
+) -> tauri::Result<MenuItem<R>> {
+    let label = find_action(id).map_or(id, |action| action.label);
+
+    match keymap.accelerator(id) {
+        Some(chord) => MenuItemBuilder::with_id(id, label)
+            .accelerator(chord)
+            .build(app)
+            .or_else(|e| {
+                // validate_chord only checks the shape; Tauri may still reject the key name
+                tracing::warn!("Ignoring accelerator {:?} for {}: {}", chord, id, e);
+                MenuItemBuilder::with_id(id, label).build(app)
+            }),
+        None => MenuItemBuilder::with_id(id, label).build(app),
+    }
+}
+
+/// Build the native menu bar with accelerators taken from the keymap
+pub fn build_menu<R: Runtime>(app: &AppHandle<R>, keymap: &Keymap) -> tauri::Result<Menu<R>> {
+    let item = |id: &str| action_item(app, keymap, id);
+
+    // App menu (macOS standard menu with Quit, Hide, etc.)
+    let app_menu = SubmenuBuilder::new(app, "wiz-term")
+        .about(None)
+        .separator()
+        .item(&item("open_settings")?)
+        .separator()
+        .services()
+        .separator()
+        .hide()
+        .hide_others()
+        .show_all()
+        .separator()
+        .quit()
+        .build()?;
+
+    // Edit menu with standard items
+    let edit_menu = SubmenuBuilder::new(app, "Edit")
+        .undo()
+        .redo()
+        .separator()
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .build()?;
+
+    let shell_menu = SubmenuBuilder::new(app, "Shell")
+        .item(&item("new_terminal")?)
+        .item(&item("new_tab")?)
+        .item(&item("new_window")?)
+        .separator()
+        .item(&item("split_horizontal")?)
+        .item(&item("split_vertical")?)
+        .separator()
+        .item(&item("next_tab")?)
+        .item(&item("prev_tab")?)
+        .separator()
+        .item(&item("close_pane")?)
+        .build()?;
+
+    let view_menu = SubmenuBuilder::new(app, "View")
+        .item(&item("toggle_minimap")?)
+        .item(&item("pin_minimap")?)
+        .separator()
+        .item(&item("reset_minimap")?)
+        .build()?;
+
+    // Note: .close_window() omitted so Close Pane (Cmd+W) closes splits instead
+    let window_menu = SubmenuBuilder::new(app, "Window")
+        .minimize()
+        .maximize()
+        .build()?;
+
+    Menu::with_items(app, &[&app_menu, &edit_menu, &shell_menu, &view_menu, &window_menu])
+}
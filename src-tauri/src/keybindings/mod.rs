@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod menu;
+pub mod registry;
+
+pub use commands::*;
+pub use menu::*;
+pub use registry::*;
@@ -0,0 +1,225 @@
+use crate::config::validate_chord;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Native menu an action appears in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuSection {
+    App,
+    Shell,
+    View,
+}
+
+/// An action that can be bound to a key chord
+#[derive(Debug)]
+pub struct ActionDef {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub default_chord: Option<&'static str>,
+    /// Actions without a menu item are matched by the frontend
+    pub menu: Option<MenuSection>,
+}
+
+const fn action(
+    id: &'static str,
+    label: &'static str,
+    default_chord: Option<&'static str>,
+    menu: Option<MenuSection>,
+) -> ActionDef {
+    ActionDef {
+        id,
+        label,
+        default_chord,
+        menu,
+    }
+}
+
+/// Every bindable action, in menu order
+pub const ACTIONS: &[ActionDef] = &[
+    action("open_settings", "Settings…", Some("CmdOrCtrl+,"), Some(MenuSection::App)),
+    action("new_terminal", "New Terminal", Some("CmdOrCtrl+N"), Some(MenuSection::Shell)),
+    action("new_tab", "New Tab", Some("CmdOrCtrl+T"), Some(MenuSection::Shell)),
+    action("new_window", "New Window", Some("CmdOrCtrl+Shift+N"), Some(MenuSection::Shell)),
+    action("split_horizontal", "Split Right", Some("CmdOrCtrl+D"), Some(MenuSection::Shell)),
+    action("split_vertical", "Split Down", Some("CmdOrCtrl+Shift+D"), Some(MenuSection::Shell)),
+    action("next_tab", "Next Tab", Some("CmdOrCtrl+Shift+]"), Some(MenuSection::Shell)),
+    action("prev_tab", "Previous Tab", Some("CmdOrCtrl+Shift+["), Some(MenuSection::Shell)),
+    action("close_pane", "Close Pane", Some("CmdOrCtrl+W"), Some(MenuSection::Shell)),
+    action("toggle_minimap", "Toggle Minimap", Some("CmdOrCtrl+Shift+M"), Some(MenuSection::View)),
+    action("pin_minimap", "Pin to Top", None, Some(MenuSection::View)),
+    action("reset_minimap", "Reset Position", None, Some(MenuSection::View)),
+    action("focus_previous_pane", "Focus Previous Pane", Some("CmdOrCtrl+["), None),
+    action("focus_next_pane", "Focus Next Pane", Some("CmdOrCtrl+]"), None),
+    action("find", "Find", Some("CmdOrCtrl+F"), None),
+    action("zoom_in", "Increase Font Size", Some("CmdOrCtrl+="), None),
+    action("zoom_out", "Decrease Font Size", Some("CmdOrCtrl+-"), None),
+    action("zoom_reset", "Reset Font Size", Some("CmdOrCtrl+0"), None),
+];
+
+/// Chords taken by the standard App/Edit/Window menu items
+const RESERVED_CHORDS: &[(&str, &str)] = &[
+    ("CmdOrCtrl+Q", "Quit"),
+    ("CmdOrCtrl+H", "Hide"),
+    ("CmdOrCtrl+Alt+H", "Hide Others"),
+    ("CmdOrCtrl+Z", "Undo"),
+    ("CmdOrCtrl+Shift+Z", "Redo"),
+    ("CmdOrCtrl+X", "Cut"),
+    ("CmdOrCtrl+C", "Copy"),
+    ("CmdOrCtrl+V", "Paste"),
+    ("CmdOrCtrl+A", "Select All"),
+    ("CmdOrCtrl+M", "Minimize"),
+];
+
+pub fn find_action(id: &str) -> Option<&'static ActionDef> {
+    ACTIONS.iter().find(|action| action.id == id)
+}
+
+/// Canonical form used to compare chords: modifiers resolved for this
+/// platform (CmdOrCtrl is Cmd on macOS, Ctrl elsewhere) in a fixed order,
+/// key uppercased. `cmd+shift+d` and `Shift+CmdOrCtrl+D` compare equal on macOS.
+pub fn normalize_chord(chord: &str) -> String {
+    let parts: Vec<&str> = chord.split('+').map(str::trim).collect();
+    let Some((key, modifiers)) = parts.split_last() else {
+        return String::new();
+    };
+
+    let (mut cmd, mut ctrl, mut alt, mut shift, mut sup) = (false, false, false, false, false);
+    for modifier in modifiers {
+        match modifier.to_lowercase().as_str() {
+            "cmd" | "command" => cmd = true,
+            "ctrl" | "control" => ctrl = true,
+            "cmdorctrl" | "commandorcontrol" => {
+                if cfg!(target_os = "macos") {
+                    cmd = true
+                } else {
+                    ctrl = true
+                }
+            }
+            "alt" | "option" => alt = true,
+            "shift" => shift = true,
+            "super" | "meta" => {
+                if cfg!(target_os = "macos") {
+                    cmd = true
+                } else {
+                    sup = true
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut normalized = Vec::new();
+    for (set, name) in [(cmd, "Cmd"), (ctrl, "Ctrl"), (alt, "Alt"), (shift, "Shift"), (sup, "Super")] {
+        if set {
+            normalized.push(name.to_string());
+        }
+    }
+    normalized.push(key.to_uppercase());
+    normalized.join("+")
+}
+
+/// Where a binding's chord came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BindingSource {
+    Default,
+    User,
+    Config,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Keybinding {
+    pub action: String,
+    pub label: String,
+    /// None when the action is unbound
+    pub chord: Option<String>,
+    pub default_chord: Option<String>,
+    pub source: BindingSource,
+    /// Handled by a native menu accelerator rather than the frontend
+    pub menu: bool,
+    /// Chord clashes with another binding; it is left unassigned until fixed
+    pub conflict: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeybindingConflict {
+    pub chord: String,
+    pub actions: Vec<String>,
+    /// Standard menu item that already owns the chord, e.g. "Copy"
+    pub reserved_by: Option<String>,
+}
+
+/// Resolved action -> chord table sent to the frontend and used for menus
+#[derive(Debug, Clone, Serialize)]
+pub struct Keymap {
+    pub bindings: Vec<Keybinding>,
+    pub conflicts: Vec<KeybindingConflict>,
+}
+
+impl Keymap {
+    /// Layer user bindings (SQLite) and config.toml `[keybindings]` over the
+    /// defaults. An empty chord unbinds an action; unknown actions are ignored.
+    pub fn resolve(user: &BTreeMap<String, String>, config: &BTreeMap<String, String>) -> Self {
+        let mut bindings: Vec<Keybinding> = ACTIONS
+            .iter()
+            .map(|action| {
+                let (chord, source) = match (config.get(action.id), user.get(action.id)) {
+                    (Some(chord), _) => (Some(chord.as_str()), BindingSource::Config),
+                    (None, Some(chord)) => (Some(chord.as_str()), BindingSource::User),
+                    (None, None) => (action.default_chord, BindingSource::Default),
+                };
+                Keybinding {
+                    action: action.id.to_string(),
+                    label: action.label.to_string(),
+                    chord: chord
+                        .map(str::trim)
+                        .filter(|chord| !chord.is_empty() && validate_chord(chord).is_ok())
+                        .map(String::from),
+                    default_chord: action.default_chord.map(String::from),
+                    source,
+                    menu: action.menu.is_some(),
+                    conflict: false,
+                }
+            })
+            .collect();
+
+        let mut by_chord: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, binding) in bindings.iter().enumerate() {
+            if let Some(chord) = &binding.chord {
+                by_chord.entry(normalize_chord(chord)).or_default().push(i);
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for (chord, indices) in by_chord {
+            let reserved_by = RESERVED_CHORDS
+                .iter()
+                .find(|(reserved, _)| normalize_chord(reserved) == chord)
+                .map(|(_, label)| label.to_string());
+            if indices.len() < 2 && reserved_by.is_none() {
+                continue;
+            }
+            for &i in &indices {
+                bindings[i].conflict = true;
+            }
+            conflicts.push(KeybindingConflict {
+                chord,
+                actions: indices.iter().map(|&i| bindings[i].action.clone()).collect(),
+                reserved_by,
+            });
+        }
+
+        Keymap {
+            bindings,
+            conflicts,
+        }
+    }
+
+    /// Chord to use as a menu accelerator (None if unbound or conflicting)
+    pub fn accelerator(&self, action: &str) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action && !binding.conflict)
+            .and_then(|binding| binding.chord.as_deref())
+    }
+}
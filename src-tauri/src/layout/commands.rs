@@ -0,0 +1,455 @@
+use super::history::{record_layout, restore_layout, RestoredLayout};
+use super::model::TerminalLayout;
+use crate::storage::database::{LayoutHistoryRecord, LayoutSnapshotRecord};
+use crate::workspace::workspace_for_window;
+use crate::AppState;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::Window;
+
+/// One lock per workspace, held across each read-modify-write of its layout
+#[derive(Debug, Default)]
+pub struct LayoutLocks {
+    workspaces: HashMap<String, Arc<Mutex<()>>>,
+}
+
+impl LayoutLocks {
+    fn workspace(&mut self, workspace: &str) -> Arc<Mutex<()>> {
+        self.workspaces
+            .entry(workspace.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+/// Run `edit` with the workspace's layout lock held, so concurrent edits
+/// (page updates from several webviews, tab commands, the frontend saving)
+/// don't overwrite each other's changes
+pub(crate) fn with_layout_lock<T>(
+    state: &AppState,
+    workspace: &str,
+    edit: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let lock = state
+        .layout_locks
+        .lock()
+        .map_err(|e| format!("Failed to lock layouts: {}", e))?
+        .workspace(workspace);
+    let _guard = lock
+        .lock()
+        .map_err(|e| format!("Failed to lock layout: {}", e))?;
+    edit()
+}
+
+/// IDs of the sessions currently held by the PTY manager
+fn live_session_ids(state: &AppState) -> Result<HashSet<String>, String> {
+    let manager = state
+        .pty_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock PTY manager: {}", e))?;
+
+    Ok(manager.session_ids())
+}
+
+/// Workspace named by a command, defaulting to the calling window's
+fn target_workspace(window: &Window, workspace: Option<String>) -> String {
+    workspace.unwrap_or_else(|| workspace_for_window(window.label()))
+}
+
+/// Load a workspace's saved layout, migrating it to the current version
+pub(crate) fn load_layout(
+    state: &AppState,
+    workspace: &str,
+) -> Result<Option<TerminalLayout>, String> {
+    let json = state
+        .db
+        .get_workspace_layout(workspace)
+        .map_err(|e| format!("Failed to get layout: {}", e))?;
+
+    json.map(|json| TerminalLayout::from_json(&json))
+        .transpose()
+        .map_err(|e| format!("Failed to load layout: {}", e))
+}
+
+/// Validate a layout against live sessions, persist it and record it in the
+/// workspace history
+pub(crate) fn store_layout(
+    state: &AppState,
+    workspace: &str,
+    layout: &TerminalLayout,
+) -> Result<(), String> {
+    let sessions = live_session_ids(state)?;
+    layout
+        .validate(&sessions)
+        .map_err(|e| format!("Invalid layout: {}", e))?;
+
+    let json = layout.to_json().map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_workspace_layout(workspace, &json)
+        .map_err(|e| format!("Failed to save layout: {}", e))?;
+
+    record_layout(state, workspace, layout)
+}
+
+/// Write a webview pane's current URL and/or title into the saved layout.
+/// Page changes aren't layout edits, so no history entry is recorded.
+pub(crate) fn record_webview_page(
+    state: &AppState,
+    workspace: &str,
+    node_id: &str,
+    url: Option<&str>,
+    title: Option<&str>,
+) -> Result<(), String> {
+    with_layout_lock(state, workspace, || {
+        let Some(mut layout) = load_layout(state, workspace)? else {
+            return Ok(());
+        };
+        if !layout.update_webview(node_id, url, title) {
+            return Ok(());
+        }
+
+        let json = layout.to_json().map_err(|e| e.to_string())?;
+        state
+            .db
+            .save_workspace_layout(workspace, &json)
+            .map_err(|e| format!("Failed to save layout: {}", e))
+    })
+}
+
+#[tauri::command]
+pub async fn pty_save_layout(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    layout_json: String,
+) -> Result<(), String> {
+    let layout = TerminalLayout::from_json(&layout_json).map_err(|e| e.to_string())?;
+    let workspace = workspace_for_window(window.label());
+    with_layout_lock(&state, &workspace, || {
+        store_layout(&state, &workspace, &layout)
+    })
+}
+
+#[tauri::command]
+pub async fn pty_get_layout(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+) -> Result<Option<String>, String> {
+    load_layout(&state, &workspace_for_window(window.label()))?
+        .map(|layout| layout.to_json().map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Get the saved layout with terminals for dead or unknown sessions removed
+#[tauri::command]
+pub async fn layout_restore(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+) -> Result<Option<TerminalLayout>, String> {
+    let Some(mut layout) = load_layout(&state, &workspace_for_window(window.label()))? else {
+        return Ok(None);
+    };
+
+    let sessions = live_session_ids(&state)?;
+    let removed = layout.retain_sessions(&sessions);
+    if !removed.is_empty() {
+        tracing::info!("Dropped {} stale sessions from restored layout", removed.len());
+    }
+
+    Ok(Some(layout))
+}
+
+/// Point saved terminal nodes at new session IDs (old ID -> new ID).
+/// Returns the number of nodes updated.
+#[tauri::command]
+pub async fn layout_remap_sessions(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    mapping: HashMap<String, String>,
+) -> Result<usize, String> {
+    let workspace = workspace_for_window(window.label());
+    with_layout_lock(&state, &workspace, || {
+        let Some(mut layout) = load_layout(&state, &workspace)? else {
+            return Ok(0);
+        };
+
+        let updated = layout.remap_sessions(&mapping);
+        if updated > 0 {
+            // Anything still pointing at a dead session would fail validation
+            layout.retain_sessions(&live_session_ids(&state)?);
+            store_layout(&state, &workspace, &layout)?;
+        }
+        Ok(updated)
+    })
+}
+
+/// Session IDs referenced by the saved layout
+#[tauri::command]
+pub async fn layout_session_ids(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+) -> Result<Vec<String>, String> {
+    Ok(load_layout(&state, &workspace_for_window(window.label()))?
+        .map(|layout| layout.session_ids())
+        .unwrap_or_default())
+}
+
+/// Apply a tab command to the calling window's layout (an empty one if
+/// nothing has been saved yet) under the workspace's layout lock. `edit`
+/// gets the workspace name and saves the layout itself.
+fn edit_window_layout(
+    state: &AppState,
+    window: &Window,
+    edit: impl FnOnce(&str, &mut TerminalLayout) -> Result<(), String>,
+) -> Result<TerminalLayout, String> {
+    let workspace = workspace_for_window(window.label());
+    with_layout_lock(state, &workspace, || {
+        let mut layout = load_layout(state, &workspace)?.unwrap_or_default();
+        edit(&workspace, &mut layout)?;
+        Ok(layout)
+    })
+}
+
+/// Add an empty tab after the active one and make it active. The frontend
+/// creates the tab's first terminal.
+#[tauri::command]
+pub async fn layout_tab_new(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    title: Option<String>,
+) -> Result<TerminalLayout, String> {
+    edit_window_layout(&state, &window, |workspace, layout| {
+        layout.add_tab(title.filter(|title| !title.trim().is_empty()));
+        store_layout(&state, workspace, layout)
+    })
+}
+
+/// Close a tab and end the sessions it holds. Closing the last tab leaves
+/// an empty one so the window always has a tab.
+#[tauri::command]
+pub async fn layout_tab_close(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    tab_id: String,
+) -> Result<TerminalLayout, String> {
+    edit_window_layout(&state, &window, |workspace, layout| {
+        let tab = layout.close_tab(&tab_id).map_err(|e| e.to_string())?;
+        if layout.tabs.is_empty() {
+            layout.add_tab(None);
+        }
+
+        // Save first: if that fails the tab is still in the stored layout,
+        // so its sessions must keep running
+        store_layout(&state, workspace, layout)?;
+
+        let mut manager = state
+            .pty_manager
+            .lock()
+            .map_err(|e| format!("Failed to lock PTY manager: {}", e))?;
+        for session_id in tab.session_ids() {
+            if let Err(e) = manager.kill_session(&session_id) {
+                tracing::warn!("{}", e);
+                continue;
+            }
+            if let Err(e) = state.db.update_terminal_session_end(&session_id, None) {
+                tracing::error!("Failed to update session in database: {}", e);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Move a tab to a new position in the tab bar
+#[tauri::command]
+pub async fn layout_tab_move(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    tab_id: String,
+    index: usize,
+) -> Result<TerminalLayout, String> {
+    edit_window_layout(&state, &window, |workspace, layout| {
+        layout.move_tab(&tab_id, index).map_err(|e| e.to_string())?;
+        store_layout(&state, workspace, layout)
+    })
+}
+
+/// Set a tab's title; an empty title goes back to the automatic one
+#[tauri::command]
+pub async fn layout_tab_rename(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    tab_id: String,
+    title: String,
+) -> Result<TerminalLayout, String> {
+    edit_window_layout(&state, &window, |workspace, layout| {
+        layout
+            .rename_tab(&tab_id, &title)
+            .map_err(|e| e.to_string())?;
+        store_layout(&state, workspace, layout)
+    })
+}
+
+/// Switch tabs. Not recorded in history: undo shouldn't step through tab
+/// switches.
+#[tauri::command]
+pub async fn layout_tab_activate(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    tab_id: String,
+) -> Result<TerminalLayout, String> {
+    edit_window_layout(&state, &window, |workspace, layout| {
+        layout.activate_tab(&tab_id).map_err(|e| e.to_string())?;
+
+        let json = layout.to_json().map_err(|e| e.to_string())?;
+        state
+            .db
+            .save_workspace_layout(workspace, &json)
+            .map_err(|e| format!("Failed to save layout: {}", e))
+    })
+}
+
+/// Tab the workspace is showing, kept across undo and redo
+fn current_active_tab(state: &AppState, workspace: &str) -> Result<Option<String>, String> {
+    Ok(load_layout(state, workspace)?.and_then(|layout| layout.active_tab))
+}
+
+/// Step the layout back one history entry
+#[tauri::command]
+pub async fn layout_undo(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+) -> Result<Option<RestoredLayout>, String> {
+    let workspace = target_workspace(&window, workspace);
+    with_layout_lock(&state, &workspace, || {
+        let active_tab = current_active_tab(&state, &workspace)?;
+        let Some((undone, current)) = state
+            .db
+            .undo_layout_history(&workspace)
+            .map_err(|e| format!("Failed to undo layout: {}", e))?
+        else {
+            return Ok(None);
+        };
+
+        restore_layout(
+            &state,
+            &workspace,
+            &current.layout_json,
+            &undone.killed_sessions,
+            active_tab.as_deref(),
+        )
+        .map(Some)
+    })
+}
+
+/// Re-apply the most recently undone layout
+#[tauri::command]
+pub async fn layout_redo(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+) -> Result<Option<RestoredLayout>, String> {
+    let workspace = target_workspace(&window, workspace);
+    with_layout_lock(&state, &workspace, || {
+        let active_tab = current_active_tab(&state, &workspace)?;
+        let Some(next) = state
+            .db
+            .redo_layout_history(&workspace)
+            .map_err(|e| format!("Failed to redo layout: {}", e))?
+        else {
+            return Ok(None);
+        };
+
+        restore_layout(
+            &state,
+            &workspace,
+            &next.layout_json,
+            &[],
+            active_tab.as_deref(),
+        )
+        .map(Some)
+    })
+}
+
+/// List layout history entries, newest first
+#[tauri::command]
+pub async fn layout_history(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+) -> Result<Vec<LayoutHistoryRecord>, String> {
+    state
+        .db
+        .list_layout_history(&target_workspace(&window, workspace))
+        .map_err(|e| format!("Failed to list layout history: {}", e))
+}
+
+/// Save the current layout under a name
+#[tauri::command]
+pub async fn layout_snapshot_save(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+    name: String,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Snapshot name cannot be empty".to_string());
+    }
+
+    let workspace = target_workspace(&window, workspace);
+    let layout = load_layout(&state, &workspace)?.ok_or("No layout to snapshot")?;
+    let json = layout.to_json().map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_layout_snapshot(&workspace, name, &json)
+        .map_err(|e| format!("Failed to save snapshot: {}", e))
+}
+
+#[tauri::command]
+pub async fn layout_snapshot_list(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+) -> Result<Vec<LayoutSnapshotRecord>, String> {
+    state
+        .db
+        .list_layout_snapshots(&target_workspace(&window, workspace))
+        .map_err(|e| format!("Failed to list snapshots: {}", e))
+}
+
+/// Restore a named snapshot as the current layout (recorded in history so it
+/// can be undone)
+#[tauri::command]
+pub async fn layout_snapshot_restore(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+    name: String,
+) -> Result<RestoredLayout, String> {
+    let workspace = target_workspace(&window, workspace);
+    let snapshot = state
+        .db
+        .get_layout_snapshot(&workspace, &name)
+        .map_err(|e| format!("Failed to get snapshot: {}", e))?
+        .ok_or_else(|| format!("Snapshot not found: {}", name))?;
+
+    with_layout_lock(&state, &workspace, || {
+        let restored = restore_layout(&state, &workspace, &snapshot.layout_json, &[], None)?;
+        record_layout(&state, &workspace, &restored.layout)?;
+        Ok(restored)
+    })
+}
+
+#[tauri::command]
+pub async fn layout_snapshot_delete(
+    state: tauri::State<'_, AppState>,
+    window: Window,
+    workspace: Option<String>,
+    name: String,
+) -> Result<bool, String> {
+    state
+        .db
+        .delete_layout_snapshot(&target_workspace(&window, workspace), &name)
+        .map_err(|e| format!("Failed to delete snapshot: {}", e))
+}
@@ -0,0 +1,100 @@
+use super::model::TerminalLayout;
+use crate::storage::database::TerminalSessionRecord;
+use crate::AppState;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Workspace of the main window
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Maximum history entries kept per workspace
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+/// A layout brought back by undo, redo or a snapshot restore
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoredLayout {
+    pub layout: TerminalLayout,
+    /// Sessions the layout references that are no longer running, so the
+    /// frontend can offer to respawn them (then call `layout_remap_sessions`)
+    pub respawn: Vec<TerminalSessionRecord>,
+}
+
+/// Append a layout to the workspace history, noting which sessions it dropped
+/// compared to the previous entry
+pub(crate) fn record_layout(
+    state: &AppState,
+    workspace: &str,
+    layout: &TerminalLayout,
+) -> Result<(), String> {
+    let previous = state
+        .db
+        .get_current_layout_history(workspace)
+        .map_err(|e| format!("Failed to read layout history: {}", e))?
+        .and_then(|record| TerminalLayout::from_json(&record.layout_json).ok());
+
+    let killed_sessions = match previous {
+        Some(previous) => {
+            let current: HashSet<String> = layout.session_ids().into_iter().collect();
+            previous
+                .session_ids()
+                .into_iter()
+                .filter(|id| !current.contains(id))
+                .filter_map(|id| state.db.get_terminal_session(&id).ok().flatten())
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    let json = layout.to_json().map_err(|e| e.to_string())?;
+    state
+        .db
+        .push_layout_history(workspace, &json, &killed_sessions, MAX_HISTORY_ENTRIES)
+        .map_err(|e| format!("Failed to record layout history: {}", e))?;
+    Ok(())
+}
+
+/// Make `layout_json` the workspace's saved layout without validating
+/// sessions (dead ones are reported in `respawn` instead). `known` supplies
+/// session records that may already have been cleaned out of
+/// `terminal_sessions`. `active_tab` stays active if the restored layout
+/// still has it.
+pub(crate) fn restore_layout(
+    state: &AppState,
+    workspace: &str,
+    layout_json: &str,
+    known: &[TerminalSessionRecord],
+    active_tab: Option<&str>,
+) -> Result<RestoredLayout, String> {
+    let mut layout = TerminalLayout::from_json(layout_json).map_err(|e| e.to_string())?;
+    if let Some(tab_id) = active_tab {
+        // Tab switches aren't in history, so the entry's own is usually stale
+        layout.activate_tab(tab_id).ok();
+    }
+    let json = layout.to_json().map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_workspace_layout(workspace, &json)
+        .map_err(|e| format!("Failed to save layout: {}", e))?;
+
+    let live = state
+        .pty_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock PTY manager: {}", e))?
+        .session_ids();
+
+    let respawn = layout
+        .session_ids()
+        .into_iter()
+        .filter(|id| !live.contains(id))
+        .filter_map(|id| {
+            state
+                .db
+                .get_terminal_session(&id)
+                .ok()
+                .flatten()
+                .or_else(|| known.iter().find(|record| record.id == id).cloned())
+        })
+        .collect();
+
+    Ok(RestoredLayout { layout, respawn })
+}
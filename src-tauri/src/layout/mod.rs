@@ -0,0 +1,7 @@
+pub mod commands;
+pub mod history;
+pub mod model;
+
+pub use commands::*;
+pub use history::*;
+pub use model::*;
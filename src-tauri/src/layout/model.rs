@@ -0,0 +1,862 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+/// Layout schema version currently written by the frontend
+pub const LAYOUT_VERSION: u32 = 2;
+
+/// Allowed drift when checking that split sizes add up to 100
+/// (drag-resizing accumulates floating point error)
+const SIZE_TOLERANCE: f64 = 0.5;
+
+/// Upgrades a raw layout value by one version
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Ordered layout upgrades. Entry `i` migrates a layout from version `i` to `i + 1`.
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Errors raised while loading or validating a layout
+#[derive(Debug, Error)]
+pub enum LayoutError {
+    #[error("Invalid layout JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Layout version {found} is newer than supported version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("Layout migration to version {version} failed: {reason}")]
+    Migration { version: u32, reason: String },
+    #[error("Duplicate node id: {0}")]
+    DuplicateId(String),
+    #[error("Split {0} has no children")]
+    EmptySplit(String),
+    #[error("Split {id} has {children} children but {sizes} sizes")]
+    SizeCountMismatch {
+        id: String,
+        children: usize,
+        sizes: usize,
+    },
+    #[error("Split {id} sizes sum to {sum}, expected 100")]
+    InvalidSizes { id: String, sum: f64 },
+    #[error("Tab not found: {0}")]
+    UnknownTab(String),
+    #[error("Node {node_id} references unknown session {session_id}")]
+    UnknownSession { node_id: String, session_id: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// Leaf node showing a PTY session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalNode {
+    pub id: String,
+    pub session_id: String,
+}
+
+/// Leaf node showing a child webview
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebviewNode {
+    pub id: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Browser profile whose cookies and storage the pane uses (the default
+    /// profile if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Throwaway storage, gone when the pane closes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub private: bool,
+}
+
+/// Container node; `sizes` are percentages matching `children` by index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitNode {
+    pub id: String,
+    pub direction: SplitDirection,
+    pub children: Vec<LayoutNode>,
+    pub sizes: Vec<f64>,
+}
+
+/// Layout tree node (mirrors `LayoutNode` in src/lib/types/terminal.ts)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LayoutNode {
+    Terminal(TerminalNode),
+    Webview(WebviewNode),
+    Split(SplitNode),
+}
+
+/// A tab holding its own split tree (mirrors `LayoutTab` in src/lib/types/terminal.ts)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutTab {
+    pub id: String,
+    /// User-given title; the frontend shows the focused pane's title otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub root: Option<LayoutNode>,
+}
+
+impl LayoutTab {
+    pub fn new(title: Option<String>) -> Self {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            id: id[..8].to_string(),
+            title,
+            root: None,
+        }
+    }
+
+    /// Session IDs of the tab's terminals in depth-first order
+    pub fn session_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        if let Some(root) = &self.root {
+            root.visit(&mut |node| {
+                if let LayoutNode::Terminal(terminal) = node {
+                    ids.push(terminal.session_id.clone());
+                }
+            });
+        }
+        ids
+    }
+}
+
+/// Root of a saved layout: tabs in display order
+/// (mirrors `TerminalLayout` in src/lib/types/terminal.ts)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalLayout {
+    pub tabs: Vec<LayoutTab>,
+    #[serde(default)]
+    pub active_tab: Option<String>,
+    pub version: u32,
+}
+
+impl Default for TerminalLayout {
+    fn default() -> Self {
+        Self {
+            tabs: Vec::new(),
+            active_tab: None,
+            version: LAYOUT_VERSION,
+        }
+    }
+}
+
+impl LayoutNode {
+    pub fn id(&self) -> &str {
+        match self {
+            LayoutNode::Terminal(n) => &n.id,
+            LayoutNode::Webview(n) => &n.id,
+            LayoutNode::Split(n) => &n.id,
+        }
+    }
+
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a LayoutNode)) {
+        f(self);
+        if let LayoutNode::Split(split) = self {
+            for child in &split.children {
+                child.visit(f);
+            }
+        }
+    }
+
+    fn visit_mut(&mut self, f: &mut impl FnMut(&mut LayoutNode)) {
+        f(self);
+        if let LayoutNode::Split(split) = self {
+            for child in &mut split.children {
+                child.visit_mut(f);
+            }
+        }
+    }
+
+    /// Drop terminal nodes rejected by `keep`, collapsing splits left with a
+    /// single child and renormalizing sizes. Returns `None` if nothing remains.
+    fn retain_terminals(self, keep: &impl Fn(&str) -> bool) -> Option<LayoutNode> {
+        match self {
+            LayoutNode::Terminal(ref node) if !keep(&node.session_id) => None,
+            LayoutNode::Split(split) => {
+                let mut children = Vec::with_capacity(split.children.len());
+                let mut sizes = Vec::with_capacity(split.sizes.len());
+                for (child, size) in split.children.into_iter().zip(split.sizes) {
+                    if let Some(child) = child.retain_terminals(keep) {
+                        children.push(child);
+                        sizes.push(size);
+                    }
+                }
+
+                match children.len() {
+                    0 => None,
+                    1 => children.pop(),
+                    _ => {
+                        let total: f64 = sizes.iter().sum();
+                        if total > 0.0 {
+                            for size in &mut sizes {
+                                *size = *size / total * 100.0;
+                            }
+                        }
+                        Some(LayoutNode::Split(SplitNode {
+                            children,
+                            sizes,
+                            ..split
+                        }))
+                    }
+                }
+            }
+            node => Some(node),
+        }
+    }
+}
+
+impl TerminalLayout {
+    /// Parse a stored layout, applying any pending version migrations
+    pub fn from_json(json: &str) -> Result<Self, LayoutError> {
+        let mut value: Value = serde_json::from_str(json)?;
+        migrate(&mut value)?;
+        let layout: TerminalLayout = serde_json::from_value(value)?;
+        layout.validate_structure()?;
+        Ok(layout)
+    }
+
+    pub fn to_json(&self) -> Result<String, LayoutError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Check tree invariants: unique IDs, non-empty splits, sizes matching
+    /// children and summing to 100
+    pub fn validate_structure(&self) -> Result<(), LayoutError> {
+        let mut tab_ids = HashSet::new();
+        for tab in &self.tabs {
+            if !tab_ids.insert(tab.id.as_str()) {
+                return Err(LayoutError::DuplicateId(tab.id.clone()));
+            }
+        }
+        if let Some(active) = &self.active_tab {
+            if !tab_ids.contains(active.as_str()) {
+                return Err(LayoutError::UnknownTab(active.clone()));
+            }
+        }
+
+        // Node IDs are unique across tabs so panes can be found without
+        // knowing their tab
+        let mut seen = HashSet::new();
+        let mut result = Ok(());
+        self.visit(&mut |node| {
+            if result.is_err() {
+                return;
+            }
+            if !seen.insert(node.id()) {
+                result = Err(LayoutError::DuplicateId(node.id().to_string()));
+                return;
+            }
+            if let LayoutNode::Split(split) = node {
+                result = validate_split(split);
+            }
+        });
+        result
+    }
+
+    /// Full validation used on save: structure plus every terminal node must
+    /// reference a session in `sessions`
+    pub fn validate(&self, sessions: &HashSet<String>) -> Result<(), LayoutError> {
+        self.validate_structure()?;
+
+        for node in self.terminal_nodes() {
+            if !sessions.contains(&node.session_id) {
+                return Err(LayoutError::UnknownSession {
+                    node_id: node.id.clone(),
+                    session_id: node.session_id.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Every node of every tab, tab by tab in depth-first order
+    fn visit<'a>(&'a self, f: &mut impl FnMut(&'a LayoutNode)) {
+        for root in self.tabs.iter().filter_map(|tab| tab.root.as_ref()) {
+            root.visit(f);
+        }
+    }
+
+    fn visit_mut(&mut self, f: &mut impl FnMut(&mut LayoutNode)) {
+        for root in self.tabs.iter_mut().filter_map(|tab| tab.root.as_mut()) {
+            root.visit_mut(f);
+        }
+    }
+
+    /// All terminal nodes, tab by tab in depth-first order
+    pub fn terminal_nodes(&self) -> Vec<&TerminalNode> {
+        let mut nodes = Vec::new();
+        self.visit(&mut |node| {
+            if let LayoutNode::Terminal(terminal) = node {
+                nodes.push(terminal);
+            }
+        });
+        nodes
+    }
+
+    /// Session IDs referenced by the layout, tab by tab in depth-first order
+    pub fn session_ids(&self) -> Vec<String> {
+        self.terminal_nodes()
+            .into_iter()
+            .map(|node| node.session_id.clone())
+            .collect()
+    }
+
+    pub fn find_node(&self, node_id: &str) -> Option<&LayoutNode> {
+        let mut found = None;
+        self.visit(&mut |node| {
+            if found.is_none() && node.id() == node_id {
+                found = Some(node);
+            }
+        });
+        found
+    }
+
+    pub fn find_session(&self, session_id: &str) -> Option<&TerminalNode> {
+        self.terminal_nodes()
+            .into_iter()
+            .find(|node| node.session_id == session_id)
+    }
+
+    /// Point terminal nodes at new session IDs (e.g. after respawning).
+    /// Returns how many nodes were updated.
+    pub fn remap_sessions(&mut self, mapping: &HashMap<String, String>) -> usize {
+        let mut updated = 0;
+        self.visit_mut(&mut |node| {
+            if let LayoutNode::Terminal(terminal) = node {
+                if let Some(new_id) = mapping.get(&terminal.session_id) {
+                    terminal.session_id = new_id.clone();
+                    updated += 1;
+                }
+            }
+        });
+        updated
+    }
+
+    /// Record the page a webview node is showing. Returns false if there is no
+    /// webview node with that ID.
+    pub fn update_webview(
+        &mut self,
+        node_id: &str,
+        url: Option<&str>,
+        title: Option<&str>,
+    ) -> bool {
+        let mut found = false;
+        self.visit_mut(&mut |node| {
+            if let LayoutNode::Webview(webview) = node {
+                if webview.id == node_id {
+                    if let Some(url) = url {
+                        webview.url = url.to_string();
+                    }
+                    if let Some(title) = title {
+                        webview.title = (!title.is_empty()).then(|| title.to_string());
+                    }
+                    found = true;
+                }
+            }
+        });
+        found
+    }
+
+    /// Remove terminal nodes whose session is not in `sessions`, then any
+    /// tab left empty by that. Returns the session IDs that were dropped.
+    pub fn retain_sessions(&mut self, sessions: &HashSet<String>) -> Vec<String> {
+        let removed: Vec<String> = self
+            .session_ids()
+            .into_iter()
+            .filter(|id| !sessions.contains(id))
+            .collect();
+
+        if !removed.is_empty() {
+            let keep = |id: &str| sessions.contains(id);
+            self.tabs.retain_mut(|tab| match tab.root.take() {
+                Some(root) => {
+                    tab.root = root.retain_terminals(&keep);
+                    tab.root.is_some()
+                }
+                None => true,
+            });
+            self.fix_active_tab(None);
+        }
+        removed
+    }
+
+    // ========== Tabs ==========
+
+    fn tab_index(&self, tab_id: &str) -> Result<usize, LayoutError> {
+        self.tabs
+            .iter()
+            .position(|tab| tab.id == tab_id)
+            .ok_or_else(|| LayoutError::UnknownTab(tab_id.to_string()))
+    }
+
+    pub fn find_tab(&self, tab_id: &str) -> Option<&LayoutTab> {
+        self.tabs.iter().find(|tab| tab.id == tab_id)
+    }
+
+    /// Tab whose tree contains the session's terminal
+    pub fn tab_for_session(&self, session_id: &str) -> Option<&LayoutTab> {
+        self.tabs
+            .iter()
+            .find(|tab| tab.session_ids().iter().any(|id| id == session_id))
+    }
+
+    /// Append an empty tab after the active one and make it active
+    pub fn add_tab(&mut self, title: Option<String>) -> &LayoutTab {
+        let tab = LayoutTab::new(title);
+        let index = self
+            .active_tab
+            .as_deref()
+            .and_then(|id| self.tab_index(id).ok())
+            .map_or(self.tabs.len(), |i| i + 1);
+        self.active_tab = Some(tab.id.clone());
+        self.tabs.insert(index, tab);
+        &self.tabs[index]
+    }
+
+    /// Remove a tab. The next tab (or the previous one, for the last tab)
+    /// becomes active if the closed tab was.
+    pub fn close_tab(&mut self, tab_id: &str) -> Result<LayoutTab, LayoutError> {
+        let index = self.tab_index(tab_id)?;
+        let tab = self.tabs.remove(index);
+        self.fix_active_tab(Some(index));
+        Ok(tab)
+    }
+
+    /// Move a tab to `index` (clamped to the end)
+    pub fn move_tab(&mut self, tab_id: &str, index: usize) -> Result<(), LayoutError> {
+        let from = self.tab_index(tab_id)?;
+        let tab = self.tabs.remove(from);
+        let index = index.min(self.tabs.len());
+        self.tabs.insert(index, tab);
+        Ok(())
+    }
+
+    /// Set or clear (with an empty title) a tab's title
+    pub fn rename_tab(&mut self, tab_id: &str, title: &str) -> Result<(), LayoutError> {
+        let index = self.tab_index(tab_id)?;
+        let title = title.trim();
+        self.tabs[index].title = (!title.is_empty()).then(|| title.to_string());
+        Ok(())
+    }
+
+    pub fn activate_tab(&mut self, tab_id: &str) -> Result<(), LayoutError> {
+        self.tab_index(tab_id)?;
+        self.active_tab = Some(tab_id.to_string());
+        Ok(())
+    }
+
+    /// Point `active_tab` at an existing tab after tabs were removed,
+    /// preferring the one now at `removed_index`
+    fn fix_active_tab(&mut self, removed_index: Option<usize>) {
+        let valid = self
+            .active_tab
+            .as_deref()
+            .is_some_and(|id| self.tabs.iter().any(|tab| tab.id == id));
+        if valid {
+            return;
+        }
+        let index = removed_index
+            .unwrap_or(0)
+            .min(self.tabs.len().saturating_sub(1));
+        self.active_tab = self.tabs.get(index).map(|tab| tab.id.clone());
+    }
+}
+
+fn validate_split(split: &SplitNode) -> Result<(), LayoutError> {
+    if split.children.is_empty() {
+        return Err(LayoutError::EmptySplit(split.id.clone()));
+    }
+    if split.children.len() != split.sizes.len() {
+        return Err(LayoutError::SizeCountMismatch {
+            id: split.id.clone(),
+            children: split.children.len(),
+            sizes: split.sizes.len(),
+        });
+    }
+
+    let sum: f64 = split.sizes.iter().sum();
+    if split.sizes.iter().any(|s| !s.is_finite() || *s < 0.0) || (sum - 100.0).abs() > SIZE_TOLERANCE
+    {
+        return Err(LayoutError::InvalidSizes {
+            id: split.id.clone(),
+            sum,
+        });
+    }
+    Ok(())
+}
+
+/// Upgrade a raw layout value to `LAYOUT_VERSION` in place
+fn migrate(value: &mut Value) -> Result<(), LayoutError> {
+    let mut version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0) as u32;
+
+    if version > LAYOUT_VERSION {
+        return Err(LayoutError::UnsupportedVersion {
+            found: version,
+            supported: LAYOUT_VERSION,
+        });
+    }
+
+    while version < LAYOUT_VERSION {
+        let step = MIGRATIONS[version as usize];
+        step(value).map_err(|reason| LayoutError::Migration {
+            version: version + 1,
+            reason,
+        })?;
+        version += 1;
+        value["version"] = Value::from(version);
+    }
+    Ok(())
+}
+
+/// Unversioned layouts were either a bare node or `{ root }` without a version
+fn migrate_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    if value.is_null() || value.get("type").is_some() {
+        let root = value.take();
+        *value = serde_json::json!({ "root": root });
+    } else if !value.is_object() {
+        return Err("expected a layout object".to_string());
+    }
+    Ok(())
+}
+
+/// Single-tree layouts become one untitled tab
+fn migrate_v1_to_v2(value: &mut Value) -> Result<(), String> {
+    let Some(layout) = value.as_object_mut() else {
+        return Err("expected a layout object".to_string());
+    };
+    let root = layout.remove("root").unwrap_or(Value::Null);
+    layout.insert(
+        "tabs".to_string(),
+        serde_json::json!([{ "id": "tab-1", "root": root }]),
+    );
+    layout.insert("activeTab".to_string(), Value::from("tab-1"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// v0: a bare node tree, no version field
+    const V0_LAYOUT: &str = r#"{
+        "type": "split",
+        "id": "root",
+        "direction": "horizontal",
+        "sizes": [60, 40],
+        "children": [
+            { "type": "terminal", "id": "left", "sessionId": "s1" },
+            { "type": "webview", "id": "docs", "url": "https://example.com" }
+        ]
+    }"#;
+
+    /// v1: `{ root, version }` with a single tree
+    const V1_LAYOUT: &str = r#"{
+        "version": 1,
+        "root": {
+            "type": "split",
+            "id": "root",
+            "direction": "vertical",
+            "sizes": [33.3, 33.3, 33.4],
+            "children": [
+                { "type": "terminal", "id": "a", "sessionId": "s1" },
+                { "type": "terminal", "id": "b", "sessionId": "s2" },
+                { "type": "terminal", "id": "c", "sessionId": "s3" }
+            ]
+        }
+    }"#;
+
+    fn sessions(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn terminal(id: &str, session_id: &str) -> LayoutNode {
+        LayoutNode::Terminal(TerminalNode {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+        })
+    }
+
+    fn split(id: &str, children: Vec<LayoutNode>, sizes: Vec<f64>) -> LayoutNode {
+        LayoutNode::Split(SplitNode {
+            id: id.to_string(),
+            direction: SplitDirection::Horizontal,
+            children,
+            sizes,
+        })
+    }
+
+    fn layout(roots: Vec<Option<LayoutNode>>) -> TerminalLayout {
+        let tabs: Vec<LayoutTab> = roots
+            .into_iter()
+            .enumerate()
+            .map(|(i, root)| LayoutTab {
+                id: format!("tab-{}", i + 1),
+                title: None,
+                root,
+            })
+            .collect();
+        TerminalLayout {
+            active_tab: tabs.first().map(|tab| tab.id.clone()),
+            tabs,
+            version: LAYOUT_VERSION,
+        }
+    }
+
+    fn sizes_of(node: &LayoutNode) -> &[f64] {
+        match node {
+            LayoutNode::Split(split) => &split.sizes,
+            other => panic!("expected a split, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn v0_layout_migrates_to_one_tab() {
+        let layout = TerminalLayout::from_json(V0_LAYOUT).unwrap();
+        assert_eq!(layout.version, LAYOUT_VERSION);
+        assert_eq!(layout.tabs.len(), 1);
+        assert_eq!(layout.tabs[0].id, "tab-1");
+        assert_eq!(layout.active_tab.as_deref(), Some("tab-1"));
+        assert_eq!(layout.session_ids(), vec!["s1"]);
+        assert!(matches!(
+            layout.find_node("docs"),
+            Some(LayoutNode::Webview(webview)) if webview.url == "https://example.com"
+        ));
+    }
+
+    #[test]
+    fn v0_empty_layout_migrates_to_one_empty_tab() {
+        let layout = TerminalLayout::from_json("null").unwrap();
+        assert_eq!(layout.tabs.len(), 1);
+        assert!(layout.tabs[0].root.is_none());
+    }
+
+    #[test]
+    fn v1_layout_migrates_to_one_tab() {
+        let layout = TerminalLayout::from_json(V1_LAYOUT).unwrap();
+        assert_eq!(layout.version, LAYOUT_VERSION);
+        assert_eq!(layout.tabs.len(), 1);
+        assert_eq!(layout.active_tab.as_deref(), Some("tab-1"));
+        assert_eq!(layout.session_ids(), vec!["s1", "s2", "s3"]);
+        layout.validate(&sessions(&["s1", "s2", "s3"])).unwrap();
+    }
+
+    #[test]
+    fn current_layout_round_trips() {
+        let layout = TerminalLayout::from_json(V1_LAYOUT).unwrap();
+        let reloaded = TerminalLayout::from_json(&layout.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded, layout);
+    }
+
+    #[test]
+    fn newer_layout_version_is_refused() {
+        let json = format!(r#"{{ "version": {}, "tabs": [] }}"#, LAYOUT_VERSION + 1);
+        assert!(matches!(
+            TerminalLayout::from_json(&json),
+            Err(LayoutError::UnsupportedVersion { found, .. }) if found == LAYOUT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn non_object_v0_layout_fails_migration() {
+        assert!(matches!(
+            TerminalLayout::from_json("[]"),
+            Err(LayoutError::Migration { version: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn duplicate_node_ids_are_rejected() {
+        let json = r#"{
+            "type": "split", "id": "root", "direction": "horizontal", "sizes": [50, 50],
+            "children": [
+                { "type": "terminal", "id": "same", "sessionId": "s1" },
+                { "type": "terminal", "id": "same", "sessionId": "s2" }
+            ]
+        }"#;
+        assert!(matches!(
+            TerminalLayout::from_json(json),
+            Err(LayoutError::DuplicateId(id)) if id == "same"
+        ));
+    }
+
+    #[test]
+    fn duplicate_node_ids_across_tabs_are_rejected() {
+        let layout = layout(vec![
+            Some(terminal("pane", "s1")),
+            Some(terminal("pane", "s2")),
+        ]);
+        assert!(matches!(
+            layout.validate_structure(),
+            Err(LayoutError::DuplicateId(id)) if id == "pane"
+        ));
+    }
+
+    #[test]
+    fn duplicate_tab_ids_are_rejected() {
+        let mut layout = layout(vec![None, None]);
+        layout.tabs[1].id = "tab-1".to_string();
+        assert!(matches!(
+            layout.validate_structure(),
+            Err(LayoutError::DuplicateId(id)) if id == "tab-1"
+        ));
+    }
+
+    #[test]
+    fn unknown_active_tab_is_rejected() {
+        let mut layout = layout(vec![None]);
+        layout.active_tab = Some("missing".to_string());
+        assert!(matches!(
+            layout.validate_structure(),
+            Err(LayoutError::UnknownTab(id)) if id == "missing"
+        ));
+    }
+
+    #[test]
+    fn sizes_within_tolerance_are_accepted() {
+        for sizes in [vec![50.0, 50.0], vec![49.8, 50.6], vec![49.9, 49.7]] {
+            let layout = layout(vec![Some(split(
+                "root",
+                vec![terminal("a", "s1"), terminal("b", "s2")],
+                sizes.clone(),
+            ))]);
+            assert!(layout.validate_structure().is_ok(), "sizes {:?}", sizes);
+        }
+    }
+
+    #[test]
+    fn sizes_outside_tolerance_are_rejected() {
+        for sizes in [
+            vec![50.0, 50.6],
+            vec![49.0, 50.4],
+            vec![-10.0, 110.0],
+            vec![f64::NAN, 100.0],
+        ] {
+            let layout = layout(vec![Some(split(
+                "root",
+                vec![terminal("a", "s1"), terminal("b", "s2")],
+                sizes.clone(),
+            ))]);
+            assert!(
+                matches!(
+                    layout.validate_structure(),
+                    Err(LayoutError::InvalidSizes { .. })
+                ),
+                "sizes {:?}",
+                sizes
+            );
+        }
+    }
+
+    #[test]
+    fn split_shape_is_checked() {
+        let empty = layout(vec![Some(split("root", Vec::new(), Vec::new()))]);
+        assert!(matches!(
+            empty.validate_structure(),
+            Err(LayoutError::EmptySplit(id)) if id == "root"
+        ));
+
+        let mismatched = layout(vec![Some(split(
+            "root",
+            vec![terminal("a", "s1")],
+            vec![50.0, 50.0],
+        ))]);
+        assert!(matches!(
+            mismatched.validate_structure(),
+            Err(LayoutError::SizeCountMismatch {
+                children: 1,
+                sizes: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_sessions_are_rejected() {
+        let layout = TerminalLayout::from_json(V1_LAYOUT).unwrap();
+        assert!(matches!(
+            layout.validate(&sessions(&["s1", "s3"])),
+            Err(LayoutError::UnknownSession { node_id, session_id })
+                if node_id == "b" && session_id == "s2"
+        ));
+    }
+
+    #[test]
+    fn retain_sessions_renormalizes_sizes() {
+        let mut layout = TerminalLayout::from_json(V1_LAYOUT).unwrap();
+        let removed = layout.retain_sessions(&sessions(&["s1", "s3"]));
+        assert_eq!(removed, vec!["s2"]);
+
+        let root = layout.tabs[0].root.as_ref().unwrap();
+        let sizes = sizes_of(root);
+        assert_eq!(sizes.len(), 2);
+        assert!((sizes[0] - 33.3 / 66.7 * 100.0).abs() < 1e-9);
+        assert!((sizes.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        layout.validate(&sessions(&["s1", "s3"])).unwrap();
+    }
+
+    #[test]
+    fn retain_sessions_collapses_single_child_splits() {
+        let mut layout = layout(vec![Some(split(
+            "root",
+            vec![
+                terminal("a", "s1"),
+                split(
+                    "inner",
+                    vec![terminal("b", "s2"), terminal("c", "s3")],
+                    vec![30.0, 70.0],
+                ),
+            ],
+            vec![50.0, 50.0],
+        ))]);
+
+        layout.retain_sessions(&sessions(&["s1", "s3"]));
+        let root = layout.tabs[0].root.as_ref().unwrap();
+        let LayoutNode::Split(root) = root else {
+            panic!("expected the root split to remain");
+        };
+        assert_eq!(root.sizes, vec![50.0, 50.0]);
+        assert_eq!(root.children[1], terminal("c", "s3"));
+
+        layout.retain_sessions(&sessions(&["s3"]));
+        assert_eq!(layout.tabs[0].root, Some(terminal("c", "s3")));
+    }
+
+    #[test]
+    fn retain_sessions_drops_emptied_tabs_and_fixes_active_tab() {
+        let mut layout = layout(vec![
+            Some(terminal("a", "s1")),
+            Some(terminal("b", "s2")),
+            None,
+        ]);
+        assert_eq!(layout.active_tab.as_deref(), Some("tab-1"));
+
+        let removed = layout.retain_sessions(&sessions(&["s2"]));
+        assert_eq!(removed, vec!["s1"]);
+        // Tabs that were already empty are kept
+        let ids: Vec<&str> = layout.tabs.iter().map(|tab| tab.id.as_str()).collect();
+        assert_eq!(ids, vec!["tab-2", "tab-3"]);
+        assert_eq!(layout.active_tab.as_deref(), Some("tab-2"));
+    }
+
+    #[test]
+    fn retain_sessions_keeps_webviews() {
+        let mut layout = TerminalLayout::from_json(V0_LAYOUT).unwrap();
+        layout.retain_sessions(&HashSet::new());
+        assert!(matches!(
+            &layout.tabs[0].root,
+            Some(LayoutNode::Webview(webview)) if webview.id == "docs"
+        ));
+    }
+}
@@ -1,18 +1,40 @@
 use std::sync::Mutex;
-use tauri::menu::{Menu, MenuItemBuilder, SubmenuBuilder};
 use tauri::{Emitter, Manager};
 
+pub mod attachments;
+pub mod clipboard;
+pub mod config;
+pub mod downloads;
+pub mod history;
+pub mod keybindings;
+pub mod layout;
+pub mod maintenance;
 pub mod pty;
+pub mod settings;
+pub mod themes;
 pub mod webview;
+pub mod workspace;
 mod storage;
 
-use pty::PtySessionManager;
+use clipboard::ClipboardRegistry;
+use config::LoadedConfig;
+use downloads::DownloadRegistry;
+use layout::LayoutLocks;
+use pty::{LoginEnvCache, PtySessionManager, SessionStatsState};
 use storage::database::Database;
-use webview::{WebviewManager, WebviewState};
+use webview::{EvalRegistry, WebviewManager, WebviewState};
+use workspace::PendingGeometry;
 
 pub struct AppState {
     pub db: Database,
     pub pty_manager: Mutex<PtySessionManager>,
+    pub config: Mutex<LoadedConfig>,
+    pub login_env: LoginEnvCache,
+    pub session_stats: Mutex<SessionStatsState>,
+    pub downloads: Mutex<DownloadRegistry>,
+    pub clipboard: Mutex<ClipboardRegistry>,
+    pub layout_locks: Mutex<LayoutLocks>,
+    pub pending_geometry: Mutex<PendingGeometry>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -24,7 +46,11 @@ pub fn run() {
     #[allow(unused_mut)]
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_fs::init());
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        // Child webviews post console messages and eval results to these
+        .register_uri_scheme_protocol(webview::CONSOLE_SCHEME, webview::console_protocol)
+        .register_uri_scheme_protocol(webview::EVAL_RESULT_SCHEME, webview::eval_protocol);
 
     // Add MCP bridge plugin for testing (debug builds only)
     #[cfg(debug_assertions)]
@@ -34,56 +60,6 @@ pub fn run() {
 
     builder
         .setup(|app| {
-            // Build App menu (macOS standard menu with Quit, Hide, etc.)
-            let app_menu = SubmenuBuilder::new(app, "wiz-term")
-                .about(None)
-                .separator()
-                .services()
-                .separator()
-                .hide()
-                .hide_others()
-                .show_all()
-                .separator()
-                .quit()
-                .build()?;
-
-            // Build View menu
-            let toggle_minimap = MenuItemBuilder::with_id("toggle_minimap", "Toggle Minimap")
-                .accelerator("CmdOrCtrl+Shift+M")
-                .build(app)?;
-            let pin_minimap = MenuItemBuilder::with_id("pin_minimap", "Pin to Top")
-                .build(app)?;
-            let reset_minimap = MenuItemBuilder::with_id("reset_minimap", "Reset Position")
-                .build(app)?;
-
-            let view_menu = SubmenuBuilder::new(app, "View")
-                .item(&toggle_minimap)
-                .item(&pin_minimap)
-                .separator()
-                .item(&reset_minimap)
-                .build()?;
-
-            // Build Edit menu with standard items
-            let edit_menu = SubmenuBuilder::new(app, "Edit")
-                .undo()
-                .redo()
-                .separator()
-                .cut()
-                .copy()
-                .paste()
-                .select_all()
-                .build()?;
-
-            // Build Window menu
-            // Note: .close_window() removed so frontend Cmd+W handler closes splits instead
-            let window_menu = SubmenuBuilder::new(app, "Window")
-                .minimize()
-                .maximize()
-                .build()?;
-
-            let menu = Menu::with_items(app, &[&app_menu, &edit_menu, &view_menu, &window_menu])?;
-            app.set_menu(menu)?;
-
             let db = Database::new().expect("Failed to initialize database");
             db.run_migrations().expect("Failed to run migrations");
 
@@ -93,22 +69,63 @@ pub fn run() {
                     tracing::info!("Marked {} stale terminal sessions as ended", marked);
                 }
             }
-            if let Ok(deleted) = db.cleanup_old_terminal_sessions(7) {
-                if deleted > 0 {
-                    tracing::info!("Cleaned up {} old terminal sessions", deleted);
-                }
+
+            // User config file overrides SQLite preferences and is hot-reloaded
+            let config = LoadedConfig::load(config::config_path());
+            if let Some(error) = &config.error {
+                tracing::error!("{}", error);
             }
 
             app.manage(AppState {
                 db,
                 pty_manager: Mutex::new(PtySessionManager::new()),
+                config: Mutex::new(config),
+                login_env: LoginEnvCache::default(),
+                session_stats: Mutex::new(SessionStatsState::default()),
+                downloads: Mutex::new(DownloadRegistry::default()),
+                clipboard: Mutex::new(ClipboardRegistry::default()),
+                layout_locks: Mutex::new(LayoutLocks::default()),
+                pending_geometry: Mutex::new(PendingGeometry::default()),
             });
 
+            // Login-shell environment for spawned commands, captured off the main thread
+            pty::prefetch_login_environment(app.handle());
+
+            // Periodic `session-stats` events, if enabled in preferences
+            pty::apply_stats_interval(app.handle());
+
+            // File transfers cut off by the last quit leave partial files behind
+            downloads::fail_interrupted_downloads(&app.state::<AppState>());
+
+            // Every session from the last run has ended, so its attachments go
+            attachments::remove_stale_attachments(&app.state::<AppState>());
+
+            // Native menus are built from the keybinding registry and rebuilt
+            // whenever bindings change
+            let keymap = keybindings::current_keymap(&app.state::<AppState>())
+                .unwrap_or_else(|e| {
+                    tracing::error!("{}; using default keybindings", e);
+                    keybindings::Keymap::resolve(&Default::default(), &Default::default())
+                });
+            keybindings::log_conflicts(&keymap);
+            app.set_menu(keybindings::build_menu(app.handle(), &keymap)?)?;
+
+            if let Err(e) = config::watch_config(app.handle().clone()) {
+                tracing::error!("Config hot reload disabled: {}", e);
+            }
+
+            // Retention cleanup now, VACUUM/integrity check on the configured interval
+            maintenance::spawn_maintenance(app.handle().clone());
+
             // Manage webview state separately for child webviews
             app.manage(WebviewState {
                 manager: Mutex::new(WebviewManager::new()),
+                evals: Mutex::new(EvalRegistry::default()),
             });
 
+            // Main window geometry and any other windows open at last quit
+            workspace::restore_windows(app.handle());
+
             tracing::info!("wiz-term app initialized");
             app.emit("backend-ready", ()).unwrap();
             Ok(())
@@ -120,32 +137,87 @@ pub fn run() {
             pty::pty_kill,
             pty::pty_list_sessions,
             pty::pty_get_session,
-            pty::pty_save_layout,
-            pty::pty_get_layout,
+            layout::pty_save_layout,
+            layout::pty_get_layout,
+            layout::layout_restore,
+            layout::layout_remap_sessions,
+            layout::layout_session_ids,
+            layout::layout_tab_new,
+            layout::layout_tab_close,
+            layout::layout_tab_move,
+            layout::layout_tab_rename,
+            layout::layout_tab_activate,
+            layout::layout_undo,
+            layout::layout_redo,
+            layout::layout_history,
+            layout::layout_snapshot_save,
+            layout::layout_snapshot_list,
+            layout::layout_snapshot_restore,
+            layout::layout_snapshot_delete,
             pty::pty_save_preferences,
             pty::pty_get_preferences,
-            pty::save_temp_image,
+            pty::pty_list_available_shells,
+            pty::pty_validate_shell,
+            pty::pty_refresh_login_env,
+            pty::pty_get_login_env_status,
+            pty::pty_session_stats,
+            pty::pty_process_tree,
+            pty::pty_signal,
+            downloads::download_respond,
+            downloads::download_list,
+            clipboard::clipboard_respond,
+            attachments::attachment_save,
+            attachments::attachment_list,
+            attachments::attachment_delete,
+            config::config_get_status,
+            config::config_reload,
+            themes::theme_list,
+            themes::theme_get,
+            themes::theme_save,
+            themes::theme_delete,
+            themes::theme_import,
+            themes::theme_set,
+            keybindings::keybindings_get,
+            keybindings::keybindings_set,
+            keybindings::keybindings_reset,
+            settings::settings_export,
+            settings::settings_import,
+            history::history_list_sessions,
+            history::history_get_session,
+            history::history_stats,
+            maintenance::db_stats,
+            maintenance::db_compact,
+            workspace::window_new,
+            workspace::window_workspace,
+            workspace::window_list_workspaces,
             webview::create_webview,
             webview::update_webview,
+            webview::set_webview_visible,
             webview::close_webview,
             webview::navigate_webview,
             webview::eval_webview,
+            webview::eval_webview_with_result,
+            webview::open_webview_devtools,
+            webview::close_webview_devtools,
+            webview::webview_console_log_path,
+            webview::browser_profile_list,
+            webview::browser_profile_create,
+            webview::browser_profile_clear,
+            webview::browser_profile_delete,
         ])
         .on_menu_event(|app, event| {
+            // Menu items are keyed by action; the focused window performs the
+            // action, except for opening windows which the backend handles
             let id = event.id().as_ref();
-            match id {
-                "toggle_minimap" => {
-                    let _ = app.emit("menu-toggle-minimap", ());
-                }
-                "pin_minimap" => {
-                    let _ = app.emit("menu-pin-minimap", ());
-                }
-                "reset_minimap" => {
-                    let _ = app.emit("menu-reset-minimap", ());
+            if id == "new_window" {
+                if let Err(e) = workspace::open_new_window(app) {
+                    tracing::error!("{}", e);
                 }
-                _ => {}
+            } else if keybindings::find_action(id).is_some() {
+                workspace::emit_to_focused(app, "keybinding-action", id);
             }
         })
+        .on_window_event(workspace::handle_window_event)
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,22 @@
+use super::tasks::{run_maintenance, MaintenanceReport};
+use crate::storage::database::DbStats;
+use crate::AppState;
+use tauri::AppHandle;
+
+/// Database file size, free pages and per-table rows/bytes
+#[tauri::command]
+pub async fn db_stats(state: tauri::State<'_, AppState>) -> Result<DbStats, String> {
+    state
+        .db
+        .stats()
+        .map_err(|e| format!("Failed to get database stats: {}", e))
+}
+
+/// Run retention cleanup, integrity check and VACUUM now
+#[tauri::command]
+pub async fn db_compact(app: AppHandle) -> Result<MaintenanceReport, String> {
+    // VACUUM can take a while on large histories; keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || run_maintenance(&app))
+        .await
+        .map_err(|e| format!("Failed to run maintenance: {}", e))?
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod tasks;
+
+pub use commands::*;
+pub use tasks::*;
@@ -0,0 +1,236 @@
+use crate::attachments::remove_attachment_files;
+use crate::config::effective_preferences;
+use crate::storage::database::TerminalPreferences;
+use crate::AppState;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info, warn};
+
+/// How often the scheduler checks whether maintenance is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+/// Images pasted before attachments existed were written to the temp dir
+/// under this prefix; they are swept until none are left
+const LEGACY_TEMP_IMAGE_PREFIX: &str = "wizterm-image-";
+
+/// VACUUM holds the database lock; never let two runs overlap
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Outcome of a maintenance run (also stored in `maintenance_log`)
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceReport {
+    pub sessions_deleted: usize,
+    /// Expired attachments, plus leftover temp images from older versions
+    pub temp_files_deleted: usize,
+    /// `["ok"]` when healthy, otherwise the problems SQLite reported
+    pub integrity: Vec<String>,
+    pub vacuumed: bool,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub duration_ms: u64,
+}
+
+struct RunGuard;
+
+impl RunGuard {
+    fn acquire() -> Result<Self, String> {
+        RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| RunGuard)
+            .map_err(|_| "Database maintenance is already running".to_string())
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// Delete ended sessions and attachments past their retention period
+pub(crate) fn apply_retention(
+    state: &AppState,
+    prefs: &TerminalPreferences,
+) -> Result<(usize, usize), String> {
+    let sessions_deleted = if prefs.session_retention_days > 0 {
+        state
+            .db
+            .cleanup_old_terminal_sessions(prefs.session_retention_days as i64)
+            .map_err(|e| format!("Failed to clean up sessions: {}", e))?
+    } else {
+        0
+    };
+
+    let temp_files_deleted = if prefs.temp_file_retention_days > 0 {
+        let expired = state
+            .db
+            .remove_attachments_older_than(prefs.temp_file_retention_days as i64)
+            .map_err(|e| format!("Failed to clean up attachments: {}", e))?;
+        let max_age = Duration::from_secs(prefs.temp_file_retention_days as u64 * SECS_PER_DAY);
+        remove_attachment_files(&expired) + cleanup_legacy_temp_images(max_age)
+    } else {
+        0
+    };
+
+    Ok((sessions_deleted, temp_files_deleted))
+}
+
+/// Remove pasted/dropped images older versions generated in the temp dir.
+/// Files saved under the user's own filename aren't ours to guess at and are
+/// left alone.
+fn cleanup_legacy_temp_images(max_age: Duration) -> usize {
+    let entries = match std::fs::read_dir(std::env::temp_dir()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read temp dir: {}", e);
+            return 0;
+        }
+    };
+
+    let now = SystemTime::now();
+    let mut deleted = 0;
+    for entry in entries.flatten() {
+        let is_ours = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(LEGACY_TEMP_IMAGE_PREFIX));
+        if !is_ours {
+            continue;
+        }
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > max_age);
+        if expired && std::fs::remove_file(entry.path()).is_ok() {
+            deleted += 1;
+        }
+    }
+    deleted
+}
+
+/// Retention cleanup, integrity check, then VACUUM and optimize. VACUUM is
+/// skipped if the integrity check finds problems so a damaged file isn't
+/// rewritten.
+pub fn run_maintenance(app: &AppHandle) -> Result<MaintenanceReport, String> {
+    let _guard = RunGuard::acquire()?;
+    let state = app.state::<AppState>();
+    let started = Instant::now();
+
+    let prefs = effective_preferences(&state)?;
+    let (sessions_deleted, temp_files_deleted) = apply_retention(&state, &prefs)?;
+
+    let bytes_before = db_bytes(&state)?;
+    let integrity = state
+        .db
+        .integrity_check()
+        .map_err(|e| format!("Failed to check database integrity: {}", e))?;
+    let healthy = integrity.len() == 1 && integrity[0] == "ok";
+
+    if healthy {
+        state
+            .db
+            .vacuum()
+            .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+    } else {
+        error!("Database integrity check failed: {:?}", integrity);
+        if let Err(e) = app.emit("db-integrity-error", &integrity) {
+            error!("Failed to emit integrity error: {}", e);
+        }
+    }
+    state
+        .db
+        .optimize()
+        .map_err(|e| format!("Failed to optimize database: {}", e))?;
+
+    let report = MaintenanceReport {
+        sessions_deleted,
+        temp_files_deleted,
+        integrity,
+        vacuumed: healthy,
+        bytes_before,
+        bytes_after: db_bytes(&state)?,
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+
+    let report_json = serde_json::to_string(&report)
+        .map_err(|e| format!("Failed to serialize maintenance report: {}", e))?;
+    state
+        .db
+        .log_maintenance(&report_json)
+        .map_err(|e| format!("Failed to record maintenance: {}", e))?;
+
+    info!(
+        "Database maintenance: {} sessions, {} temp files removed, {} -> {} bytes in {}ms",
+        report.sessions_deleted,
+        report.temp_files_deleted,
+        report.bytes_before,
+        report.bytes_after,
+        report.duration_ms
+    );
+    Ok(report)
+}
+
+fn db_bytes(state: &AppState) -> Result<u64, String> {
+    let stats = state
+        .db
+        .stats()
+        .map_err(|e| format!("Failed to get database stats: {}", e))?;
+    Ok(stats.file_bytes + stats.wal_bytes)
+}
+
+/// Apply retention now, then run full maintenance whenever
+/// `maintenance_interval_hours` has passed since the last run. The interval
+/// is re-read each cycle so preference changes apply without a restart.
+pub fn spawn_maintenance(app: AppHandle) {
+    std::thread::spawn(move || {
+        {
+            let state = app.state::<AppState>();
+            match effective_preferences(&state).and_then(|prefs| apply_retention(&state, &prefs)) {
+                Ok((sessions, files)) if sessions + files > 0 => info!(
+                    "Cleaned up {} old terminal sessions and {} temp files",
+                    sessions, files
+                ),
+                Ok(_) => {}
+                Err(e) => error!("{}", e),
+            }
+        }
+
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+            if maintenance_due(&app) {
+                if let Err(e) = run_maintenance(&app) {
+                    error!("{}", e);
+                }
+            }
+        }
+    });
+}
+
+fn maintenance_due(app: &AppHandle) -> bool {
+    let state = app.state::<AppState>();
+    let interval_hours = match effective_preferences(&state) {
+        Ok(prefs) => prefs.maintenance_interval_hours,
+        Err(e) => {
+            error!("{}", e);
+            return false;
+        }
+    };
+    if interval_hours <= 0 {
+        return false;
+    }
+
+    match state.db.last_maintenance() {
+        Ok(Some(last)) => {
+            chrono::Utc::now().timestamp() - last >= interval_hours as i64 * 60 * 60
+        }
+        Ok(None) => true,
+        Err(e) => {
+            error!("Failed to read maintenance log: {}", e);
+            false
+        }
+    }
+}
@@ -1,22 +1,74 @@
+use super::login_env::{
+    login_environment, login_environment_status, refresh_login_environment, LoginEnvStatus,
+};
+use super::process::{
+    process_tree, signal_session_process, ProcessNode, ProcessSignal, SignalTarget,
+};
 use super::session::{CreateSessionRequest, PtySessionInfo};
+use super::shell::{available_shells, validate_shell, AvailableShell};
+use super::stats::{sample_sessions, SessionStats};
+use crate::config::{broadcast_preferences, effective_preferences};
+use crate::clipboard::CLIPBOARD_POLICIES;
+use crate::downloads::DOWNLOAD_POLICIES;
 use crate::storage::database::TerminalPreferences;
+use crate::webview::check_user_agent;
 use crate::AppState;
 use chrono::Utc;
-use std::io::Write;
-use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::BTreeMap;
+use tauri::Manager;
 
 #[tauri::command]
 pub async fn pty_create_session(
     state: tauri::State<'_, AppState>,
     app: tauri::AppHandle,
-    request: CreateSessionRequest,
+    window: tauri::Window,
+    mut request: CreateSessionRequest,
 ) -> Result<PtySessionInfo, String> {
+    let shell_preference = effective_preferences(&state)?.shell_path;
+    let mut profile_env = BTreeMap::new();
+    if let Some(name) = &request.profile {
+        let config = state
+            .config
+            .lock()
+            .map_err(|e| format!("Failed to lock config: {}", e))?;
+        let profile = config
+            .file
+            .as_ref()
+            .and_then(|file| file.profiles.get(name))
+            .ok_or_else(|| format!("Unknown profile: {}", name))?;
+        // The request wins over the profile for anything it sets
+        if request.command.is_none() {
+            request.command = profile.command.clone();
+            if request.args.is_none() && profile.command.is_some() {
+                request.args = Some(profile.args.clone());
+            }
+        }
+        if request.cwd.is_none() {
+            request.cwd = profile.cwd.clone();
+        }
+        profile_env = profile.env.clone();
+    }
+    // Resolved before locking the manager: a first capture can take a few
+    // seconds, so it runs on a blocking thread
+    let login_env = tauri::async_runtime::spawn_blocking({
+        let app = app.clone();
+        move || login_environment(&app)
+    })
+    .await
+    .map_err(|e| format!("Failed to read login environment: {}", e))?;
     let mut manager = state
         .pty_manager
         .lock()
         .map_err(|e| format!("Failed to lock PTY manager: {}", e))?;
 
-    let session_info = manager.spawn_session(app, request)?;
+    let session_info = manager.spawn_session(
+        app,
+        window.label(),
+        request,
+        &shell_preference,
+        login_env.as_deref(),
+        &profile_env,
+    )?;
 
     // Save to database
     state
@@ -53,13 +105,21 @@ pub async fn pty_resize(
     session_id: String,
     cols: u16,
     rows: u16,
+    pixel_width: Option<u16>,
+    pixel_height: Option<u16>,
 ) -> Result<(), String> {
     let mut manager = state
         .pty_manager
         .lock()
         .map_err(|e| format!("Failed to lock PTY manager: {}", e))?;
 
-    manager.resize_session(&session_id, cols, rows)
+    manager.resize_session(
+        &session_id,
+        cols,
+        rows,
+        pixel_width.unwrap_or(0),
+        pixel_height.unwrap_or(0),
+    )
 }
 
 #[tauri::command]
@@ -83,16 +143,18 @@ pub async fn pty_kill(
     Ok(())
 }
 
+/// List the calling window's sessions
 #[tauri::command]
 pub async fn pty_list_sessions(
     state: tauri::State<'_, AppState>,
+    window: tauri::Window,
 ) -> Result<Vec<PtySessionInfo>, String> {
     let manager = state
         .pty_manager
         .lock()
         .map_err(|e| format!("Failed to lock PTY manager: {}", e))?;
 
-    Ok(manager.list_sessions())
+    Ok(manager.list_window_sessions(window.label()))
 }
 
 #[tauri::command]
@@ -109,102 +171,156 @@ pub async fn pty_get_session(
 }
 
 #[tauri::command]
-pub async fn pty_save_layout(
+pub async fn pty_save_preferences(
     state: tauri::State<'_, AppState>,
-    layout_json: String,
+    app: tauri::AppHandle,
+    preferences: TerminalPreferences,
 ) -> Result<(), String> {
+    // Empty means "use the login shell"
+    if !preferences.shell_path.trim().is_empty() {
+        validate_shell(&shellexpand::tilde(preferences.shell_path.trim()))?;
+    }
+    if !DOWNLOAD_POLICIES.contains(&preferences.download_policy.as_str()) {
+        return Err(format!("Unknown download policy: {}", preferences.download_policy));
+    }
+    for policy in [&preferences.clipboard_write, &preferences.clipboard_read] {
+        if !CLIPBOARD_POLICIES.contains(&policy.as_str()) {
+            return Err(format!("Unknown clipboard policy: {}", policy));
+        }
+    }
+    if let Some(problem) = check_user_agent(&preferences.webview_user_agent) {
+        return Err(format!("Webview user agent {}", problem));
+    }
+
+    // The panel sends effective preferences; keep what config.toml sets out
+    // of SQLite
+    let stored = state
+        .db
+        .get_terminal_preferences()
+        .map_err(|e| format!("Failed to get preferences: {}", e))?;
+    let preferences = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .unmerge(preferences, stored);
+
     state
         .db
-        .save_terminal_layout(&layout_json)
-        .map_err(|e| format!("Failed to save layout: {}", e))
+        .save_terminal_preferences(&preferences)
+        .map_err(|e| format!("Failed to save preferences: {}", e))?;
+
+    broadcast_preferences(&app, &state)
 }
 
+/// Shells from /etc/shells for the settings panel
 #[tauri::command]
-pub async fn pty_get_layout(
-    state: tauri::State<'_, AppState>,
-) -> Result<Option<String>, String> {
-    state
-        .db
-        .get_terminal_layout()
-        .map_err(|e| format!("Failed to get layout: {}", e))
+pub async fn pty_list_available_shells() -> Result<Vec<AvailableShell>, String> {
+    Ok(available_shells())
 }
 
+/// Check a shell path without saving it
 #[tauri::command]
-pub async fn pty_save_preferences(
+pub async fn pty_validate_shell(path: String) -> Result<(), String> {
+    validate_shell(&shellexpand::tilde(path.trim()))
+}
+
+/// Re-read the login-shell environment (e.g. after editing ~/.zprofile)
+#[tauri::command]
+pub async fn pty_refresh_login_env(app: tauri::AppHandle) -> Result<LoginEnvStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || refresh_login_environment(&app))
+        .await
+        .map_err(|e| format!("Failed to refresh login environment: {}", e))?
+}
+
+/// Shell, variable count and age of the cached login environment
+#[tauri::command]
+pub async fn pty_get_login_env_status(
     state: tauri::State<'_, AppState>,
-    preferences: TerminalPreferences,
-) -> Result<(), String> {
+) -> Result<Option<LoginEnvStatus>, String> {
+    login_environment_status(&state)
+}
+
+/// CPU, memory and thread usage of the calling window's sessions (or just
+/// `session_id`), each including its child processes
+#[tauri::command]
+pub async fn pty_session_stats(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    session_id: Option<String>,
+) -> Result<Vec<SessionStats>, String> {
+    let label = window.label().to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let sessions: Vec<(String, Option<u32>)> = state
+            .pty_manager
+            .lock()
+            .map_err(|e| format!("Failed to lock PTY manager: {}", e))?
+            .session_pids()
+            .into_iter()
+            .filter(|(id, owner, _)| match &session_id {
+                Some(session_id) => id == session_id,
+                None => *owner == label,
+            })
+            .map(|(id, _, pid)| (id, pid))
+            .collect();
+
+        if let Some(session_id) = &session_id {
+            if sessions.is_empty() {
+                return Err(format!("Session not found: {}", session_id));
+            }
+        }
+        sample_sessions(&state, &sessions)
+    })
+    .await
+    .map_err(|e| format!("Failed to sample session stats: {}", e))?
+}
+
+fn session_shell_pid(state: &AppState, session_id: &str) -> Result<u32, String> {
     state
-        .db
-        .save_terminal_preferences(&preferences)
-        .map_err(|e| format!("Failed to save preferences: {}", e))
+        .pty_manager
+        .lock()
+        .map_err(|e| format!("Failed to lock PTY manager: {}", e))?
+        .shell_pid(session_id)
 }
 
+/// Every process under a session's shell, as a tree rooted at the shell
 #[tauri::command]
-pub async fn pty_get_preferences(
+pub async fn pty_process_tree(
     state: tauri::State<'_, AppState>,
-) -> Result<TerminalPreferences, String> {
-    state
-        .db
-        .get_terminal_preferences()
-        .map_err(|e| format!("Failed to get preferences: {}", e))
+    session_id: String,
+) -> Result<ProcessNode, String> {
+    let shell_pid = session_shell_pid(&state, &session_id)?;
+    tauri::async_runtime::spawn_blocking(move || process_tree(shell_pid))
+        .await
+        .map_err(|e| format!("Failed to read process tree: {}", e))?
+        .ok_or_else(|| format!("Session has exited: {}", session_id))
 }
 
-/// Save image data to a temp file and return the path.
-/// Used for pasting/dropping images into the terminal for Claude Code.
+/// Send INT, TERM, KILL, STOP or CONT to one process (`{ "pid": n }`) or a
+/// process group (`{ "group": n }`) in a session, without ending the pane
 #[tauri::command]
-pub async fn save_temp_image(
-    data: String, // base64 encoded image data
-    filename: Option<String>,
-) -> Result<String, String> {
-    // Determine file extension from data URL or filename
-    let (extension, image_data) = if data.starts_with("data:image/") {
-        // Parse data URL: data:image/png;base64,<data>
-        let parts: Vec<&str> = data.splitn(2, ',').collect();
-        if parts.len() != 2 {
-            return Err("Invalid data URL format".to_string());
-        }
+pub async fn pty_signal(
+    state: tauri::State<'_, AppState>,
+    session_id: String,
+    target: SignalTarget,
+    signal: String,
+) -> Result<(), String> {
+    let signal = ProcessSignal::parse(&signal)?;
+    let shell_pid = session_shell_pid(&state, &session_id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        signal_session_process(shell_pid, target, signal)
+    })
+    .await
+    .map_err(|e| format!("Failed to send signal: {}", e))??;
 
-        let mime_ext = if parts[0].contains("png") {
-            "png"
-        } else if parts[0].contains("jpeg") || parts[0].contains("jpg") {
-            "jpg"
-        } else if parts[0].contains("gif") {
-            "gif"
-        } else if parts[0].contains("webp") {
-            "webp"
-        } else {
-            "png" // default
-        };
-
-        (mime_ext.to_string(), parts[1].to_string())
-    } else {
-        // Raw base64 - guess from filename or default to png
-        let ext = filename
-            .as_ref()
-            .and_then(|f| f.rsplit('.').next())
-            .unwrap_or("png")
-            .to_string();
-        (ext, data)
-    };
-
-    // Decode base64
-    let bytes = STANDARD
-        .decode(&image_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    // Create temp file with original filename or generated name
-    let temp_dir = std::env::temp_dir();
-    let file_name = filename.unwrap_or_else(|| {
-        format!("wizterm-image-{}.{}", uuid::Uuid::new_v4(), extension)
-    });
-    let file_path = temp_dir.join(&file_name);
-
-    // Write to file
-    let mut file = std::fs::File::create(&file_path)
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
-    file.write_all(&bytes)
-        .map_err(|e| format!("Failed to write image data: {}", e))?;
-
-    Ok(file_path.to_string_lossy().to_string())
+    tracing::info!("Sent {:?} to {:?} in session {}", signal, target, session_id);
+    Ok(())
+}
+
+/// Get preferences, with any values from config.toml taking precedence
+#[tauri::command]
+pub async fn pty_get_preferences(
+    state: tauri::State<'_, AppState>,
+) -> Result<TerminalPreferences, String> {
+    effective_preferences(&state)
 }
@@ -0,0 +1,337 @@
+use super::shell::resolve_shell;
+use crate::config::effective_preferences;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+/// Captured environment is reused for this long before a background refresh.
+/// A failed capture is also remembered this long, so sessions don't each
+/// wait out the timeout again.
+const CACHE_TTL: Duration = Duration::from_secs(30 * 60);
+/// Slow rc files (nvm, conda, ...) shouldn't hang terminal creation
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Printed before `env -0` so anything rc files write to stdout is skipped
+const ENV_MARKER: &str = "__WIZTERM_LOGIN_ENV__";
+
+/// Per-shell or per-terminal values that must not leak into other sessions
+const SKIPPED_VARS: &[&str] = &[
+    "_", "PWD", "OLDPWD", "SHLVL", "TERM", "COLUMNS", "LINES", "PS1", "PS2",
+];
+
+/// Login-shell environment applied to every spawned command, so a profile
+/// command like `npm run dev` sees the same PATH as an interactive shell
+/// even when the app was started from a desktop launcher.
+#[derive(Debug)]
+pub struct LoginEnvironment {
+    pub shell: String,
+    pub vars: Arc<BTreeMap<String, String>>,
+    pub captured_at: DateTime<Utc>,
+    captured: Instant,
+    pub duration_ms: u64,
+}
+
+/// A capture that failed; sessions use the app's own environment meanwhile
+#[derive(Debug)]
+struct LoginEnvFailure {
+    shell: String,
+    failed: Instant,
+}
+
+#[derive(Debug, Default)]
+struct LoginEnvSlot {
+    current: Option<LoginEnvironment>,
+    failure: Option<LoginEnvFailure>,
+    refreshing: bool,
+}
+
+/// Cache kept in `AppState`. Sessions that find a capture in flight wait on
+/// `captured` for its result instead of starting another one.
+#[derive(Debug, Default)]
+pub struct LoginEnvCache {
+    slot: Mutex<LoginEnvSlot>,
+    captured: Condvar,
+}
+
+impl LoginEnvCache {
+    fn lock(&self) -> Result<MutexGuard<'_, LoginEnvSlot>, String> {
+        self.slot
+            .lock()
+            .map_err(|e| format!("Failed to lock login environment: {}", e))
+    }
+}
+
+/// What the last capture produced, for the settings panel
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginEnvStatus {
+    pub shell: String,
+    pub variables: usize,
+    pub captured_at: String,
+    pub duration_ms: u64,
+}
+
+impl From<&LoginEnvironment> for LoginEnvStatus {
+    fn from(env: &LoginEnvironment) -> Self {
+        Self {
+            shell: env.shell.clone(),
+            variables: env.vars.len(),
+            captured_at: env.captured_at.to_rfc3339(),
+            duration_ms: env.duration_ms,
+        }
+    }
+}
+
+/// Run `<shell> -l -i -c 'env -0'` and parse the result. Returns an error if
+/// the shell fails or takes longer than `CAPTURE_TIMEOUT`, including time
+/// spent waiting for its output to close.
+#[cfg(unix)]
+pub fn capture_login_environment(shell: &str) -> Result<LoginEnvironment, String> {
+    use std::os::unix::process::CommandExt;
+    use std::sync::mpsc;
+
+    let started = Instant::now();
+    let mut command = Command::new(shell);
+    command
+        .args(["-l", "-i", "-c"])
+        .arg(format!("printf '%s\\0' {}; env -0", ENV_MARKER))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    // Its own session keeps the interactive shell off the app's terminal and
+    // puts everything rc files start into one group we can kill
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", shell, e))?;
+
+    // Drain stdout on a separate thread so a large environment can't fill the pipe
+    let mut stdout = child.stdout.take().ok_or("Failed to capture shell output")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = tx.send(stdout.read_to_end(&mut output).map(|_| output));
+    });
+
+    let timed_out = || {
+        format!(
+            "Timed out after {}s reading the login environment from {}",
+            CAPTURE_TIMEOUT.as_secs(),
+            shell
+        )
+    };
+    let deadline = started + CAPTURE_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                kill_process_group(&mut child);
+                return Err(timed_out());
+            }
+            Err(e) => {
+                kill_process_group(&mut child);
+                return Err(format!("Failed to wait for {}: {}", shell, e));
+            }
+        }
+    };
+
+    // A background job started by an rc file can hold stdout open after the
+    // shell exits, so the read shares the deadline
+    let output = match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(output) => output.map_err(|e| format!("Failed to read login environment: {}", e))?,
+        Err(_) => {
+            kill_process_group(&mut child);
+            return Err(timed_out());
+        }
+    };
+    if !status.success() {
+        return Err(format!("{} exited with {} while reading its environment", shell, status));
+    }
+
+    let vars = parse_env_output(&output)
+        .ok_or_else(|| format!("{} did not print its environment", shell))?;
+    Ok(LoginEnvironment {
+        shell: shell.to_string(),
+        vars: Arc::new(vars),
+        captured_at: Utc::now(),
+        captured: Instant::now(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Kill the capture shell and anything it left running; it leads its own group
+#[cfg(unix)]
+fn kill_process_group(child: &mut std::process::Child) {
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.wait();
+}
+
+#[cfg(not(unix))]
+pub fn capture_login_environment(shell: &str) -> Result<LoginEnvironment, String> {
+    Err(format!("Login environment capture is not supported for {}", shell))
+}
+
+/// NUL-separated `KEY=value` pairs following the marker
+fn parse_env_output(output: &[u8]) -> Option<BTreeMap<String, String>> {
+    let mut entries = output.split(|&b| b == 0);
+    entries.find(|entry| entry.ends_with(ENV_MARKER.as_bytes()))?;
+
+    Some(
+        entries
+            .filter_map(|entry| {
+                let entry = String::from_utf8_lossy(entry);
+                let (key, value) = entry.split_once('=')?;
+                let keep = !key.is_empty() && !SKIPPED_VARS.contains(&key);
+                keep.then(|| (key.to_string(), value.to_string()))
+            })
+            .collect(),
+    )
+}
+
+fn login_shell_path(state: &AppState) -> Result<String, String> {
+    let preference = effective_preferences(state)?.shell_path;
+    // An invalid preference fails the spawn itself; the environment can still
+    // come from the login shell
+    resolve_shell(&preference)
+        .or_else(|_| resolve_shell(""))
+        .map(|shell| shell.path)
+}
+
+/// Capture now, replacing the cache
+pub fn refresh_login_environment(app: &AppHandle) -> Result<LoginEnvStatus, String> {
+    let state = app.state::<AppState>();
+    let shell = login_shell_path(&state)?;
+    state.login_env.lock()?.refreshing = true;
+    let result = capture_login_environment(&shell);
+
+    let mut cache = state.login_env.lock()?;
+    cache.refreshing = false;
+    state.login_env.captured.notify_all();
+    let env = match result {
+        Ok(env) => env,
+        Err(e) => {
+            cache.failure = Some(LoginEnvFailure {
+                shell,
+                failed: Instant::now(),
+            });
+            return Err(e);
+        }
+    };
+    cache.failure = None;
+    info!(
+        "Captured {} login environment variables from {} in {}ms",
+        env.vars.len(),
+        env.shell,
+        env.duration_ms
+    );
+    let status = LoginEnvStatus::from(&env);
+    cache.current = Some(env);
+    Ok(status)
+}
+
+fn spawn_refresh(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = refresh_login_environment(&app) {
+            warn!("{}", e);
+        }
+    });
+}
+
+/// Start capturing in the background so the first terminal doesn't wait
+pub fn prefetch_login_environment(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    if let Ok(mut cache) = state.login_env.lock() {
+        cache.refreshing = true;
+    }
+    spawn_refresh(app);
+}
+
+/// Variables to apply to a new session. A stale cache is used as-is while a
+/// background refresh runs; with no cache at all this waits for the capture
+/// already in flight, or captures itself (bounded by the timeout). Blocks,
+/// so call it off the async runtime. None if capture isn't possible or
+/// recently failed; a failure is retried in the background once it's
+/// `CACHE_TTL` old.
+pub fn login_environment(app: &AppHandle) -> Option<Arc<BTreeMap<String, String>>> {
+    let state = app.state::<AppState>();
+    let shell = login_shell_path(&state).ok()?;
+
+    {
+        let mut cache = state.login_env.lock().ok()?;
+        let deadline = Instant::now() + CAPTURE_TIMEOUT * 2;
+        loop {
+            if let Some(env) = cache.current.as_ref().filter(|env| env.shell == shell) {
+                let vars = env.vars.clone();
+                if env.captured.elapsed() > CACHE_TTL && !cache.refreshing {
+                    cache.refreshing = true;
+                    spawn_refresh(app);
+                }
+                return Some(vars);
+            }
+            if let Some(failure) = cache.failure.as_ref().filter(|f| f.shell == shell) {
+                if failure.failed.elapsed() > CACHE_TTL && !cache.refreshing {
+                    cache.refreshing = true;
+                    spawn_refresh(app);
+                }
+                return None;
+            }
+            if !cache.refreshing {
+                break;
+            }
+            // The startup prefetch or another new session is capturing already
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+            cache = state
+                .login_env
+                .captured
+                .wait_timeout(cache, remaining)
+                .ok()?
+                .0;
+        }
+        cache.refreshing = true;
+    }
+
+    match refresh_login_environment(app) {
+        Ok(_) => state
+            .login_env
+            .lock()
+            .ok()?
+            .current
+            .as_ref()
+            .map(|env| env.vars.clone()),
+        Err(e) => {
+            warn!("{}; using the app's own environment", e);
+            None
+        }
+    }
+}
+
+/// Status of the cached capture, if any
+pub fn login_environment_status(state: &AppState) -> Result<Option<LoginEnvStatus>, String> {
+    Ok(state
+        .login_env
+        .lock()?
+        .current
+        .as_ref()
+        .map(LoginEnvStatus::from))
+}
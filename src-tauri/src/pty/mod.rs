@@ -1,5 +1,15 @@
 pub mod commands;
+pub mod login_env;
+pub mod osc;
+pub mod process;
 pub mod session;
+pub mod shell;
+pub mod stats;
 
 pub use commands::*;
+pub use login_env::*;
+pub use osc::*;
+pub use process::*;
 pub use session::*;
+pub use shell::*;
+pub use stats::*;
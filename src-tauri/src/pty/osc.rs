@@ -0,0 +1,217 @@
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Longest OSC prefix held back while a handler decides what to do with it;
+/// anything longer is passed through to the terminal
+const MAX_PREFIX: usize = 4096;
+
+/// What to do with an OSC sequence, decided from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OscDecision {
+    /// Not enough of the sequence seen yet
+    Undecided,
+    /// Leave it in the output for the terminal
+    Pass,
+    /// Remove it from the output; the payload starts `body_start` bytes into
+    /// the prefix
+    Intercept { body_start: usize },
+}
+
+/// Receives the OSC sequences the backend handles itself
+pub trait OscHandler {
+    /// Called with the bytes after `ESC ]` as more arrive, until it returns
+    /// something other than `Undecided`. May be asked again with a longer
+    /// prefix after returning `Pass`, so it should only act once it returns
+    /// `Intercept`.
+    fn classify(&mut self, prefix: &[u8]) -> OscDecision;
+    /// Payload of the intercepted sequence, in chunks of any size
+    fn data(&mut self, data: &[u8]);
+    /// The intercepted sequence ended; `complete` is false if it was cut off
+    fn end(&mut self, complete: bool);
+}
+
+/// Several handlers behind one scanner. The first to intercept a sequence
+/// receives all of it; a sequence every handler passes on goes to the terminal.
+#[derive(Default)]
+pub struct OscHandlers {
+    handlers: Vec<Box<dyn OscHandler>>,
+    active: Option<usize>,
+}
+
+impl OscHandlers {
+    pub fn with(mut self, handler: impl OscHandler + 'static) -> Self {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+}
+
+impl OscHandler for OscHandlers {
+    fn classify(&mut self, prefix: &[u8]) -> OscDecision {
+        let mut undecided = false;
+        for (index, handler) in self.handlers.iter_mut().enumerate() {
+            match handler.classify(prefix) {
+                OscDecision::Intercept { body_start } => {
+                    self.active = Some(index);
+                    return OscDecision::Intercept { body_start };
+                }
+                OscDecision::Undecided => undecided = true,
+                OscDecision::Pass => {}
+            }
+        }
+        if undecided {
+            OscDecision::Undecided
+        } else {
+            OscDecision::Pass
+        }
+    }
+
+    fn data(&mut self, data: &[u8]) {
+        if let Some(handler) = self.active.and_then(|index| self.handlers.get_mut(index)) {
+            handler.data(data);
+        }
+    }
+
+    fn end(&mut self, complete: bool) {
+        if let Some(handler) = self
+            .active
+            .take()
+            .and_then(|index| self.handlers.get_mut(index))
+        {
+            handler.end(complete);
+        }
+    }
+}
+
+enum ScanState {
+    Ground,
+    Escape,
+    Prefix(Vec<u8>),
+    Intercept,
+    InterceptEscape,
+}
+
+/// Splits PTY output into bytes for the terminal and OSC sequences for an
+/// `OscHandler`. Sequences may span any number of reads; only the prefix is
+/// buffered, so large payloads stream straight through to the handler.
+pub struct OscScanner {
+    state: ScanState,
+}
+
+impl Default for OscScanner {
+    fn default() -> Self {
+        Self {
+            state: ScanState::Ground,
+        }
+    }
+}
+
+fn find_terminator(data: &[u8]) -> Option<usize> {
+    data.iter().position(|&b| b == BEL || b == ESC)
+}
+
+impl OscScanner {
+    /// Scan one read. Returns the bytes to send to the terminal.
+    pub fn feed(&mut self, input: &[u8], handler: &mut impl OscHandler) -> Vec<u8> {
+        let mut out = Vec::with_capacity(input.len());
+        let mut i = 0;
+
+        while i < input.len() {
+            match &mut self.state {
+                ScanState::Ground => match input[i..].iter().position(|&b| b == ESC) {
+                    Some(offset) => {
+                        out.extend_from_slice(&input[i..i + offset]);
+                        i += offset + 1;
+                        self.state = ScanState::Escape;
+                    }
+                    None => {
+                        out.extend_from_slice(&input[i..]);
+                        i = input.len();
+                    }
+                },
+                ScanState::Escape => {
+                    if input[i] == b']' {
+                        i += 1;
+                        self.state = ScanState::Prefix(Vec::new());
+                    } else {
+                        // Some other escape sequence; the byte is re-read in Ground
+                        out.push(ESC);
+                        self.state = ScanState::Ground;
+                    }
+                }
+                ScanState::Prefix(prefix) => {
+                    let rest = &input[i..];
+                    let terminator = find_terminator(rest);
+                    let take = terminator
+                        .unwrap_or(rest.len())
+                        .min(MAX_PREFIX - prefix.len());
+                    prefix.extend_from_slice(&rest[..take]);
+                    i += take;
+
+                    match handler.classify(prefix) {
+                        OscDecision::Intercept { body_start } => {
+                            handler.data(&prefix[body_start.min(prefix.len())..]);
+                            self.state = ScanState::Intercept;
+                        }
+                        OscDecision::Undecided
+                            if terminator.is_none() && prefix.len() < MAX_PREFIX =>
+                        {
+                            // Wait for the next read
+                        }
+                        // Passed, ended before a decision or too long to hold
+                        _ => {
+                            out.extend_from_slice(&[ESC, b']']);
+                            out.extend_from_slice(prefix);
+                            self.state = ScanState::Ground;
+                        }
+                    }
+                }
+                ScanState::Intercept => {
+                    let rest = &input[i..];
+                    match find_terminator(rest) {
+                        Some(offset) => {
+                            handler.data(&rest[..offset]);
+                            i += offset + 1;
+                            if rest[offset] == BEL {
+                                handler.end(true);
+                                self.state = ScanState::Ground;
+                            } else {
+                                self.state = ScanState::InterceptEscape;
+                            }
+                        }
+                        None => {
+                            handler.data(rest);
+                            i = input.len();
+                        }
+                    }
+                }
+                ScanState::InterceptEscape => {
+                    if input[i] == b'\\' {
+                        i += 1;
+                        handler.end(true);
+                        self.state = ScanState::Ground;
+                    } else {
+                        // ESC without `\` aborts the sequence and starts a new one
+                        handler.end(false);
+                        self.state = ScanState::Escape;
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    /// The stream ended: return anything held back and end an unfinished
+    /// intercepted sequence
+    pub fn finish(&mut self, handler: &mut impl OscHandler) -> Vec<u8> {
+        match std::mem::replace(&mut self.state, ScanState::Ground) {
+            ScanState::Ground => Vec::new(),
+            ScanState::Escape => vec![ESC],
+            ScanState::Prefix(prefix) => [&[ESC, b']'][..], &prefix].concat(),
+            ScanState::Intercept | ScanState::InterceptEscape => {
+                handler.end(false);
+                Vec::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// One process under a session's shell
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessNode {
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    /// Process group, the target for a group signal (Unix only)
+    pub pgid: Option<u32>,
+    pub name: String,
+    /// Full command line; empty if it can't be read
+    pub command: Vec<String>,
+    /// Scheduler state as reported by the OS (Runnable, Sleeping, Stopped, ...)
+    pub state: String,
+    /// RFC 3339
+    pub started_at: Option<String>,
+    pub children: Vec<ProcessNode>,
+}
+
+/// Signals `pty_signal` is allowed to send
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSignal {
+    Interrupt,
+    Terminate,
+    Kill,
+    Stop,
+    Continue,
+}
+
+impl ProcessSignal {
+    /// Accepts `INT`, `SIGINT` or `sigint` style names
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let upper = name.trim().to_ascii_uppercase();
+        match upper.strip_prefix("SIG").unwrap_or(&upper) {
+            "INT" => Ok(Self::Interrupt),
+            "TERM" => Ok(Self::Terminate),
+            "KILL" => Ok(Self::Kill),
+            "STOP" => Ok(Self::Stop),
+            "CONT" => Ok(Self::Continue),
+            _ => Err(format!(
+                "Unsupported signal: {} (expected INT, TERM, KILL, STOP or CONT)",
+                name
+            )),
+        }
+    }
+
+    #[cfg(unix)]
+    fn number(self) -> libc::c_int {
+        match self {
+            Self::Interrupt => libc::SIGINT,
+            Self::Terminate => libc::SIGTERM,
+            Self::Kill => libc::SIGKILL,
+            Self::Stop => libc::SIGSTOP,
+            Self::Continue => libc::SIGCONT,
+        }
+    }
+}
+
+/// What `pty_signal` sends to: one process, or every process in a group
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalTarget {
+    Pid(u32),
+    Group(u32),
+}
+
+/// Child PIDs of every process. Threads show up as processes on Linux and
+/// are left out.
+pub(crate) fn child_map(system: &System) -> HashMap<Pid, Vec<Pid>> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+    children
+}
+
+/// `root` and all of its descendants that are still running
+pub(crate) fn descendants(
+    system: &System,
+    children: &HashMap<Pid, Vec<Pid>>,
+    root: Pid,
+) -> Vec<Pid> {
+    let mut found = Vec::new();
+    let mut pending = vec![root];
+    while let Some(pid) = pending.pop() {
+        if system.process(pid).is_none() {
+            continue;
+        }
+        found.push(pid);
+        if let Some(kids) = children.get(&pid) {
+            pending.extend(kids);
+        }
+    }
+    found
+}
+
+/// Fresh process table with command lines, for one-off inspection
+fn process_table() -> System {
+    let mut system = System::new();
+    let kind = ProcessRefreshKind::nothing().with_cmd(UpdateKind::OnlyIfNotSet);
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+    system
+}
+
+#[cfg(unix)]
+fn process_group(pid: u32) -> Option<u32> {
+    let pgid = unsafe { libc::getpgid(pid as libc::pid_t) };
+    (pgid > 0).then_some(pgid as u32)
+}
+
+#[cfg(not(unix))]
+fn process_group(_pid: u32) -> Option<u32> {
+    None
+}
+
+fn build_node(system: &System, children: &HashMap<Pid, Vec<Pid>>, pid: Pid) -> Option<ProcessNode> {
+    let process = system.process(pid)?;
+    let mut kids: Vec<ProcessNode> = children
+        .get(&pid)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| build_node(system, children, *child))
+        .collect();
+    kids.sort_by_key(|node| node.pid);
+
+    Some(ProcessNode {
+        pid: pid.as_u32(),
+        ppid: process.parent().map(|parent| parent.as_u32()),
+        pgid: process_group(pid.as_u32()),
+        name: process.name().to_string_lossy().to_string(),
+        command: process
+            .cmd()
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect(),
+        state: process.status().to_string(),
+        started_at: DateTime::from_timestamp(process.start_time() as i64, 0)
+            .map(|at| at.to_rfc3339()),
+        children: kids,
+    })
+}
+
+/// The tree rooted at a session's shell; None if the shell is gone
+pub fn process_tree(shell_pid: u32) -> Option<ProcessNode> {
+    let system = process_table();
+    let children = child_map(&system);
+    build_node(&system, &children, Pid::from_u32(shell_pid))
+}
+
+/// Send `signal` to `target` after checking it belongs to the session whose
+/// shell is `shell_pid`. A group must be led by a process in the session, so
+/// a signal can never reach the app or another session.
+#[cfg(unix)]
+pub fn signal_session_process(
+    shell_pid: u32,
+    target: SignalTarget,
+    signal: ProcessSignal,
+) -> Result<(), String> {
+    let system = process_table();
+    let members = descendants(&system, &child_map(&system), Pid::from_u32(shell_pid));
+    let belongs = |pid: u32| members.iter().any(|member| member.as_u32() == pid);
+
+    let rc = match target {
+        SignalTarget::Pid(pid) => {
+            if !belongs(pid) {
+                return Err(format!("Process {} does not belong to this session", pid));
+            }
+            unsafe { libc::kill(pid as libc::pid_t, signal.number()) }
+        }
+        SignalTarget::Group(pgid) => {
+            if !belongs(pgid) || process_group(pgid) != Some(pgid) {
+                return Err(format!(
+                    "Process group {} is not led by a process in this session",
+                    pgid
+                ));
+            }
+            unsafe { libc::killpg(pgid as libc::pid_t, signal.number()) }
+        }
+    };
+
+    if rc != 0 {
+        return Err(format!(
+            "Failed to send signal: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn signal_session_process(
+    _shell_pid: u32,
+    _target: SignalTarget,
+    _signal: ProcessSignal,
+) -> Result<(), String> {
+    Err("Sending signals is not supported on this platform".to_string())
+}
@@ -1,13 +1,25 @@
+use super::osc::{OscHandlers, OscScanner};
+use super::shell::resolve_shell;
+use crate::attachments::release_session_attachments;
+use crate::clipboard::{discard_session_clipboard_requests, ClipboardInterceptor};
+use crate::downloads::{discard_session_downloads, DownloadInterceptor};
+use crate::AppState;
 use chrono::{DateTime, Utc};
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{Read, Write};
+use std::path::Path;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
 use tracing::{error, info};
 use uuid::Uuid;
 
+/// How long to wait for a child to be reaped after its PTY closes
+const EXIT_POLL_ATTEMPTS: u32 = 20;
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Represents an active PTY session
 pub struct PtySession {
     pub id: String,
@@ -15,11 +27,21 @@ pub struct PtySession {
     pub args: Vec<String>,
     pub cwd: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Label of the window whose layout shows this session; output and
+    /// exit events go only to it
+    pub window: String,
     pub master: Arc<std::sync::Mutex<Box<dyn MasterPty + Send>>>,
     pub writer: Arc<std::sync::Mutex<Box<dyn Write + Send>>>,
     pub child: Arc<std::sync::Mutex<Box<dyn Child + Send + Sync>>>,
     pub cols: u16,
     pub rows: u16,
+    /// Size of the text area in pixels, reported to programs via
+    /// `TIOCGWINSZ` (0 if the frontend hasn't measured it)
+    pub pixel_width: u16,
+    pub pixel_height: u16,
+    /// config.toml profile the session was started with, for per-profile
+    /// policies
+    pub profile: Option<String>,
 }
 
 /// Session info for frontend (serializable)
@@ -30,8 +52,12 @@ pub struct PtySessionInfo {
     pub args: Vec<String>,
     pub cwd: Option<String>,
     pub created_at: String,
+    pub window: String,
     pub cols: u16,
     pub rows: u16,
+    pub pixel_width: u16,
+    pub pixel_height: u16,
+    pub profile: Option<String>,
     pub is_alive: bool,
 }
 
@@ -43,6 +69,9 @@ pub struct CreateSessionRequest {
     pub cwd: Option<String>,
     pub cols: Option<u16>,
     pub rows: Option<u16>,
+    pub pixel_width: Option<u16>,
+    pub pixel_height: Option<u16>,
+    pub profile: Option<String>,
 }
 
 /// Terminal output event payload
@@ -71,33 +100,36 @@ impl PtySessionManager {
         }
     }
 
-    /// Spawn a new PTY session
+    /// Spawn a new PTY session owned by `window`. `shell_preference` is the
+    /// `shell_path` preference, used when the request has no command;
+    /// `login_env` is the captured login-shell environment applied on top of
+    /// the app's own, and `profile_env` the profile's `env` on top of that.
     pub fn spawn_session(
         &mut self,
         app_handle: AppHandle,
+        window: &str,
         request: CreateSessionRequest,
+        shell_preference: &str,
+        login_env: Option<&BTreeMap<String, String>>,
+        profile_env: &BTreeMap<String, String>,
     ) -> Result<PtySessionInfo, String> {
         let id = Uuid::new_v4().to_string();
         let cols = request.cols.unwrap_or(80);
         let rows = request.rows.unwrap_or(24);
+        let pixel_width = request.pixel_width.unwrap_or(0);
+        let pixel_height = request.pixel_height.unwrap_or(0);
 
         // Determine if using default shell (no explicit command)
         let using_default_shell = request.command.is_none();
 
-        // Default to user's shell from SHELL env var, fallback to /bin/zsh (macOS default)
-        let command = request.command.unwrap_or_else(|| {
-            std::env::var("SHELL").unwrap_or_else(|_| {
-                // On macOS, zsh is the default shell since Catalina
-                // Try zsh first, then bash, then sh
-                if std::path::Path::new("/bin/zsh").exists() {
-                    "/bin/zsh".to_string()
-                } else if std::path::Path::new("/bin/bash").exists() {
-                    "/bin/bash".to_string()
-                } else {
-                    "/bin/sh".to_string()
-                }
-            })
-        });
+        let command = match request.command {
+            Some(command) => command,
+            None => {
+                let shell = resolve_shell(shell_preference)?;
+                info!("Using shell {} (from {:?})", shell.path, shell.source);
+                shell.path
+            }
+        };
 
         // Use login shell (-l) for default shell to ensure proper environment loading
         // This is critical when app is launched from Finder (no inherited shell env)
@@ -119,8 +151,8 @@ impl PtySessionManager {
             .openpty(PtySize {
                 rows,
                 cols,
-                pixel_width: 0,
-                pixel_height: 0,
+                pixel_width,
+                pixel_height,
             })
             .map_err(|e| format!("Failed to open PTY: {}", e))?;
 
@@ -133,11 +165,18 @@ impl PtySessionManager {
         // Set working directory
         if let Some(ref cwd) = request.cwd {
             let expanded = shellexpand::tilde(cwd).to_string();
+            if !Path::new(&expanded).is_dir() {
+                return Err(format!("Working directory {:?} is not a directory", cwd));
+            }
             cmd.cwd(&expanded);
         } else if let Some(home) = dirs::home_dir() {
             cmd.cwd(home);
         }
 
+        for (key, value) in login_env.into_iter().flatten().chain(profile_env) {
+            cmd.env(key, value);
+        }
+
         // Set TERM for color support
         cmd.env("TERM", "xterm-256color");
 
@@ -160,11 +199,22 @@ impl PtySessionManager {
             .map_err(|e| format!("Failed to take writer: {}", e))?;
 
         let session_id = id.clone();
+        let window_label = window.to_string();
         let app_handle_clone = app_handle.clone();
+        let profile = request.profile.clone();
+        let child = Arc::new(std::sync::Mutex::new(child));
+        let child_clone = child.clone();
 
         // Spawn output reader thread
         std::thread::spawn(move || {
-            Self::read_output(session_id, reader, app_handle_clone);
+            Self::read_output(
+                session_id,
+                window_label,
+                profile,
+                reader,
+                child_clone,
+                app_handle_clone,
+            );
         });
 
         let session = PtySession {
@@ -173,11 +223,15 @@ impl PtySessionManager {
             args: args.clone(),
             cwd: request.cwd.clone(),
             created_at: Utc::now(),
+            window: window.to_string(),
             master: Arc::new(std::sync::Mutex::new(pair.master)),
             writer: Arc::new(std::sync::Mutex::new(writer)),
-            child: Arc::new(std::sync::Mutex::new(child)),
+            child,
             cols,
             rows,
+            pixel_width,
+            pixel_height,
+            profile: request.profile,
         };
 
         let info = self.session_to_info(&session);
@@ -189,9 +243,41 @@ impl PtySessionManager {
     /// Read output from PTY and emit events
     fn read_output(
         session_id: String,
+        window: String,
+        profile: Option<String>,
         mut reader: Box<dyn Read + Send>,
+        child: Arc<std::sync::Mutex<Box<dyn Child + Send + Sync>>>,
         app_handle: AppHandle,
     ) {
+        let emit = |data: Vec<u8>| {
+            if data.is_empty() {
+                return;
+            }
+            let output = TerminalOutput {
+                session_id: session_id.clone(),
+                data,
+            };
+            if let Err(e) = app_handle.emit_to(window.as_str(), "terminal-output", output) {
+                error!("Failed to emit terminal output: {}", e);
+            }
+        };
+
+        // File transfers and clipboard requests are taken out of the stream
+        // before it reaches xterm
+        let mut scanner = OscScanner::default();
+        let mut handlers = OscHandlers::default()
+            .with(DownloadInterceptor::new(
+                app_handle.clone(),
+                session_id.clone(),
+                window.clone(),
+            ))
+            .with(ClipboardInterceptor::new(
+                app_handle.clone(),
+                session_id.clone(),
+                window.clone(),
+                profile,
+            ));
+
         let mut buf = [0u8; 4096];
         loop {
             match reader.read(&mut buf) {
@@ -200,32 +286,50 @@ impl PtySessionManager {
                     info!("PTY session {} EOF", session_id);
                     break;
                 }
-                Ok(n) => {
-                    let output = TerminalOutput {
-                        session_id: session_id.clone(),
-                        data: buf[..n].to_vec(),
-                    };
-                    if let Err(e) = app_handle.emit("terminal-output", output) {
-                        error!("Failed to emit terminal output: {}", e);
-                    }
-                }
+                Ok(n) => emit(scanner.feed(&buf[..n], &mut handlers)),
                 Err(e) => {
                     error!("Error reading PTY output: {}", e);
                     break;
                 }
             }
         }
+        emit(scanner.finish(&mut handlers));
+
+        let exit_code = Self::wait_for_exit(&child);
+        let state = app_handle.state::<AppState>();
+        if let Err(e) = state
+            .db
+            .update_terminal_session_end(&session_id, exit_code.map(|code| code as i32))
+        {
+            error!("Failed to record session end: {}", e);
+        }
+        release_session_attachments(&state, &session_id);
+        discard_session_downloads(&app_handle, &session_id);
+        discard_session_clipboard_requests(&app_handle, &session_id);
 
         // Emit exit event
         let exit = TerminalExit {
             session_id: session_id.clone(),
-            exit_code: None,
+            exit_code,
         };
-        if let Err(e) = app_handle.emit("terminal-exit", exit) {
+        if let Err(e) = app_handle.emit_to(window.as_str(), "terminal-exit", exit) {
             error!("Failed to emit terminal exit: {}", e);
         }
     }
 
+    /// Exit code of a child whose PTY reached EOF. The process may close its
+    /// end slightly before it is reaped, so poll briefly rather than block.
+    fn wait_for_exit(child: &std::sync::Mutex<Box<dyn Child + Send + Sync>>) -> Option<u32> {
+        for _ in 0..EXIT_POLL_ATTEMPTS {
+            let status = child.lock().ok()?.try_wait().ok()?;
+            if let Some(status) = status {
+                return Some(status.exit_code());
+            }
+            std::thread::sleep(EXIT_POLL_INTERVAL);
+        }
+        None
+    }
+
     /// Write data to PTY stdin
     pub fn write_to_session(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
         let session = self
@@ -245,8 +349,15 @@ impl PtySessionManager {
         Ok(())
     }
 
-    /// Resize PTY
-    pub fn resize_session(&mut self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
+    /// Resize PTY. Pixel dimensions are 0 when unknown.
+    pub fn resize_session(
+        &mut self,
+        session_id: &str,
+        cols: u16,
+        rows: u16,
+        pixel_width: u16,
+        pixel_height: u16,
+    ) -> Result<(), String> {
         let session = self
             .sessions
             .get_mut(session_id)
@@ -261,13 +372,15 @@ impl PtySessionManager {
             .resize(PtySize {
                 rows,
                 cols,
-                pixel_width: 0,
-                pixel_height: 0,
+                pixel_width,
+                pixel_height,
             })
             .map_err(|e| format!("Failed to resize PTY: {}", e))?;
 
         session.cols = cols;
         session.rows = rows;
+        session.pixel_width = pixel_width;
+        session.pixel_height = pixel_height;
 
         Ok(())
     }
@@ -300,11 +413,77 @@ impl PtySessionManager {
             .collect()
     }
 
+    /// List the sessions owned by one window
+    pub fn list_window_sessions(&self, window: &str) -> Vec<PtySessionInfo> {
+        self.sessions
+            .values()
+            .filter(|s| s.window == window)
+            .map(|s| self.session_to_info(s))
+            .collect()
+    }
+
+    /// Kill every session owned by a window that is closing. Returns the
+    /// IDs that were killed.
+    pub fn kill_window_sessions(&mut self, window: &str) -> Vec<String> {
+        let ids: Vec<String> = self
+            .sessions
+            .values()
+            .filter(|s| s.window == window)
+            .map(|s| s.id.clone())
+            .collect();
+        for id in &ids {
+            if let Err(e) = self.kill_session(id) {
+                error!("{}", e);
+            }
+        }
+        ids
+    }
+
+    /// Session ID, owning window and shell PID of every session (the PID is
+    /// None once the shell has exited)
+    pub fn session_pids(&self) -> Vec<(String, String, Option<u32>)> {
+        self.sessions
+            .values()
+            .map(|s| (s.id.clone(), s.window.clone(), Self::live_pid(s)))
+            .collect()
+    }
+
+    /// PID of a session's shell, if it is still running
+    pub fn shell_pid(&self, session_id: &str) -> Result<u32, String> {
+        let session = self
+            .sessions
+            .get(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        Self::live_pid(session).ok_or_else(|| format!("Session has exited: {}", session_id))
+    }
+
+    fn live_pid(session: &PtySession) -> Option<u32> {
+        let mut child = session.child.lock().ok()?;
+        match child.try_wait() {
+            Ok(None) => child.process_id(),
+            _ => None,
+        }
+    }
+
     /// Get a specific session
     pub fn get_session(&self, session_id: &str) -> Option<PtySessionInfo> {
         self.sessions.get(session_id).map(|s| self.session_to_info(s))
     }
 
+    /// IDs of all sessions known to the manager (alive or exited)
+    pub fn session_ids(&self) -> HashSet<String> {
+        self.sessions.keys().cloned().collect()
+    }
+
+    /// IDs of sessions whose shell is still running
+    pub fn running_session_ids(&self) -> HashSet<String> {
+        self.sessions
+            .values()
+            .filter(|session| Self::live_pid(session).is_some())
+            .map(|session| session.id.clone())
+            .collect()
+    }
+
     /// Convert session to info struct
     fn session_to_info(&self, session: &PtySession) -> PtySessionInfo {
         let is_alive = session
@@ -319,8 +498,12 @@ impl PtySessionManager {
             args: session.args.clone(),
             cwd: session.cwd.clone(),
             created_at: session.created_at.to_rfc3339(),
+            window: session.window.clone(),
             cols: session.cols,
             rows: session.rows,
+            pixel_width: session.pixel_width,
+            pixel_height: session.pixel_height,
+            profile: session.profile.clone(),
             is_alive,
         }
     }
@@ -0,0 +1,198 @@
+use serde::Serialize;
+use std::path::Path;
+
+const SHELLS_FILE: &str = "/etc/shells";
+
+/// Tried in order when neither the preference, passwd nor `$SHELL` gives a usable shell
+const FALLBACK_SHELLS: &[&str] = &["/bin/zsh", "/bin/bash", "/bin/sh"];
+
+/// Where the shell for a new session came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShellSource {
+    Preference,
+    Passwd,
+    Environment,
+    Fallback,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedShell {
+    pub path: String,
+    pub source: ShellSource,
+}
+
+/// A shell listed in /etc/shells, for the settings panel
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailableShell {
+    pub path: String,
+    /// File name, e.g. `zsh`
+    pub name: String,
+    /// The user's login shell from the passwd database
+    pub is_login_shell: bool,
+}
+
+/// Shell used when no command is given: the `shell_path` preference if set,
+/// then the passwd entry, then `$SHELL`, then the first fallback that exists.
+/// An explicitly chosen shell that fails validation is an error rather than
+/// being silently replaced.
+pub fn resolve_shell(preference: &str) -> Result<ResolvedShell, String> {
+    let preference = preference.trim();
+    if !preference.is_empty() {
+        let path = shellexpand::tilde(preference).to_string();
+        validate_shell(&path)?;
+        return Ok(ResolvedShell {
+            path,
+            source: ShellSource::Preference,
+        });
+    }
+
+    if let Some(path) = login_shell().filter(|path| is_executable(Path::new(path))) {
+        return Ok(ResolvedShell {
+            path,
+            source: ShellSource::Passwd,
+        });
+    }
+
+    if let Some(path) = std::env::var("SHELL")
+        .ok()
+        .filter(|path| is_executable(Path::new(path)))
+    {
+        return Ok(ResolvedShell {
+            path,
+            source: ShellSource::Environment,
+        });
+    }
+
+    FALLBACK_SHELLS
+        .iter()
+        .find(|path| is_executable(Path::new(path)))
+        .map(|path| ResolvedShell {
+            path: path.to_string(),
+            source: ShellSource::Fallback,
+        })
+        .ok_or_else(|| "No usable shell found; set a shell path in Settings".to_string())
+}
+
+/// Check that `path` is an executable file listed in /etc/shells (the same
+/// rule `chsh` applies). The listing check is skipped where /etc/shells is
+/// missing.
+pub fn validate_shell(path: &str) -> Result<(), String> {
+    let shell = Path::new(path);
+    if !shell.is_absolute() {
+        return Err(format!("Shell path {:?} must be absolute", path));
+    }
+    if !shell.is_file() {
+        return Err(format!("Shell {:?} does not exist", path));
+    }
+    if !is_executable(shell) {
+        return Err(format!("Shell {:?} is not executable", path));
+    }
+
+    let Some(listed) = read_shells_file() else {
+        return Ok(());
+    };
+    let canonical = std::fs::canonicalize(shell).ok();
+    let is_listed = listed.iter().any(|entry| {
+        entry == path
+            || canonical.is_some()
+                && std::fs::canonicalize(entry).ok().as_ref() == canonical.as_ref()
+    });
+    if is_listed {
+        Ok(())
+    } else {
+        Err(format!(
+            "Shell {:?} is not listed in {}; add it there to use it as a login shell",
+            path, SHELLS_FILE
+        ))
+    }
+}
+
+/// Installed shells from /etc/shells (duplicates and missing entries removed)
+pub fn available_shells() -> Vec<AvailableShell> {
+    let login = login_shell();
+    let mut shells: Vec<AvailableShell> = Vec::new();
+    for path in read_shells_file().unwrap_or_default() {
+        if !is_executable(Path::new(&path)) || shells.iter().any(|s| s.path == path) {
+            continue;
+        }
+        shells.push(AvailableShell {
+            name: Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone()),
+            is_login_shell: login.as_deref() == Some(path.as_str()),
+            path,
+        });
+    }
+    shells
+}
+
+/// Entries from /etc/shells, or None if the file can't be read
+fn read_shells_file() -> Option<Vec<String>> {
+    let contents = std::fs::read_to_string(SHELLS_FILE).ok()?;
+    Some(
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect(),
+    )
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Login shell from the passwd database. Unlike `$SHELL`, this is set even
+/// when the app is started from a desktop launcher.
+#[cfg(unix)]
+pub fn login_shell() -> Option<String> {
+    use std::ffi::CStr;
+
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+
+    loop {
+        // SAFETY: every pointer refers to a live local and buf.len() is its capacity
+        let rc = unsafe {
+            libc::getpwuid_r(
+                libc::getuid(),
+                &mut passwd,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        if rc == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() || passwd.pw_shell.is_null() {
+            return None;
+        }
+        break;
+    }
+
+    // SAFETY: pw_shell points into buf, which outlives this borrow
+    let shell = unsafe { CStr::from_ptr(passwd.pw_shell) }
+        .to_string_lossy()
+        .to_string();
+    (!shell.is_empty()).then_some(shell)
+}
+
+#[cfg(not(unix))]
+pub fn login_shell() -> Option<String> {
+    None
+}
@@ -0,0 +1,232 @@
+use super::process::{child_map, descendants};
+use crate::config::effective_preferences;
+use crate::AppState;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info};
+
+/// Processes listed per session in `top_processes`
+const TOP_PROCESSES: usize = 5;
+/// Shortest allowed `session_stats_interval_ms` (0 disables sampling)
+pub const MIN_STATS_INTERVAL_MS: i32 = 250;
+/// An on-demand sample older than this takes a second refresh so CPU usage
+/// covers a fresh window instead of the time since the last sample
+const STALE_SAMPLE: Duration = Duration::from_secs(5);
+
+/// One process in a session's tree
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessStats {
+    pub pid: u32,
+    pub name: String,
+    /// Percent of one core (can exceed 100 for multi-threaded processes)
+    pub cpu_percent: f32,
+    /// Resident set size
+    pub memory_bytes: u64,
+}
+
+/// Resource usage of a session's shell and all of its descendants
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStats {
+    pub session_id: String,
+    /// Shell process; None if the session has exited
+    pub pid: Option<u32>,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// Total threads, where the platform reports them (Linux)
+    pub threads: Option<usize>,
+    pub process_count: usize,
+    /// Busiest processes by CPU, then memory
+    pub top_processes: Vec<ProcessStats>,
+}
+
+/// Process table kept between samples (CPU usage is the difference from the
+/// previous refresh) plus the periodic sampler's state. Nothing is allocated
+/// until the first sample, and the table is only kept while the periodic
+/// sampler runs.
+#[derive(Default)]
+pub struct SessionStatsState {
+    system: Option<System>,
+    refreshed: Option<Instant>,
+    /// Periodic `session-stats` interval; 0 when disabled
+    interval_ms: u64,
+    sampler_running: bool,
+}
+
+impl SessionStatsState {
+    fn refresh(&mut self) -> &System {
+        let system = self.system.get_or_insert_with(System::new);
+        let kind = ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory()
+            .with_tasks();
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+        self.refreshed = Some(Instant::now());
+        system
+    }
+
+    /// Refresh, first taking a baseline if the last refresh is missing or
+    /// stale so CPU figures are meaningful
+    fn refresh_for_cpu(&mut self) -> &System {
+        if self.refreshed.is_none_or(|at| at.elapsed() > STALE_SAMPLE) {
+            self.refresh();
+            std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        }
+        self.refresh()
+    }
+
+    /// Free the process table
+    fn release(&mut self) {
+        self.system = None;
+        self.refreshed = None;
+    }
+}
+
+/// Aggregate each session's process tree. `sessions` pairs session IDs with
+/// their shell PID.
+fn collect(system: &System, sessions: &[(String, Option<u32>)]) -> Vec<SessionStats> {
+    let children = child_map(system);
+
+    sessions
+        .iter()
+        .map(|(session_id, pid)| {
+            let mut stats = SessionStats {
+                session_id: session_id.clone(),
+                pid: *pid,
+                cpu_percent: 0.0,
+                memory_bytes: 0,
+                threads: None,
+                process_count: 0,
+                top_processes: Vec::new(),
+            };
+            let Some(root) = pid.map(Pid::from_u32) else {
+                return stats;
+            };
+
+            let mut processes = Vec::new();
+            for pid in descendants(system, &children, root) {
+                let Some(process) = system.process(pid) else {
+                    continue;
+                };
+                stats.cpu_percent += process.cpu_usage();
+                stats.memory_bytes += process.memory();
+                // Threads are left out of the tree and counted here instead
+                if let Some(tasks) = process.tasks() {
+                    *stats.threads.get_or_insert(0) += tasks.len().max(1);
+                }
+                processes.push(ProcessStats {
+                    pid: pid.as_u32(),
+                    name: process.name().to_string_lossy().to_string(),
+                    cpu_percent: process.cpu_usage(),
+                    memory_bytes: process.memory(),
+                });
+            }
+
+            stats.process_count = processes.len();
+            processes.sort_by(|a, b| {
+                b.cpu_percent
+                    .total_cmp(&a.cpu_percent)
+                    .then(b.memory_bytes.cmp(&a.memory_bytes))
+            });
+            processes.truncate(TOP_PROCESSES);
+            stats.top_processes = processes;
+            stats
+        })
+        .collect()
+}
+
+/// Sample the given sessions now. Without the periodic sampler the process
+/// table is freed again afterwards, so the next sample takes a new baseline.
+pub fn sample_sessions(
+    state: &AppState,
+    sessions: &[(String, Option<u32>)],
+) -> Result<Vec<SessionStats>, String> {
+    let mut stats = state
+        .session_stats
+        .lock()
+        .map_err(|e| format!("Failed to lock session stats: {}", e))?;
+    let sampled = collect(stats.refresh_for_cpu(), sessions);
+    if !stats.sampler_running {
+        stats.release();
+    }
+    Ok(sampled)
+}
+
+/// Start, retune or stop the periodic `session-stats` event to match the
+/// `session_stats_interval_ms` preference. Called at startup and whenever
+/// preferences change.
+pub fn apply_stats_interval(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let interval_ms = match effective_preferences(&state) {
+        Ok(prefs) => prefs.session_stats_interval_ms.max(0) as u64,
+        Err(e) => {
+            error!("{}", e);
+            return;
+        }
+    };
+
+    let Ok(mut stats) = state.session_stats.lock() else {
+        return;
+    };
+    stats.interval_ms = interval_ms;
+    if interval_ms > 0 && !stats.sampler_running {
+        stats.sampler_running = true;
+        let app = app.clone();
+        std::thread::spawn(move || run_sampler(app));
+        info!("Session stats sampling every {}ms", interval_ms);
+    }
+}
+
+/// Emit each window's session stats every interval until sampling is disabled
+fn run_sampler(app: AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        let interval = match state.session_stats.lock() {
+            Ok(stats) if stats.interval_ms > 0 => stats.interval_ms,
+            Ok(mut stats) => {
+                // Disabled: free the process table and stop
+                stats.sampler_running = false;
+                stats.release();
+                info!("Session stats sampling stopped");
+                return;
+            }
+            Err(_) => return,
+        };
+        std::thread::sleep(Duration::from_millis(interval));
+
+        let sessions = match state.pty_manager.lock() {
+            Ok(manager) => manager.session_pids(),
+            Err(e) => {
+                error!("Failed to lock PTY manager: {}", e);
+                continue;
+            }
+        };
+        if sessions.is_empty() {
+            continue;
+        }
+
+        let sampled = match state.session_stats.lock() {
+            Ok(mut stats) => {
+                let roots: Vec<(String, Option<u32>)> = sessions
+                    .iter()
+                    .map(|(id, _, pid)| (id.clone(), *pid))
+                    .collect();
+                collect(stats.refresh(), &roots)
+            }
+            Err(_) => return,
+        };
+
+        // Each window only hears about its own sessions
+        let mut by_window: HashMap<&str, Vec<SessionStats>> = HashMap::new();
+        for ((_, window, _), stats) in sessions.iter().zip(sampled) {
+            by_window.entry(window.as_str()).or_default().push(stats);
+        }
+        for (window, stats) in by_window {
+            if let Err(e) = app.emit_to(window, "session-stats", stats) {
+                error!("Failed to emit session stats: {}", e);
+            }
+        }
+    }
+}
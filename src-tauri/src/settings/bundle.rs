@@ -0,0 +1,488 @@
+use crate::config::{validate_chord, ConfigFile};
+use crate::clipboard::CLIPBOARD_POLICIES;
+use crate::downloads::DOWNLOAD_POLICIES;
+use crate::keybindings::find_action;
+use crate::layout::model::TerminalLayout;
+use crate::pty::{validate_shell, MIN_STATS_INTERVAL_MS};
+use crate::storage::database::{
+    ColorSchemeRecord, LayoutSnapshotRecord, SettingsImport, TerminalPreferences,
+};
+use crate::themes::{builtin_scheme, ColorScheme};
+use crate::webview::check_user_agent;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use thiserror::Error;
+
+/// Marker stored in every bundle so other JSON/TOML files are rejected
+pub const BUNDLE_FORMAT: &str = "wiz-term-settings";
+/// Bumped whenever a bundled type gains or changes fields. Older bundles
+/// still import; what they lack takes its default.
+///
+/// - 1: preferences up to `color_scheme_light`
+/// - 2: download, clipboard, attachment, session stats, maintenance and
+///   webview preferences; `[downloads]`, `[clipboard]`, `[attachments]`,
+///   `[maintenance]` and `[browser]` config sections
+pub const BUNDLE_VERSION: u32 = 2;
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Unrecognized settings file {0} (expected .json or .toml)")]
+    UnknownFormat(String),
+    #[error("Failed to parse settings bundle: {0}")]
+    Parse(String),
+    #[error("Failed to serialize settings bundle: {0}")]
+    Serialize(String),
+    #[error("Not a wiz-term settings bundle (format {0:?})")]
+    WrongFormat(String),
+    #[error("Settings bundle version {found} is newer than supported version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("Settings bundle is invalid:\n  - {}", .0.join("\n  - "))]
+    Invalid(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Json,
+    Toml,
+}
+
+impl BundleFormat {
+    pub fn from_path(path: &Path) -> Result<Self, BundleError> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("json") => Ok(BundleFormat::Json),
+            Some("toml") => Ok(BundleFormat::Toml),
+            _ => Err(BundleError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+/// A named layout snapshot as stored in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceSnapshot {
+    pub workspace: String,
+    pub name: String,
+    pub created_at: i64,
+    pub layout: TerminalLayout,
+}
+
+/// Everything needed to set up wiz-term on another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsBundle {
+    pub format: String,
+    pub version: u32,
+    /// RFC 3339 timestamp
+    pub exported_at: String,
+    /// Preferences stored in SQLite (config.toml values travel in `config`)
+    pub preferences: Option<TerminalPreferences>,
+    /// Custom and imported color schemes (built-ins are not exported)
+    #[serde(default)]
+    pub themes: Vec<ColorScheme>,
+    /// Keybindings saved from the UI
+    #[serde(default)]
+    pub keybindings: BTreeMap<String, String>,
+    /// Named layout snapshots from every workspace
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceSnapshot>,
+    /// config.toml contents, including launch profiles
+    pub config: Option<ConfigFile>,
+}
+
+/// Fields read before the full parse so a wrong or newer file gets a clear error
+#[derive(Deserialize)]
+struct BundleHeader {
+    format: Option<String>,
+    version: Option<u32>,
+}
+
+/// Validated bundle contents, ready to write
+#[derive(Debug)]
+pub struct PreparedImport {
+    pub import: SettingsImport,
+    pub config: Option<ConfigFile>,
+    /// Problems that don't block the import, e.g. a shell missing on this machine
+    pub warnings: Vec<String>,
+}
+
+impl SettingsBundle {
+    pub fn new() -> Self {
+        Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            preferences: None,
+            themes: Vec::new(),
+            keybindings: BTreeMap::new(),
+            workspaces: Vec::new(),
+            config: None,
+        }
+    }
+
+    pub fn parse(contents: &str, format: BundleFormat) -> Result<Self, BundleError> {
+        let header: BundleHeader = match format {
+            BundleFormat::Json => {
+                serde_json::from_str(contents).map_err(|e| BundleError::Parse(e.to_string()))?
+            }
+            BundleFormat::Toml => {
+                toml::from_str(contents).map_err(|e| BundleError::Parse(e.to_string()))?
+            }
+        };
+        let found = header.format.unwrap_or_default();
+        if found != BUNDLE_FORMAT {
+            return Err(BundleError::WrongFormat(found));
+        }
+        let version = header.version.unwrap_or_default();
+        if version > BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion {
+                found: version,
+                supported: BUNDLE_VERSION,
+            });
+        }
+
+        match format {
+            BundleFormat::Json => {
+                serde_json::from_str(contents).map_err(|e| BundleError::Parse(e.to_string()))
+            }
+            BundleFormat::Toml => {
+                toml::from_str(contents).map_err(|e| BundleError::Parse(e.to_string()))
+            }
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, BundleError> {
+        let format = BundleFormat::from_path(path)?;
+        let contents = std::fs::read_to_string(path).map_err(|source| BundleError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::parse(&contents, format)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), BundleError> {
+        let contents = match BundleFormat::from_path(path)? {
+            BundleFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| BundleError::Serialize(e.to_string()))?,
+            BundleFormat::Toml => {
+                toml::to_string_pretty(self).map_err(|e| BundleError::Serialize(e.to_string()))?
+            }
+        };
+        std::fs::write(path, contents).map_err(|source| BundleError::Io {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    /// Validate every section against the current schema. `existing_schemes`
+    /// are stored schemes that survive the import (empty when replacing).
+    pub fn prepare(self, existing_schemes: &HashSet<String>) -> Result<PreparedImport, BundleError> {
+        let mut problems = Vec::new();
+        let mut warnings = Vec::new();
+        let mut import = SettingsImport::default();
+
+        let mut theme_names = HashSet::new();
+        for mut scheme in self.themes {
+            let name = scheme.name.clone();
+            if let Err(e) = scheme.normalize() {
+                problems.push(format!("themes.{:?}: {}", name, e));
+                continue;
+            }
+            if builtin_scheme(&scheme.name).is_some() {
+                problems.push(format!("themes.{:?} replaces a built-in scheme", scheme.name));
+                continue;
+            }
+            if !theme_names.insert(scheme.name.clone()) {
+                problems.push(format!("themes.{:?} appears more than once", scheme.name));
+                continue;
+            }
+            match serde_json::to_string(&scheme.palette) {
+                Ok(palette_json) => import.color_schemes.push(ColorSchemeRecord {
+                    variant: scheme.variant.as_str().to_string(),
+                    name: scheme.name,
+                    palette_json,
+                    source: scheme.source,
+                    updated_at: 0,
+                }),
+                Err(e) => problems.push(format!("themes.{:?}: {}", name, e)),
+            }
+        }
+
+        if let Some(prefs) = &self.preferences {
+            let scheme_exists = |name: &str| {
+                builtin_scheme(name).is_some()
+                    || theme_names.contains(name)
+                    || existing_schemes.contains(name)
+            };
+            problems.extend(preference_problems(prefs, scheme_exists));
+            let shell = prefs.shell_path.trim();
+            if !shell.is_empty() {
+                if let Err(e) = validate_shell(&shellexpand::tilde(shell)) {
+                    warnings.push(format!("preferences.shell_path: {} on this machine", e));
+                }
+            }
+        }
+        import.preferences = self.preferences;
+
+        for (action, chord) in &self.keybindings {
+            if find_action(action).is_none() {
+                problems.push(format!("keybindings.{} is not a known action", action));
+            } else if !chord.trim().is_empty() {
+                if let Err(e) = validate_chord(chord) {
+                    problems.push(format!("keybindings.{}: {}", action, e));
+                }
+            }
+        }
+        import.keybindings = self.keybindings;
+
+        for snapshot in self.workspaces {
+            let label = format!("workspaces.{:?}.{:?}", snapshot.workspace, snapshot.name);
+            if snapshot.workspace.trim().is_empty() || snapshot.name.trim().is_empty() {
+                problems.push(format!("{} needs a workspace and a name", label));
+                continue;
+            }
+            // Round-trip through the layout loader to migrate and validate the tree
+            let layout = serde_json::to_string(&snapshot.layout)
+                .map_err(|e| e.to_string())
+                .and_then(|json| TerminalLayout::from_json(&json).map_err(|e| e.to_string()))
+                .and_then(|layout| layout.to_json().map_err(|e| e.to_string()));
+            match layout {
+                Ok(layout_json) => import.layout_snapshots.push(LayoutSnapshotRecord {
+                    workspace: snapshot.workspace,
+                    name: snapshot.name,
+                    layout_json,
+                    created_at: snapshot.created_at,
+                }),
+                Err(e) => problems.push(format!("{}: {}", label, e)),
+            }
+        }
+
+        if let Some(config) = &self.config {
+            problems.extend(config.problems().into_iter().map(|p| format!("config: {}", p)));
+            warnings.extend(config.warnings().into_iter().map(|w| format!("config: {}", w)));
+        }
+
+        if !problems.is_empty() {
+            return Err(BundleError::Invalid(problems));
+        }
+        Ok(PreparedImport {
+            import,
+            config: self.config,
+            warnings,
+        })
+    }
+}
+
+impl Default for SettingsBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Range checks matching the settings panel and config.toml
+fn preference_problems(
+    prefs: &TerminalPreferences,
+    scheme_exists: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !(6..=72).contains(&prefs.font_size) {
+        problems.push(format!(
+            "preferences.font_size must be between 6 and 72 (got {})",
+            prefs.font_size
+        ));
+    }
+    if prefs.font_family.trim().is_empty() {
+        problems.push("preferences.font_family cannot be empty".to_string());
+    }
+    if !(0..=1_000_000).contains(&prefs.scrollback) {
+        problems.push(format!(
+            "preferences.scrollback must be between 0 and 1000000 (got {})",
+            prefs.scrollback
+        ));
+    }
+    if prefs.minimap_refresh_ms < 16 {
+        problems.push(format!(
+            "preferences.minimap_refresh_ms must be at least 16 (got {})",
+            prefs.minimap_refresh_ms
+        ));
+    }
+    if prefs.session_stats_interval_ms != 0
+        && prefs.session_stats_interval_ms < MIN_STATS_INTERVAL_MS
+    {
+        problems.push(format!(
+            "preferences.session_stats_interval_ms must be 0 or at least {} (got {})",
+            MIN_STATS_INTERVAL_MS, prefs.session_stats_interval_ms
+        ));
+    }
+    for (key, value) in [
+        ("session_retention_days", prefs.session_retention_days),
+        ("temp_file_retention_days", prefs.temp_file_retention_days),
+        ("maintenance_interval_hours", prefs.maintenance_interval_hours),
+        ("download_max_mb", prefs.download_max_mb),
+        ("clipboard_max_kb", prefs.clipboard_max_kb),
+        ("attachment_max_mb", prefs.attachment_max_mb),
+    ] {
+        if value < 0 {
+            problems.push(format!("preferences.{} cannot be negative (got {})", key, value));
+        }
+    }
+    if !DOWNLOAD_POLICIES.contains(&prefs.download_policy.as_str()) {
+        problems.push(format!(
+            "preferences.download_policy must be one of {} (got {:?})",
+            DOWNLOAD_POLICIES.join(", "),
+            prefs.download_policy
+        ));
+    }
+    for (key, policy) in [
+        ("clipboard_write", &prefs.clipboard_write),
+        ("clipboard_read", &prefs.clipboard_read),
+    ] {
+        if !CLIPBOARD_POLICIES.contains(&policy.as_str()) {
+            problems.push(format!(
+                "preferences.{} must be one of {} (got {:?})",
+                key,
+                CLIPBOARD_POLICIES.join(", "),
+                policy
+            ));
+        }
+    }
+    if let Some(problem) = check_user_agent(&prefs.webview_user_agent) {
+        problems.push(format!("preferences.webview_user_agent {}", problem));
+    }
+    if !scheme_exists(&prefs.color_scheme) {
+        problems.push(format!(
+            "preferences.color_scheme {:?} is not a known scheme",
+            prefs.color_scheme
+        ));
+    }
+    if let Some(light) = &prefs.color_scheme_light {
+        if !scheme_exists(light) {
+            problems.push(format!(
+                "preferences.color_scheme_light {:?} is not a known scheme",
+                light
+            ));
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A bundle as version 1 exported it: preferences stop at
+    /// `color_scheme_light` and config.toml has no later sections
+    const VERSION_1_BUNDLE: &str = r#"{
+        "format": "wiz-term-settings",
+        "version": 1,
+        "exported_at": "2026-01-05T09:30:00+00:00",
+        "preferences": {
+            "font_size": 15,
+            "font_family": "JetBrains Mono",
+            "scrollback": 20000,
+            "cursor_blink": false,
+            "minimap_refresh_ms": 250,
+            "use_webgl": false,
+            "shell_path": "",
+            "color_scheme": "wiz-term Dark",
+            "color_scheme_light": null
+        },
+        "themes": [],
+        "keybindings": { "split_horizontal": "CmdOrCtrl+Shift+D" },
+        "workspaces": [],
+        "config": {
+            "font": { "size": 14 },
+            "profiles": { "dev": { "command": "npm", "args": ["run", "dev"] } }
+        }
+    }"#;
+
+    fn import(bundle: SettingsBundle) -> PreparedImport {
+        bundle
+            .prepare(&HashSet::new())
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[test]
+    fn version_1_bundle_imports_with_defaults() {
+        let bundle = SettingsBundle::parse(VERSION_1_BUNDLE, BundleFormat::Json).unwrap();
+        assert_eq!(bundle.version, 1);
+
+        let prepared = import(bundle);
+        let prefs = prepared.import.preferences.unwrap();
+        assert_eq!(prefs.font_size, 15);
+        assert_eq!(prefs.font_family, "JetBrains Mono");
+        assert!(!prefs.use_webgl);
+
+        let defaults = TerminalPreferences::default();
+        assert_eq!(prefs.download_policy, defaults.download_policy);
+        assert_eq!(prefs.clipboard_read, defaults.clipboard_read);
+        assert_eq!(prefs.webview_user_agent, defaults.webview_user_agent);
+        assert_eq!(
+            prefs.session_retention_days,
+            defaults.session_retention_days
+        );
+
+        let config = prepared.config.unwrap();
+        assert_eq!(config.font.size, Some(14));
+        assert_eq!(config.profiles["dev"].command.as_deref(), Some("npm"));
+        assert_eq!(
+            prepared.import.keybindings["split_horizontal"],
+            "CmdOrCtrl+Shift+D"
+        );
+    }
+
+    #[test]
+    fn version_1_bundle_round_trips_at_current_version() {
+        let prepared = import(SettingsBundle::parse(VERSION_1_BUNDLE, BundleFormat::Json).unwrap());
+        let mut bundle = SettingsBundle::new();
+        bundle.preferences = prepared.import.preferences;
+        bundle.keybindings = prepared.import.keybindings;
+        bundle.config = prepared.config;
+        let expected = serde_json::to_value(&bundle.preferences).unwrap();
+
+        for format in [BundleFormat::Json, BundleFormat::Toml] {
+            let contents = match format {
+                BundleFormat::Json => serde_json::to_string_pretty(&bundle).unwrap(),
+                BundleFormat::Toml => toml::to_string_pretty(&bundle).unwrap(),
+            };
+            let reread = SettingsBundle::parse(&contents, format).unwrap();
+            assert_eq!(reread.version, BUNDLE_VERSION);
+
+            let prepared = import(reread);
+            assert_eq!(
+                serde_json::to_value(&prepared.import.preferences).unwrap(),
+                expected,
+                "{:?}",
+                format
+            );
+            assert_eq!(
+                prepared.config.unwrap().profiles["dev"].args,
+                ["run", "dev"]
+            );
+        }
+    }
+
+    #[test]
+    fn newer_bundle_is_refused() {
+        let contents = VERSION_1_BUNDLE.replace(
+            "\"version\": 1",
+            &format!("\"version\": {}", BUNDLE_VERSION + 1),
+        );
+        assert!(matches!(
+            SettingsBundle::parse(&contents, BundleFormat::Json),
+            Err(BundleError::UnsupportedVersion { .. })
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+use super::bundle::{SettingsBundle, WorkspaceSnapshot};
+use crate::config::{broadcast_preferences, IMPORTED_CONFIG_FILE_NAME};
+use crate::keybindings::apply_keybindings;
+use crate::layout::model::TerminalLayout;
+use crate::themes::{builtin_scheme, scheme_from_record, DEFAULT_SCHEME};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Add to existing settings; bundle values win on conflicts
+    Merge,
+    /// Remove custom schemes, keybindings and snapshots not in the bundle
+    Replace,
+}
+
+/// What an export or import wrote (or would write, for a dry run)
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsSummary {
+    pub path: String,
+    pub preferences: bool,
+    pub themes: usize,
+    pub keybindings: usize,
+    pub workspaces: usize,
+    pub config: bool,
+    /// Where the imported config was (or would be) written
+    pub config_file: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+fn expand_path(path: &str) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(path).to_string())
+}
+
+fn stored_scheme_names(state: &AppState) -> Result<HashSet<String>, String> {
+    Ok(state
+        .db
+        .list_color_schemes()
+        .map_err(|e| format!("Failed to list color schemes: {}", e))?
+        .into_iter()
+        .map(|record| record.name)
+        .collect())
+}
+
+/// Write preferences, custom themes, keybindings, layout snapshots and
+/// config.toml to a `.json` or `.toml` bundle
+#[tauri::command]
+pub async fn settings_export(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<SettingsSummary, String> {
+    let mut bundle = SettingsBundle::new();
+
+    bundle.preferences = Some(
+        state
+            .db
+            .get_terminal_preferences()
+            .map_err(|e| format!("Failed to get preferences: {}", e))?,
+    );
+
+    let schemes = state
+        .db
+        .list_color_schemes()
+        .map_err(|e| format!("Failed to list color schemes: {}", e))?;
+    for record in schemes {
+        bundle.themes.push(scheme_from_record(record)?);
+    }
+
+    bundle.keybindings = state
+        .db
+        .get_keybindings()
+        .map_err(|e| format!("Failed to get keybindings: {}", e))?;
+
+    let mut warnings = Vec::new();
+    let snapshots = state
+        .db
+        .list_all_layout_snapshots()
+        .map_err(|e| format!("Failed to list layout snapshots: {}", e))?;
+    for snapshot in snapshots {
+        match TerminalLayout::from_json(&snapshot.layout_json) {
+            Ok(layout) => bundle.workspaces.push(WorkspaceSnapshot {
+                workspace: snapshot.workspace,
+                name: snapshot.name,
+                created_at: snapshot.created_at,
+                layout,
+            }),
+            Err(e) => warnings.push(format!(
+                "Skipped snapshot {:?} in workspace {:?}: {}",
+                snapshot.name, snapshot.workspace, e
+            )),
+        }
+    }
+
+    bundle.config = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .file
+        .clone();
+
+    let target = expand_path(&path);
+    bundle.write(&target).map_err(|e| e.to_string())?;
+    tracing::info!("Exported settings to {}", target.display());
+
+    Ok(SettingsSummary {
+        path: target.to_string_lossy().to_string(),
+        preferences: bundle.preferences.is_some(),
+        themes: bundle.themes.len(),
+        keybindings: bundle.keybindings.len(),
+        workspaces: bundle.workspaces.len(),
+        config: bundle.config.is_some(),
+        config_file: None,
+        warnings,
+    })
+}
+
+/// Validate a bundle and write it to the database. Its config becomes
+/// config.toml only if there is none yet; an existing one is left alone and
+/// the config goes to `config.imported.toml` beside it, to merge by hand.
+/// Nothing is written if any section fails validation or `dry_run` is set.
+#[tauri::command]
+pub async fn settings_import(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    path: String,
+    mode: ImportMode,
+    dry_run: Option<bool>,
+) -> Result<SettingsSummary, String> {
+    let source = expand_path(&path);
+    let bundle = SettingsBundle::read(&source).map_err(|e| e.to_string())?;
+
+    // When replacing, stored schemes are deleted and can't satisfy references
+    let existing_schemes = match mode {
+        ImportMode::Merge => stored_scheme_names(&state)?,
+        ImportMode::Replace => HashSet::new(),
+    };
+    let mut prepared = bundle.prepare(&existing_schemes).map_err(|e| e.to_string())?;
+
+    let config_path = state
+        .config
+        .lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .path
+        .clone();
+    let config_target = prepared.config.is_some().then(|| {
+        if config_path.exists() {
+            config_path.with_file_name(IMPORTED_CONFIG_FILE_NAME)
+        } else {
+            config_path.clone()
+        }
+    });
+    if let Some(target) = &config_target {
+        if *target != config_path {
+            prepared.warnings.push(format!(
+                "config.toml is left unchanged; the bundle's config goes to {}",
+                target.display()
+            ));
+        }
+    }
+
+    let summary = SettingsSummary {
+        path: source.to_string_lossy().to_string(),
+        preferences: prepared.import.preferences.is_some(),
+        themes: prepared.import.color_schemes.len(),
+        keybindings: prepared.import.keybindings.len(),
+        workspaces: prepared.import.layout_snapshots.len(),
+        config: prepared.config.is_some(),
+        config_file: config_target
+            .as_ref()
+            .map(|target| target.to_string_lossy().to_string()),
+        warnings: prepared.warnings,
+    };
+    if dry_run.unwrap_or(false) {
+        return Ok(summary);
+    }
+
+    // A replace drops the active scheme if the bundle doesn't bring it back
+    let mut import = prepared.import;
+    if mode == ImportMode::Replace && import.preferences.is_none() {
+        let mut prefs = state
+            .db
+            .get_terminal_preferences()
+            .map_err(|e| format!("Failed to get preferences: {}", e))?;
+        let kept = |name: &str| {
+            builtin_scheme(name).is_some() || import.color_schemes.iter().any(|s| s.name == name)
+        };
+        let mut changed = false;
+        if !kept(&prefs.color_scheme) {
+            prefs.color_scheme = DEFAULT_SCHEME.to_string();
+            changed = true;
+        }
+        if prefs.color_scheme_light.as_deref().is_some_and(|name| !kept(name)) {
+            prefs.color_scheme_light = None;
+            changed = true;
+        }
+        if changed {
+            import.preferences = Some(prefs);
+        }
+    }
+
+    state
+        .db
+        .import_settings(&import, mode == ImportMode::Replace)
+        .map_err(|e| format!("Failed to import settings: {}", e))?;
+
+    if let (Some(imported), Some(target)) = (prepared.config, config_target) {
+        let mut config = state
+            .config
+            .lock()
+            .map_err(|e| format!("Failed to lock config: {}", e))?;
+        let file = match (mode, config.file.clone()) {
+            (ImportMode::Merge, Some(mut current)) => {
+                current.merge_from(imported);
+                current
+            }
+            _ => imported,
+        };
+        file.save(&target)?;
+        if target == config.path {
+            // Reload now rather than waiting for the watcher so the broadcast below is current
+            config.reload()?;
+        }
+    }
+
+    tracing::info!("Imported settings from {} ({:?})", source.display(), mode);
+    broadcast_preferences(&app, &state)?;
+    apply_keybindings(&app, &state)?;
+
+    Ok(summary)
+}
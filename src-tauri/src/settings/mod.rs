@@ -0,0 +1,5 @@
+pub mod bundle;
+pub mod commands;
+
+pub use bundle::*;
+pub use commands::*;
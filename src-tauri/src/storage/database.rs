@@ -1,10 +1,28 @@
+use super::migrations::{self, MigrationError};
+use crate::themes::DEFAULT_SCHEME;
 use chrono::Utc;
-use rusqlite::{params, Connection, Result as SqliteResult};
-use std::path::PathBuf;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Result as SqliteResult};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+pub const DEFAULT_SESSION_RETENTION_DAYS: i32 = 7;
+pub const DEFAULT_TEMP_FILE_RETENTION_DAYS: i32 = 1;
+pub const DEFAULT_MAINTENANCE_INTERVAL_HOURS: i32 = 24;
+pub const DEFAULT_DOWNLOAD_MAX_MB: i32 = 1024;
+pub const DEFAULT_DOWNLOAD_POLICY: &str = "ask";
+pub const DEFAULT_CLIPBOARD_WRITE: &str = "allow";
+pub const DEFAULT_CLIPBOARD_READ: &str = "deny";
+pub const DEFAULT_CLIPBOARD_MAX_KB: i32 = 1024;
+pub const DEFAULT_ATTACHMENT_MAX_MB: i32 = 100;
+pub const DEFAULT_WEBVIEW_USER_AGENT: &str = "chrome";
+/// Maintenance runs kept in `maintenance_log`
+const MAX_MAINTENANCE_LOG_ENTRIES: i64 = 20;
+
 pub struct Database {
     conn: Mutex<Connection>,
+    path: PathBuf,
 }
 
 impl Database {
@@ -19,6 +37,7 @@ impl Database {
 
         Ok(Self {
             conn: Mutex::new(conn),
+            path: db_path,
         })
     }
 
@@ -29,58 +48,10 @@ impl Database {
             .join("wiz-term.db")
     }
 
-    pub fn run_migrations(&self) -> SqliteResult<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute_batch(
-            r#"
-            -- Terminal sessions for persistence
-            CREATE TABLE IF NOT EXISTS terminal_sessions (
-                id TEXT PRIMARY KEY,
-                command TEXT NOT NULL,
-                args TEXT NOT NULL,
-                cwd TEXT,
-                created_at INTEGER NOT NULL,
-                ended_at INTEGER,
-                exit_code INTEGER
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_terminal_sessions_created ON terminal_sessions(created_at DESC);
-
-            -- Terminal layout for split pane persistence
-            CREATE TABLE IF NOT EXISTS terminal_layout (
-                id INTEGER PRIMARY KEY DEFAULT 1,
-                layout_json TEXT NOT NULL,
-                updated_at INTEGER NOT NULL
-            );
-
-            -- Terminal preferences/settings
-            CREATE TABLE IF NOT EXISTS terminal_preferences (
-                id INTEGER PRIMARY KEY DEFAULT 1,
-                font_size INTEGER NOT NULL DEFAULT 13,
-                font_family TEXT NOT NULL DEFAULT 'SF Mono',
-                scrollback INTEGER NOT NULL DEFAULT 10000,
-                cursor_blink INTEGER NOT NULL DEFAULT 1,
-                minimap_refresh_ms INTEGER NOT NULL DEFAULT 200,
-                use_webgl INTEGER NOT NULL DEFAULT 1,
-                shell_path TEXT NOT NULL DEFAULT '/bin/zsh',
-                updated_at INTEGER NOT NULL
-            );
-        "#,
-        )?;
-
-        // Migration: Add use_webgl column if missing (for existing databases)
-        let _ = conn.execute(
-            "ALTER TABLE terminal_preferences ADD COLUMN use_webgl INTEGER NOT NULL DEFAULT 1",
-            [],
-        );
-
-        // Migration: Add shell_path column if missing
-        let _ = conn.execute(
-            "ALTER TABLE terminal_preferences ADD COLUMN shell_path TEXT NOT NULL DEFAULT '/bin/zsh'",
-            [],
-        );
-
-        Ok(())
+    /// Upgrade the schema to the latest version (see `migrations.rs`)
+    pub fn run_migrations(&self) -> Result<(), MigrationError> {
+        let mut conn = self.conn.lock().unwrap();
+        migrations::migrate(&mut conn, &self.path)
     }
 
     // ========== Terminal Session Methods ==========
@@ -154,6 +125,38 @@ impl Database {
         rows.collect()
     }
 
+    /// Get a single terminal session record (active or ended)
+    pub fn get_terminal_session(&self, id: &str) -> SqliteResult<Option<TerminalSessionRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            r#"
+            SELECT id, command, args, cwd, created_at, ended_at, exit_code
+            FROM terminal_sessions
+            WHERE id = ?1
+            "#,
+            [id],
+            |row| {
+                let args_str: String = row.get(2)?;
+                let args: Vec<String> = serde_json::from_str(&args_str).unwrap_or_default();
+                Ok(TerminalSessionRecord {
+                    id: row.get(0)?,
+                    command: row.get(1)?,
+                    args,
+                    cwd: row.get(3)?,
+                    created_at: row.get(4)?,
+                    ended_at: row.get(5)?,
+                    exit_code: row.get(6)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Delete a terminal session record
     pub fn delete_terminal_session(&self, id: &str) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
@@ -183,31 +186,686 @@ impl Database {
         Ok(updated)
     }
 
-    // ========== Terminal Layout Methods ==========
+    /// Page through past and current sessions, newest first. Returns the
+    /// page and the total number of matching sessions.
+    pub fn list_terminal_sessions(
+        &self,
+        filter: &SessionHistoryFilter,
+        limit: i64,
+        offset: i64,
+    ) -> SqliteResult<(Vec<TerminalSessionRecord>, i64)> {
+        let conn = self.conn.lock().unwrap();
+        let (clause, mut values) = filter.where_clause();
+
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM terminal_sessions {}", clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        values.push(Value::Integer(limit));
+        values.push(Value::Integer(offset));
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT id, command, args, cwd, created_at, ended_at, exit_code
+            FROM terminal_sessions {}
+            ORDER BY created_at DESC, id
+            LIMIT ? OFFSET ?
+            "#,
+            clause
+        ))?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            let args_str: String = row.get(2)?;
+            let args: Vec<String> = serde_json::from_str(&args_str).unwrap_or_default();
+            Ok(TerminalSessionRecord {
+                id: row.get(0)?,
+                command: row.get(1)?,
+                args,
+                cwd: row.get(3)?,
+                created_at: row.get(4)?,
+                ended_at: row.get(5)?,
+                exit_code: row.get(6)?,
+            })
+        })?;
 
-    /// Save terminal layout
-    pub fn save_terminal_layout(&self, layout_json: &str) -> SqliteResult<()> {
+        Ok((rows.collect::<SqliteResult<_>>()?, total))
+    }
+
+    /// Aggregate statistics over the sessions matching `filter`
+    pub fn terminal_session_stats(
+        &self,
+        filter: &SessionHistoryFilter,
+        top: i64,
+    ) -> SqliteResult<SessionStats> {
+        let conn = self.conn.lock().unwrap();
+        let (clause, values) = filter.where_clause();
+        let and = if clause.is_empty() { "WHERE" } else { "AND" };
+
+        let (total_sessions, average_lifetime_secs): (i64, Option<f64>) = conn.query_row(
+            &format!(
+                r#"
+                SELECT COUNT(*), AVG(CASE WHEN ended_at IS NOT NULL THEN ended_at - created_at END)
+                FROM terminal_sessions {}
+                "#,
+                clause
+            ),
+            params_from_iter(values.iter()),
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT date(created_at, 'unixepoch', 'localtime') AS day, COUNT(*)
+            FROM terminal_sessions {}
+            GROUP BY day ORDER BY day
+            "#,
+            clause
+        ))?;
+        let sessions_per_day = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok(DailySessionCount {
+                    day: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<SqliteResult<_>>()?;
+
+        let mut top_values = values.clone();
+        top_values.push(Value::Integer(top));
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT cwd, COUNT(*) AS uses FROM terminal_sessions {} {} cwd IS NOT NULL
+            GROUP BY cwd ORDER BY uses DESC, cwd LIMIT ?
+            "#,
+            clause, and
+        ))?;
+        let top_directories = stmt
+            .query_map(params_from_iter(top_values.iter()), |row| {
+                Ok(UsageCount {
+                    value: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<SqliteResult<_>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            r#"
+            SELECT command, COUNT(*) AS uses FROM terminal_sessions {}
+            GROUP BY command ORDER BY uses DESC, command LIMIT ?
+            "#,
+            clause
+        ))?;
+        let top_commands = stmt
+            .query_map(params_from_iter(top_values.iter()), |row| {
+                Ok(UsageCount {
+                    value: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<SqliteResult<_>>()?;
+
+        Ok(SessionStats {
+            total_sessions,
+            average_lifetime_secs,
+            sessions_per_day,
+            top_directories,
+            top_commands,
+        })
+    }
+
+    // ========== Workspace Methods ==========
+
+    /// Save a workspace's layout (creating the workspace if needed)
+    pub fn save_workspace_layout(&self, workspace: &str, layout_json: &str) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
         let now = Utc::now().timestamp();
         conn.execute(
             r#"
-            INSERT OR REPLACE INTO terminal_layout (id, layout_json, updated_at)
-            VALUES (1, ?1, ?2)
+            INSERT INTO workspaces (workspace, layout_json, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?3)
+            ON CONFLICT(workspace) DO UPDATE SET layout_json = ?2, updated_at = ?3
             "#,
-            params![layout_json, now],
+            params![workspace, layout_json, now],
         )?;
         Ok(())
     }
 
-    /// Get terminal layout
-    pub fn get_terminal_layout(&self) -> SqliteResult<Option<String>> {
+    /// Get a workspace's layout
+    pub fn get_workspace_layout(&self, workspace: &str) -> SqliteResult<Option<String>> {
         let conn = self.conn.lock().unwrap();
-        let result: Result<String, _> = conn.query_row(
-            "SELECT layout_json FROM terminal_layout WHERE id = 1",
-            [],
+        let result: Result<Option<String>, _> = conn.query_row(
+            "SELECT layout_json FROM workspaces WHERE workspace = ?1",
+            [workspace],
             |row| row.get(0),
         );
-        Ok(result.ok())
+        Ok(result.ok().flatten())
+    }
+
+    /// Remember where a workspace's window was (creating the workspace if needed)
+    pub fn save_window_geometry(
+        &self,
+        workspace: &str,
+        geometry: &WindowGeometry,
+    ) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().timestamp();
+        conn.execute(
+            r#"
+            INSERT INTO workspaces (workspace, window_x, window_y, window_width, window_height, maximized, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+            ON CONFLICT(workspace) DO UPDATE SET
+                window_x = ?2, window_y = ?3, window_width = ?4, window_height = ?5,
+                maximized = ?6, updated_at = ?7
+            "#,
+            params![
+                workspace,
+                geometry.x,
+                geometry.y,
+                geometry.width,
+                geometry.height,
+                geometry.maximized as i32,
+                now
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// All workspaces, oldest first
+    pub fn list_workspaces(&self) -> SqliteResult<Vec<WorkspaceRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT workspace, window_x, window_y, window_width, window_height, maximized, created_at, updated_at
+            FROM workspaces
+            ORDER BY created_at, workspace
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let bounds: (Option<f64>, Option<f64>, Option<f64>, Option<f64>) =
+                (row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?);
+            let geometry = match bounds {
+                (Some(x), Some(y), Some(width), Some(height)) => Some(WindowGeometry {
+                    x,
+                    y,
+                    width,
+                    height,
+                    maximized: row.get::<_, i32>(5)? != 0,
+                }),
+                _ => None,
+            };
+            Ok(WorkspaceRecord {
+                workspace: row.get(0)?,
+                geometry,
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Get one workspace
+    pub fn get_workspace(&self, workspace: &str) -> SqliteResult<Option<WorkspaceRecord>> {
+        Ok(self
+            .list_workspaces()?
+            .into_iter()
+            .find(|record| record.workspace == workspace))
+    }
+
+    /// Forget a closed window's workspace and its undo history (named
+    /// snapshots are kept)
+    pub fn delete_workspace(&self, workspace: &str) -> SqliteResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM layout_history WHERE workspace = ?1", [workspace])?;
+        let deleted = tx.execute("DELETE FROM workspaces WHERE workspace = ?1", [workspace])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    // ========== Layout History Methods ==========
+
+    /// Append a layout to a workspace's history, discarding any redo entries
+    /// and trimming to `max_entries`. Returns false if the layout is identical
+    /// to the current entry.
+    pub fn push_layout_history(
+        &self,
+        workspace: &str,
+        layout_json: &str,
+        killed_sessions: &[TerminalSessionRecord],
+        max_entries: usize,
+    ) -> SqliteResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let current: Option<String> = tx
+            .query_row(
+                r#"
+                SELECT layout_json FROM layout_history
+                WHERE workspace = ?1 AND undone = 0
+                ORDER BY id DESC LIMIT 1
+                "#,
+                [workspace],
+                |row| row.get(0),
+            )
+            .ok();
+        if current.as_deref() == Some(layout_json) {
+            return Ok(false);
+        }
+
+        tx.execute(
+            "DELETE FROM layout_history WHERE workspace = ?1 AND undone = 1",
+            [workspace],
+        )?;
+
+        let killed_json =
+            serde_json::to_string(killed_sessions).unwrap_or_else(|_| "[]".to_string());
+        tx.execute(
+            r#"
+            INSERT INTO layout_history (workspace, layout_json, killed_sessions, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![workspace, layout_json, killed_json, Utc::now().timestamp()],
+        )?;
+
+        tx.execute(
+            r#"
+            DELETE FROM layout_history
+            WHERE workspace = ?1 AND id NOT IN (
+                SELECT id FROM layout_history WHERE workspace = ?1
+                ORDER BY id DESC LIMIT ?2
+            )
+            "#,
+            params![workspace, max_entries as i64],
+        )?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Get the current (most recent, not undone) history entry
+    pub fn get_current_layout_history(
+        &self,
+        workspace: &str,
+    ) -> SqliteResult<Option<LayoutHistoryRecord>> {
+        let conn = self.conn.lock().unwrap();
+        Self::query_layout_history(
+            &conn,
+            "WHERE workspace = ?1 AND undone = 0 ORDER BY id DESC LIMIT 1",
+            workspace,
+        )
+        .map(|mut records| records.pop())
+    }
+
+    /// Step back one entry. Returns the entry that was undone and the entry
+    /// that is now current, or None if there is nothing to undo.
+    pub fn undo_layout_history(
+        &self,
+        workspace: &str,
+    ) -> SqliteResult<Option<(LayoutHistoryRecord, LayoutHistoryRecord)>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let mut records = Self::query_layout_history(
+            &tx,
+            "WHERE workspace = ?1 AND undone = 0 ORDER BY id DESC LIMIT 2",
+            workspace,
+        )?;
+        if records.len() < 2 {
+            return Ok(None);
+        }
+        let previous = records.pop().unwrap();
+        let mut undone = records.pop().unwrap();
+
+        tx.execute(
+            "UPDATE layout_history SET undone = 1 WHERE id = ?1",
+            [undone.id],
+        )?;
+        tx.commit()?;
+
+        undone.undone = true;
+        Ok(Some((undone, previous)))
+    }
+
+    /// Re-apply the oldest undone entry, returning it
+    pub fn redo_layout_history(&self, workspace: &str) -> SqliteResult<Option<LayoutHistoryRecord>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let Some(mut next) = Self::query_layout_history(
+            &tx,
+            "WHERE workspace = ?1 AND undone = 1 ORDER BY id ASC LIMIT 1",
+            workspace,
+        )?
+        .pop() else {
+            return Ok(None);
+        };
+
+        tx.execute(
+            "UPDATE layout_history SET undone = 0 WHERE id = ?1",
+            [next.id],
+        )?;
+        tx.commit()?;
+
+        next.undone = false;
+        Ok(Some(next))
+    }
+
+    /// List a workspace's history, newest first
+    pub fn list_layout_history(&self, workspace: &str) -> SqliteResult<Vec<LayoutHistoryRecord>> {
+        let conn = self.conn.lock().unwrap();
+        Self::query_layout_history(&conn, "WHERE workspace = ?1 ORDER BY id DESC", workspace)
+    }
+
+    fn query_layout_history(
+        conn: &Connection,
+        clause: &str,
+        workspace: &str,
+    ) -> SqliteResult<Vec<LayoutHistoryRecord>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, workspace, layout_json, killed_sessions, undone, created_at FROM layout_history {}",
+            clause
+        ))?;
+
+        let rows = stmt.query_map([workspace], |row| {
+            let killed_str: String = row.get(3)?;
+            Ok(LayoutHistoryRecord {
+                id: row.get(0)?,
+                workspace: row.get(1)?,
+                layout_json: row.get(2)?,
+                killed_sessions: serde_json::from_str(&killed_str).unwrap_or_default(),
+                undone: row.get::<_, i32>(4)? != 0,
+                created_at: row.get(5)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    // ========== Layout Snapshot Methods ==========
+
+    /// Save (or overwrite) a named layout snapshot
+    pub fn save_layout_snapshot(
+        &self,
+        workspace: &str,
+        name: &str,
+        layout_json: &str,
+    ) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT OR REPLACE INTO layout_snapshots (workspace, name, layout_json, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![workspace, name, layout_json, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Get a named layout snapshot
+    pub fn get_layout_snapshot(
+        &self,
+        workspace: &str,
+        name: &str,
+    ) -> SqliteResult<Option<LayoutSnapshotRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            r#"
+            SELECT workspace, name, layout_json, created_at FROM layout_snapshots
+            WHERE workspace = ?1 AND name = ?2
+            "#,
+            params![workspace, name],
+            |row| {
+                Ok(LayoutSnapshotRecord {
+                    workspace: row.get(0)?,
+                    name: row.get(1)?,
+                    layout_json: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Every workspace's snapshots, by workspace then newest first
+    pub fn list_all_layout_snapshots(&self) -> SqliteResult<Vec<LayoutSnapshotRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT workspace, name, layout_json, created_at FROM layout_snapshots
+            ORDER BY workspace, created_at DESC
+            "#,
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(LayoutSnapshotRecord {
+                workspace: row.get(0)?,
+                name: row.get(1)?,
+                layout_json: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// List a workspace's snapshots, newest first
+    pub fn list_layout_snapshots(&self, workspace: &str) -> SqliteResult<Vec<LayoutSnapshotRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT workspace, name, layout_json, created_at FROM layout_snapshots
+            WHERE workspace = ?1
+            ORDER BY created_at DESC
+            "#,
+        )?;
+
+        let rows = stmt.query_map([workspace], |row| {
+            Ok(LayoutSnapshotRecord {
+                workspace: row.get(0)?,
+                name: row.get(1)?,
+                layout_json: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Delete a named layout snapshot
+    pub fn delete_layout_snapshot(&self, workspace: &str, name: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM layout_snapshots WHERE workspace = ?1 AND name = ?2",
+            params![workspace, name],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    // ========== Download Methods ==========
+
+    /// Record a file transfer as it starts
+    pub fn insert_download(&self, record: &DownloadRecord) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT INTO downloads (id, session_id, file_name, path, size_bytes, status, error, created_at, completed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            params![
+                record.id,
+                record.session_id,
+                record.file_name,
+                record.path,
+                record.size_bytes,
+                record.status,
+                record.error,
+                record.created_at,
+                record.completed_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Store a transfer's outcome
+    pub fn update_download(&self, record: &DownloadRecord) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            UPDATE downloads
+            SET path = ?2, size_bytes = ?3, status = ?4, error = ?5, completed_at = ?6
+            WHERE id = ?1
+            "#,
+            params![
+                record.id,
+                record.path,
+                record.size_bytes,
+                record.status,
+                record.error,
+                record.completed_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent downloads first
+    pub fn list_downloads(&self, limit: i64) -> SqliteResult<Vec<DownloadRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, session_id, file_name, path, size_bytes, status, error, created_at, completed_at
+            FROM downloads
+            ORDER BY created_at DESC
+            LIMIT ?1
+            "#,
+        )?;
+        let rows = stmt.query_map([limit], |row| {
+            Ok(DownloadRecord {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                file_name: row.get(2)?,
+                path: row.get(3)?,
+                size_bytes: row.get(4)?,
+                status: row.get(5)?,
+                error: row.get(6)?,
+                created_at: row.get(7)?,
+                completed_at: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Mark transfers left pending by a previous run as failed. Returns
+    /// their partial file paths.
+    pub fn fail_pending_downloads(&self) -> SqliteResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let partials = conn
+            .prepare("SELECT path FROM downloads WHERE status = 'pending' AND path IS NOT NULL")?
+            .query_map([], |row| row.get(0))?
+            .collect::<SqliteResult<Vec<String>>>()?;
+        conn.execute(
+            r#"
+            UPDATE downloads
+            SET status = 'failed', error = 'Interrupted', path = NULL, completed_at = ?1
+            WHERE status = 'pending'
+            "#,
+            [Utc::now().timestamp()],
+        )?;
+        Ok(partials)
+    }
+
+    // ========== Attachment Methods ==========
+
+    pub fn insert_attachment(&self, record: &AttachmentRecord) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT INTO attachments (id, session_id, file_name, mime_type, path, size_bytes, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            params![
+                record.id,
+                record.session_id,
+                record.file_name,
+                record.mime_type,
+                record.path,
+                record.size_bytes,
+                record.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// A session's attachments (or all of them), newest first
+    pub fn list_attachments(&self, session_id: Option<&str>) -> SqliteResult<Vec<AttachmentRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, session_id, file_name, mime_type, path, size_bytes, created_at
+            FROM attachments
+            WHERE ?1 IS NULL OR session_id = ?1
+            ORDER BY created_at DESC
+            "#,
+        )?;
+        let rows = stmt.query_map([session_id], |row| {
+            Ok(AttachmentRecord {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                file_name: row.get(2)?,
+                mime_type: row.get(3)?,
+                path: row.get(4)?,
+                size_bytes: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Delete attachment rows matching `condition` and return their file
+    /// paths so the files can be removed
+    fn take_attachment_paths(
+        conn: &Connection,
+        condition: &str,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> SqliteResult<Vec<String>> {
+        let paths = conn
+            .prepare(&format!("SELECT path FROM attachments WHERE {}", condition))?
+            .query_map(params, |row| row.get(0))?
+            .collect::<SqliteResult<Vec<String>>>()?;
+        conn.execute(&format!("DELETE FROM attachments WHERE {}", condition), params)?;
+        Ok(paths)
+    }
+
+    pub fn remove_attachment(&self, id: &str) -> SqliteResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        Self::take_attachment_paths(&conn, "id = ?1", params![id])
+    }
+
+    pub fn remove_session_attachments(&self, session_id: &str) -> SqliteResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        Self::take_attachment_paths(&conn, "session_id = ?1", params![session_id])
+    }
+
+    /// Attachments of sessions that have ended or no longer exist
+    pub fn remove_orphaned_attachments(&self) -> SqliteResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        Self::take_attachment_paths(
+            &conn,
+            r#"session_id NOT IN (
+                SELECT id FROM terminal_sessions WHERE ended_at IS NULL
+            )"#,
+            params![],
+        )
+    }
+
+    pub fn remove_attachments_older_than(&self, days: i64) -> SqliteResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let cutoff = Utc::now().timestamp() - days * 24 * 60 * 60;
+        Self::take_attachment_paths(&conn, "created_at < ?1", params![cutoff])
     }
 
     // ========== Terminal Preferences Methods ==========
@@ -215,13 +873,17 @@ impl Database {
     /// Save terminal preferences
     pub fn save_terminal_preferences(&self, prefs: &TerminalPreferences) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
+        Self::write_terminal_preferences(&conn, prefs)
+    }
+
+    fn write_terminal_preferences(conn: &Connection, prefs: &TerminalPreferences) -> SqliteResult<()> {
         let now = Utc::now().timestamp();
         conn.execute(
             r#"
-            INSERT OR REPLACE INTO terminal_preferences (id, font_size, font_family, scrollback, cursor_blink, minimap_refresh_ms, use_webgl, shell_path, updated_at)
-            VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            INSERT OR REPLACE INTO terminal_preferences (id, font_size, font_family, scrollback, cursor_blink, minimap_refresh_ms, use_webgl, shell_path, color_scheme, color_scheme_light, session_retention_days, temp_file_retention_days, maintenance_interval_hours, session_stats_interval_ms, downloads_dir, download_max_mb, download_policy, clipboard_write, clipboard_read, clipboard_max_kb, attachment_max_mb, webview_user_agent, webview_devtools, webview_console_log, updated_at)
+            VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24)
             "#,
-            params![prefs.font_size, prefs.font_family, prefs.scrollback, prefs.cursor_blink as i32, prefs.minimap_refresh_ms, prefs.use_webgl as i32, prefs.shell_path, now],
+            params![prefs.font_size, prefs.font_family, prefs.scrollback, prefs.cursor_blink as i32, prefs.minimap_refresh_ms, prefs.use_webgl as i32, prefs.shell_path, prefs.color_scheme, prefs.color_scheme_light, prefs.session_retention_days, prefs.temp_file_retention_days, prefs.maintenance_interval_hours, prefs.session_stats_interval_ms, prefs.downloads_dir, prefs.download_max_mb, prefs.download_policy, prefs.clipboard_write, prefs.clipboard_read, prefs.clipboard_max_kb, prefs.attachment_max_mb, prefs.webview_user_agent, prefs.webview_devtools as i32, prefs.webview_console_log as i32, now],
         )?;
         Ok(())
     }
@@ -230,7 +892,7 @@ impl Database {
     pub fn get_terminal_preferences(&self) -> SqliteResult<TerminalPreferences> {
         let conn = self.conn.lock().unwrap();
         let result = conn.query_row(
-            "SELECT font_size, font_family, scrollback, cursor_blink, minimap_refresh_ms, use_webgl, shell_path FROM terminal_preferences WHERE id = 1",
+            "SELECT font_size, font_family, scrollback, cursor_blink, minimap_refresh_ms, use_webgl, shell_path, color_scheme, color_scheme_light, session_retention_days, temp_file_retention_days, maintenance_interval_hours, session_stats_interval_ms, downloads_dir, download_max_mb, download_policy, clipboard_write, clipboard_read, clipboard_max_kb, attachment_max_mb, webview_user_agent, webview_devtools, webview_console_log FROM terminal_preferences WHERE id = 1",
             [],
             |row| {
                 Ok(TerminalPreferences {
@@ -240,7 +902,23 @@ impl Database {
                     cursor_blink: row.get::<_, i32>(3)? != 0,
                     minimap_refresh_ms: row.get(4)?,
                     use_webgl: row.get::<_, i32>(5).unwrap_or(1) != 0,
-                    shell_path: row.get::<_, String>(6).unwrap_or_else(|_| "/bin/zsh".to_string()),
+                    shell_path: row.get::<_, String>(6).unwrap_or_default(),
+                    color_scheme: row.get::<_, String>(7).unwrap_or_else(|_| DEFAULT_SCHEME.to_string()),
+                    color_scheme_light: row.get(8).unwrap_or(None),
+                    session_retention_days: row.get(9).unwrap_or(DEFAULT_SESSION_RETENTION_DAYS),
+                    temp_file_retention_days: row.get(10).unwrap_or(DEFAULT_TEMP_FILE_RETENTION_DAYS),
+                    maintenance_interval_hours: row.get(11).unwrap_or(DEFAULT_MAINTENANCE_INTERVAL_HOURS),
+                    session_stats_interval_ms: row.get(12).unwrap_or(0),
+                    downloads_dir: row.get::<_, String>(13).unwrap_or_default(),
+                    download_max_mb: row.get(14).unwrap_or(DEFAULT_DOWNLOAD_MAX_MB),
+                    download_policy: row.get::<_, String>(15).unwrap_or_else(|_| DEFAULT_DOWNLOAD_POLICY.to_string()),
+                    clipboard_write: row.get::<_, String>(16).unwrap_or_else(|_| DEFAULT_CLIPBOARD_WRITE.to_string()),
+                    clipboard_read: row.get::<_, String>(17).unwrap_or_else(|_| DEFAULT_CLIPBOARD_READ.to_string()),
+                    clipboard_max_kb: row.get(18).unwrap_or(DEFAULT_CLIPBOARD_MAX_KB),
+                    attachment_max_mb: row.get(19).unwrap_or(DEFAULT_ATTACHMENT_MAX_MB),
+                    webview_user_agent: row.get::<_, String>(20).unwrap_or_else(|_| DEFAULT_WEBVIEW_USER_AGENT.to_string()),
+                    webview_devtools: row.get::<_, i32>(21).unwrap_or(0) != 0,
+                    webview_console_log: row.get::<_, i32>(22).unwrap_or(0) != 0,
                 })
             },
         );
@@ -248,10 +926,258 @@ impl Database {
         // Return defaults if no preferences saved yet
         result.or_else(|_| Ok(TerminalPreferences::default()))
     }
+
+    // ========== Color Scheme Methods ==========
+
+    /// Save (or replace) a color scheme
+    pub fn save_color_scheme(
+        &self,
+        name: &str,
+        variant: &str,
+        palette_json: &str,
+        source: Option<&str>,
+    ) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT OR REPLACE INTO color_schemes (name, variant, palette_json, source, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![name, variant, palette_json, source, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Get a color scheme by name
+    pub fn get_color_scheme(&self, name: &str) -> SqliteResult<Option<ColorSchemeRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let result = conn.query_row(
+            "SELECT name, variant, palette_json, source, updated_at FROM color_schemes WHERE name = ?1",
+            [name],
+            Self::color_scheme_from_row,
+        );
+
+        match result {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// List all stored color schemes by name
+    pub fn list_color_schemes(&self) -> SqliteResult<Vec<ColorSchemeRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT name, variant, palette_json, source, updated_at FROM color_schemes ORDER BY name COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([], Self::color_scheme_from_row)?;
+        rows.collect()
+    }
+
+    /// Delete a color scheme
+    pub fn delete_color_scheme(&self, name: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute("DELETE FROM color_schemes WHERE name = ?1", [name])?;
+        Ok(deleted > 0)
+    }
+
+    fn color_scheme_from_row(row: &rusqlite::Row) -> SqliteResult<ColorSchemeRecord> {
+        Ok(ColorSchemeRecord {
+            name: row.get(0)?,
+            variant: row.get(1)?,
+            palette_json: row.get(2)?,
+            source: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    }
+
+    // ========== Keybinding Methods ==========
+
+    /// Save a user keybinding (an empty chord unbinds the action)
+    pub fn save_keybinding(&self, action: &str, chord: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO keybindings (action, chord, updated_at) VALUES (?1, ?2, ?3)",
+            params![action, chord, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// User keybindings by action
+    pub fn get_keybindings(&self) -> SqliteResult<BTreeMap<String, String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT action, chord FROM keybindings")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    /// Remove a user keybinding, restoring the default
+    pub fn delete_keybinding(&self, action: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute("DELETE FROM keybindings WHERE action = ?1", [action])?;
+        Ok(deleted > 0)
+    }
+
+    /// Remove every user keybinding
+    pub fn clear_keybindings(&self) -> SqliteResult<usize> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM keybindings", [])
+    }
+
+    // ========== Maintenance Methods ==========
+
+    /// Run `PRAGMA integrity_check`; returns `["ok"]` when the database is healthy
+    pub fn integrity_check(&self) -> SqliteResult<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Rebuild the file to reclaim free pages, then truncate the WAL
+    pub fn vacuum(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+    }
+
+    /// Let SQLite refresh query planner statistics where useful
+    pub fn optimize(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("PRAGMA optimize;")
+    }
+
+    /// Page and per-table size information
+    pub fn stats(&self) -> SqliteResult<DbStats> {
+        let conn = self.conn.lock().unwrap();
+        let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let freelist_count: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+
+        let names: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<SqliteResult<_>>()?
+        };
+
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let rows: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")),
+                [],
+                |row| row.get(0),
+            )?;
+            // dbstat counts the table and its indexes; absent if SQLite was built without it
+            let bytes: Option<i64> = conn
+                .query_row(
+                    r#"
+                    SELECT SUM(pgsize) FROM dbstat
+                    WHERE name = ?1 OR name IN (SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1)
+                    "#,
+                    [&name],
+                    |row| row.get(0),
+                )
+                .ok()
+                .flatten();
+            tables.push(TableStats { name, rows, bytes });
+        }
+
+        let last_maintenance: Option<i64> = conn
+            .query_row("SELECT MAX(ran_at) FROM maintenance_log", [], |row| row.get(0))
+            .unwrap_or(None);
+
+        let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let mut wal_path = self.path.clone().into_os_string();
+        wal_path.push("-wal");
+
+        Ok(DbStats {
+            path: self.path.to_string_lossy().to_string(),
+            file_bytes: file_size(&self.path),
+            wal_bytes: file_size(Path::new(&wal_path)),
+            page_size,
+            page_count,
+            freelist_count,
+            schema_version: migrations::schema_version(&conn)?,
+            last_maintenance,
+            tables,
+        })
+    }
+
+    /// Record a maintenance run (older entries are trimmed)
+    pub fn log_maintenance(&self, report_json: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO maintenance_log (ran_at, report_json) VALUES (?1, ?2)",
+            params![Utc::now().timestamp(), report_json],
+        )?;
+        conn.execute(
+            r#"
+            DELETE FROM maintenance_log
+            WHERE id NOT IN (SELECT id FROM maintenance_log ORDER BY id DESC LIMIT ?1)
+            "#,
+            [MAX_MAINTENANCE_LOG_ENTRIES],
+        )?;
+        Ok(())
+    }
+
+    /// When maintenance last completed
+    pub fn last_maintenance(&self) -> SqliteResult<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT MAX(ran_at) FROM maintenance_log", [], |row| row.get(0))
+    }
+
+    // ========== Settings Import ==========
+
+    /// Write an imported settings bundle in one transaction. With `replace`,
+    /// custom schemes, keybindings and snapshots not in the bundle are removed.
+    pub fn import_settings(&self, import: &SettingsImport, replace: bool) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let now = Utc::now().timestamp();
+
+        if replace {
+            tx.execute("DELETE FROM color_schemes", [])?;
+            tx.execute("DELETE FROM keybindings", [])?;
+            tx.execute("DELETE FROM layout_snapshots", [])?;
+        }
+
+        if let Some(prefs) = &import.preferences {
+            Self::write_terminal_preferences(&tx, prefs)?;
+        }
+        for scheme in &import.color_schemes {
+            tx.execute(
+                r#"
+                INSERT OR REPLACE INTO color_schemes (name, variant, palette_json, source, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+                params![scheme.name, scheme.variant, scheme.palette_json, scheme.source, now],
+            )?;
+        }
+        for (action, chord) in &import.keybindings {
+            tx.execute(
+                "INSERT OR REPLACE INTO keybindings (action, chord, updated_at) VALUES (?1, ?2, ?3)",
+                params![action, chord, now],
+            )?;
+        }
+        for snapshot in &import.layout_snapshots {
+            tx.execute(
+                r#"
+                INSERT OR REPLACE INTO layout_snapshots (workspace, name, layout_json, created_at)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+                params![snapshot.workspace, snapshot.name, snapshot.layout_json, snapshot.created_at],
+            )?;
+        }
+
+        tx.commit()
+    }
 }
 
-/// Terminal preferences struct
+/// Terminal preferences struct. Missing fields (e.g. in a settings bundle
+/// from an older version) take their defaults.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct TerminalPreferences {
     pub font_size: i32,
     pub font_family: String,
@@ -259,7 +1185,44 @@ pub struct TerminalPreferences {
     pub cursor_blink: bool,
     pub minimap_refresh_ms: i32,
     pub use_webgl: bool,
+    /// Shell for new sessions; empty uses the login shell from passwd
     pub shell_path: String,
+    /// Color scheme used in dark mode (and in light mode if no light scheme is set)
+    pub color_scheme: String,
+    /// Color scheme used when the system appearance is light
+    pub color_scheme_light: Option<String>,
+    /// Delete ended sessions older than this (0 keeps them forever)
+    pub session_retention_days: i32,
+    /// Delete attachments (pasted/dropped files) older than this, even if
+    /// their session is still running (0 keeps them until it ends)
+    pub temp_file_retention_days: i32,
+    /// How often cleanup, integrity check and VACUUM run (0 disables)
+    pub maintenance_interval_hours: i32,
+    /// How often `session-stats` events are sent (0 disables sampling)
+    pub session_stats_interval_ms: i32,
+    /// Where OSC 1337 file transfers are saved; empty uses the system
+    /// Downloads folder
+    pub downloads_dir: String,
+    /// Largest file transfer accepted (0 for no limit)
+    pub download_max_mb: i32,
+    /// "ask", "accept" or "deny" incoming file transfers
+    pub download_policy: String,
+    /// "allow", "ask" or "deny" programs setting the clipboard (OSC 52)
+    pub clipboard_write: String,
+    /// "allow", "ask" or "deny" programs reading the clipboard (OSC 52)
+    pub clipboard_read: String,
+    /// Largest OSC 52 clipboard text accepted or sent (0 for no limit)
+    pub clipboard_max_kb: i32,
+    /// Largest file pasted or dropped into a terminal (0 for no limit)
+    pub attachment_max_mb: i32,
+    /// User agent of webview panes: "chrome", "native" (the engine's own) or
+    /// a literal user agent string
+    pub webview_user_agent: String,
+    /// Allow opening webview developer tools in release builds (debug builds
+    /// always can)
+    pub webview_devtools: bool,
+    /// Append webview console messages to the webview console log
+    pub webview_console_log: bool,
 }
 
 impl Default for TerminalPreferences {
@@ -271,13 +1234,29 @@ impl Default for TerminalPreferences {
             cursor_blink: true,
             minimap_refresh_ms: 200,
             use_webgl: true,
-            shell_path: "/bin/zsh".to_string(),
+            shell_path: String::new(),
+            color_scheme: DEFAULT_SCHEME.to_string(),
+            color_scheme_light: None,
+            session_retention_days: DEFAULT_SESSION_RETENTION_DAYS,
+            temp_file_retention_days: DEFAULT_TEMP_FILE_RETENTION_DAYS,
+            maintenance_interval_hours: DEFAULT_MAINTENANCE_INTERVAL_HOURS,
+            session_stats_interval_ms: 0,
+            downloads_dir: String::new(),
+            download_max_mb: DEFAULT_DOWNLOAD_MAX_MB,
+            download_policy: DEFAULT_DOWNLOAD_POLICY.to_string(),
+            clipboard_write: DEFAULT_CLIPBOARD_WRITE.to_string(),
+            clipboard_read: DEFAULT_CLIPBOARD_READ.to_string(),
+            clipboard_max_kb: DEFAULT_CLIPBOARD_MAX_KB,
+            attachment_max_mb: DEFAULT_ATTACHMENT_MAX_MB,
+            webview_user_agent: DEFAULT_WEBVIEW_USER_AGENT.to_string(),
+            webview_devtools: false,
+            webview_console_log: false,
         }
     }
 }
 
 /// Record struct for terminal sessions from database
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TerminalSessionRecord {
     pub id: String,
     pub command: String,
@@ -287,3 +1266,203 @@ pub struct TerminalSessionRecord {
     pub ended_at: Option<i64>,
     pub exit_code: Option<i32>,
 }
+
+/// Layout history entry from database
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LayoutHistoryRecord {
+    pub id: i64,
+    pub workspace: String,
+    pub layout_json: String,
+    /// Sessions removed from the layout by this step
+    pub killed_sessions: Vec<TerminalSessionRecord>,
+    pub undone: bool,
+    pub created_at: i64,
+}
+
+/// Filters for the session history browser (all optional, combined with AND)
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SessionHistoryFilter {
+    /// Substring of the command, case-insensitive
+    pub command: Option<String>,
+    /// Working directory, e.g. "~/code", matching it and its subdirectories
+    /// but not siblings like "~/codebase" (`~` is expanded; sessions started
+    /// with a literal `~` in their cwd match too)
+    pub cwd_prefix: Option<String>,
+    pub exit_code: Option<i32>,
+    /// Only sessions started at or after this Unix timestamp
+    pub since: Option<i64>,
+    /// Only sessions started before this Unix timestamp
+    pub until: Option<i64>,
+}
+
+impl SessionHistoryFilter {
+    /// WHERE clause (empty if no filters) and its positional parameters
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(command) = self.command.as_deref().filter(|c| !c.is_empty()) {
+            conditions.push("instr(lower(command), lower(?)) > 0");
+            values.push(Value::Text(command.to_string()));
+        }
+        if let Some(prefix) = self.cwd_prefix.as_deref().filter(|p| !p.is_empty()) {
+            // "/" trims to "", which then matches every absolute path
+            let prefix = prefix.trim_end_matches('/');
+            // The directory itself or anything below it. substr rather than
+            // LIKE so '%' and '_' in paths match literally
+            conditions.push(
+                "(cwd = ? OR substr(cwd, 1, length(?) + 1) = ? || '/' \
+                 OR cwd = ? OR substr(cwd, 1, length(?) + 1) = ? || '/')",
+            );
+            for prefix in [shellexpand::tilde(prefix).as_ref(), prefix] {
+                for _ in 0..3 {
+                    values.push(Value::Text(prefix.to_string()));
+                }
+            }
+        }
+        if let Some(exit_code) = self.exit_code {
+            conditions.push("exit_code = ?");
+            values.push(Value::Integer(exit_code as i64));
+        }
+        if let Some(since) = self.since {
+            conditions.push("created_at >= ?");
+            values.push(Value::Integer(since));
+        }
+        if let Some(until) = self.until {
+            conditions.push("created_at < ?");
+            values.push(Value::Integer(until));
+        }
+
+        if conditions.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!("WHERE {}", conditions.join(" AND ")), values)
+        }
+    }
+}
+
+/// Aggregate session statistics
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionStats {
+    pub total_sessions: i64,
+    /// Mean seconds between start and end, over ended sessions
+    pub average_lifetime_secs: Option<f64>,
+    pub sessions_per_day: Vec<DailySessionCount>,
+    pub top_directories: Vec<UsageCount>,
+    pub top_commands: Vec<UsageCount>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DailySessionCount {
+    /// Local date, YYYY-MM-DD
+    pub day: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UsageCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Database size information for `db_stats`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DbStats {
+    pub path: String,
+    pub file_bytes: u64,
+    pub wal_bytes: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    /// Unused pages that VACUUM would reclaim
+    pub freelist_count: i64,
+    pub schema_version: u32,
+    pub last_maintenance: Option<i64>,
+    pub tables: Vec<TableStats>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+    /// Bytes used by the table and its indexes
+    pub bytes: Option<i64>,
+}
+
+/// OSC 1337 file transfer from database
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DownloadRecord {
+    pub id: String,
+    pub session_id: String,
+    /// Sanitized name sent by the program
+    pub file_name: String,
+    /// Saved file once completed (the partial file while pending)
+    pub path: Option<String>,
+    pub size_bytes: i64,
+    /// pending, completed, denied, too_large or failed
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+/// File pasted or dropped into a session, kept until the session ends
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AttachmentRecord {
+    pub id: String,
+    pub session_id: String,
+    /// Sanitized name the file was saved under
+    pub file_name: String,
+    /// Detected from the file's contents
+    pub mime_type: String,
+    pub path: String,
+    pub size_bytes: i64,
+    pub created_at: i64,
+}
+
+/// Window bounds in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub maximized: bool,
+}
+
+/// A window's workspace from database
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WorkspaceRecord {
+    pub workspace: String,
+    pub geometry: Option<WindowGeometry>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Named layout snapshot from database
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LayoutSnapshotRecord {
+    pub workspace: String,
+    pub name: String,
+    pub layout_json: String,
+    pub created_at: i64,
+}
+
+/// Validated settings written by `Database::import_settings`
+#[derive(Debug, Clone, Default)]
+pub struct SettingsImport {
+    pub preferences: Option<TerminalPreferences>,
+    pub color_schemes: Vec<ColorSchemeRecord>,
+    pub keybindings: BTreeMap<String, String>,
+    pub layout_snapshots: Vec<LayoutSnapshotRecord>,
+}
+
+/// Stored color scheme from database
+#[derive(Debug, Clone)]
+pub struct ColorSchemeRecord {
+    pub name: String,
+    pub variant: String,
+    pub palette_json: String,
+    pub source: Option<String>,
+    pub updated_at: i64,
+}
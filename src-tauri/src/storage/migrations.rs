@@ -0,0 +1,762 @@
+use rusqlite::Connection;
+use std::path::Path;
+use thiserror::Error;
+use tracing::info;
+
+/// A single schema upgrade, applied in its own transaction
+pub struct Migration {
+    /// Value of `PRAGMA user_version` after this migration runs
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// Every schema change, in order. Never edit an entry once released; add a
+/// new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial sessions, layout and preferences tables",
+        sql: r#"
+            CREATE TABLE terminal_sessions (
+                id TEXT PRIMARY KEY,
+                command TEXT NOT NULL,
+                args TEXT NOT NULL,
+                cwd TEXT,
+                created_at INTEGER NOT NULL,
+                ended_at INTEGER,
+                exit_code INTEGER
+            );
+
+            CREATE INDEX idx_terminal_sessions_created ON terminal_sessions(created_at DESC);
+
+            CREATE TABLE terminal_layout (
+                id INTEGER PRIMARY KEY DEFAULT 1,
+                layout_json TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE terminal_preferences (
+                id INTEGER PRIMARY KEY DEFAULT 1,
+                font_size INTEGER NOT NULL DEFAULT 13,
+                font_family TEXT NOT NULL DEFAULT 'SF Mono',
+                scrollback INTEGER NOT NULL DEFAULT 10000,
+                cursor_blink INTEGER NOT NULL DEFAULT 1,
+                minimap_refresh_ms INTEGER NOT NULL DEFAULT 200,
+                updated_at INTEGER NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        version: 2,
+        description: "add use_webgl preference",
+        sql: "ALTER TABLE terminal_preferences ADD COLUMN use_webgl INTEGER NOT NULL DEFAULT 1;",
+    },
+    Migration {
+        version: 3,
+        description: "add shell_path preference",
+        sql: "ALTER TABLE terminal_preferences ADD COLUMN shell_path TEXT NOT NULL DEFAULT '/bin/zsh';",
+    },
+    Migration {
+        version: 4,
+        description: "layout history and snapshots",
+        sql: r#"
+            CREATE TABLE layout_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                workspace TEXT NOT NULL,
+                layout_json TEXT NOT NULL,
+                killed_sessions TEXT NOT NULL DEFAULT '[]',
+                undone INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX idx_layout_history_workspace ON layout_history(workspace, id DESC);
+
+            CREATE TABLE layout_snapshots (
+                workspace TEXT NOT NULL,
+                name TEXT NOT NULL,
+                layout_json TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (workspace, name)
+            );
+        "#,
+    },
+    Migration {
+        version: 5,
+        description: "color schemes and active scheme preferences",
+        sql: r#"
+            CREATE TABLE color_schemes (
+                name TEXT PRIMARY KEY,
+                variant TEXT NOT NULL,
+                palette_json TEXT NOT NULL,
+                source TEXT,
+                updated_at INTEGER NOT NULL
+            );
+
+            ALTER TABLE terminal_preferences ADD COLUMN color_scheme TEXT NOT NULL DEFAULT 'wiz-term Dark';
+            ALTER TABLE terminal_preferences ADD COLUMN color_scheme_light TEXT;
+        "#,
+    },
+    Migration {
+        version: 6,
+        description: "user keybindings",
+        sql: r#"
+            CREATE TABLE keybindings (
+                action TEXT PRIMARY KEY,
+                chord TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        version: 7,
+        description: "index session directories for the history browser",
+        sql: r#"
+            CREATE INDEX idx_terminal_sessions_cwd ON terminal_sessions(cwd);
+        "#,
+    },
+    Migration {
+        version: 8,
+        description: "retention preferences and maintenance log",
+        sql: r#"
+            ALTER TABLE terminal_preferences ADD COLUMN session_retention_days INTEGER NOT NULL DEFAULT 7;
+            ALTER TABLE terminal_preferences ADD COLUMN temp_file_retention_days INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE terminal_preferences ADD COLUMN maintenance_interval_hours INTEGER NOT NULL DEFAULT 24;
+
+            CREATE TABLE maintenance_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ran_at INTEGER NOT NULL,
+                report_json TEXT NOT NULL
+            );
+        "#,
+    },
+    Migration {
+        version: 9,
+        description: "empty shell_path means the login shell",
+        // Only the default changed (`TerminalPreferences::default`, which
+        // every save writes in full). A saved '/bin/zsh' may have been chosen
+        // on purpose, so it is kept; nothing to migrate
+        sql: "",
+    },
+    Migration {
+        version: 10,
+        description: "per-window workspaces replace the single layout row",
+        sql: r#"
+            CREATE TABLE workspaces (
+                workspace TEXT PRIMARY KEY,
+                layout_json TEXT,
+                window_x REAL,
+                window_y REAL,
+                window_width REAL,
+                window_height REAL,
+                maximized INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            INSERT INTO workspaces (workspace, layout_json, created_at, updated_at)
+            SELECT 'default', layout_json, updated_at, updated_at FROM terminal_layout WHERE id = 1;
+
+            DROP TABLE terminal_layout;
+        "#,
+    },
+    Migration {
+        version: 11,
+        description: "session resource stats interval preference",
+        sql: r#"
+            ALTER TABLE terminal_preferences ADD COLUMN session_stats_interval_ms INTEGER NOT NULL DEFAULT 0;
+        "#,
+    },
+    Migration {
+        version: 12,
+        description: "OSC 1337 file downloads and their preferences",
+        sql: r#"
+            CREATE TABLE downloads (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                path TEXT,
+                size_bytes INTEGER NOT NULL DEFAULT 0,
+                status TEXT NOT NULL,
+                error TEXT,
+                created_at INTEGER NOT NULL,
+                completed_at INTEGER
+            );
+            CREATE INDEX idx_downloads_created ON downloads(created_at);
+
+            ALTER TABLE terminal_preferences ADD COLUMN downloads_dir TEXT NOT NULL DEFAULT '';
+            ALTER TABLE terminal_preferences ADD COLUMN download_max_mb INTEGER NOT NULL DEFAULT 1024;
+            ALTER TABLE terminal_preferences ADD COLUMN download_policy TEXT NOT NULL DEFAULT 'ask';
+        "#,
+    },
+    Migration {
+        version: 13,
+        description: "OSC 52 clipboard policy preferences",
+        sql: r#"
+            ALTER TABLE terminal_preferences ADD COLUMN clipboard_write TEXT NOT NULL DEFAULT 'allow';
+            ALTER TABLE terminal_preferences ADD COLUMN clipboard_read TEXT NOT NULL DEFAULT 'deny';
+            ALTER TABLE terminal_preferences ADD COLUMN clipboard_max_kb INTEGER NOT NULL DEFAULT 1024;
+        "#,
+    },
+    Migration {
+        version: 14,
+        description: "session attachments (pasted and dropped files)",
+        sql: r#"
+            CREATE TABLE attachments (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                mime_type TEXT NOT NULL,
+                path TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX idx_attachments_session ON attachments(session_id);
+            CREATE INDEX idx_attachments_created ON attachments(created_at);
+
+            ALTER TABLE terminal_preferences ADD COLUMN attachment_max_mb INTEGER NOT NULL DEFAULT 100;
+        "#,
+    },
+    Migration {
+        version: 15,
+        description: "webview user agent preference",
+        sql: r#"
+            ALTER TABLE terminal_preferences ADD COLUMN webview_user_agent TEXT NOT NULL DEFAULT 'chrome';
+        "#,
+    },
+    Migration {
+        version: 16,
+        description: "webview developer tools and console log preferences",
+        sql: r#"
+            ALTER TABLE terminal_preferences ADD COLUMN webview_devtools INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE terminal_preferences ADD COLUMN webview_console_log INTEGER NOT NULL DEFAULT 0;
+        "#,
+    },
+];
+
+/// Schema version this build writes
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("Database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Database schema version {found} is newer than this build supports ({supported}); refusing to open it")]
+    NewerSchema { found: u32, supported: u32 },
+    #[error("Failed to back up database before migrating: {0}")]
+    Backup(String),
+    #[error("Migration {version} ({description}) failed: {source}")]
+    Failed {
+        version: u32,
+        description: &'static str,
+        source: rusqlite::Error,
+    },
+}
+
+/// Bring the schema up to `latest_version()`. Existing databases are copied to
+/// `<db>.v<old>.bak` before the first pending migration runs.
+pub fn migrate(conn: &mut Connection, db_path: &Path) -> Result<(), MigrationError> {
+    run_migrations(conn, db_path, MIGRATIONS)
+}
+
+fn run_migrations(
+    conn: &mut Connection,
+    db_path: &Path,
+    migrations: &[Migration],
+) -> Result<(), MigrationError> {
+    let mut current = schema_version(conn)?;
+    if current == 0 {
+        current = detect_legacy_version(conn)?;
+        if current > 0 {
+            info!(
+                "Adopting unversioned database at schema version {}",
+                current
+            );
+            conn.pragma_update(None, "user_version", current)?;
+        }
+    }
+
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    if current > latest {
+        return Err(MigrationError::NewerSchema {
+            found: current,
+            supported: latest,
+        });
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    if current > 0 {
+        backup(conn, db_path, current)?;
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        info!(
+            "Applying database migration {}: {}",
+            migration.version, migration.description
+        );
+        let fail = |source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            source,
+        };
+
+        let tx = conn.transaction().map_err(fail)?;
+        tx.execute_batch(migration.sql).map_err(fail)?;
+        tx.pragma_update(None, "user_version", migration.version)
+            .map_err(fail)?;
+        tx.commit().map_err(fail)?;
+    }
+
+    Ok(())
+}
+
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Databases created before `user_version` tracking used
+/// `CREATE TABLE IF NOT EXISTS` plus best-effort `ALTER TABLE`s. Work out
+/// which historical schema they match so only the missing steps run.
+fn detect_legacy_version(conn: &Connection) -> rusqlite::Result<u32> {
+    if !table_exists(conn, "terminal_preferences")? {
+        return Ok(0);
+    }
+    if table_exists(conn, "layout_history")? {
+        return Ok(4);
+    }
+    if column_exists(conn, "terminal_preferences", "shell_path")? {
+        return Ok(3);
+    }
+    if column_exists(conn, "terminal_preferences", "use_webgl")? {
+        return Ok(2);
+    }
+    Ok(1)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Copy the database aside with `VACUUM INTO` (safe while in WAL mode)
+fn backup(conn: &Connection, db_path: &Path, version: u32) -> Result<(), MigrationError> {
+    let mut backup_path = db_path.as_os_str().to_owned();
+    backup_path.push(format!(".v{}.bak", version));
+    let backup_path = std::path::PathBuf::from(backup_path);
+
+    if backup_path.exists() {
+        std::fs::remove_file(&backup_path).map_err(|e| MigrationError::Backup(e.to_string()))?;
+    }
+
+    conn.execute("VACUUM INTO ?1", [backup_path.to_string_lossy()])
+        .map_err(|e| MigrationError::Backup(e.to_string()))?;
+    info!("Backed up database to {}", backup_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Path `migrate` may write its backup next to, removed when dropped
+    struct TempDbPath(PathBuf);
+
+    impl TempDbPath {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "wiz-term-migrations-{}-{}",
+                name,
+                uuid::Uuid::new_v4().simple()
+            ));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir.join("wiz-term.db"))
+        }
+    }
+
+    impl Drop for TempDbPath {
+        fn drop(&mut self) {
+            if let Some(dir) = self.0.parent() {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    /// First release: preferences without `use_webgl` or `shell_path`
+    const LEGACY_V1_SCHEMA: &str = r#"
+        CREATE TABLE IF NOT EXISTS terminal_sessions (
+            id TEXT PRIMARY KEY,
+            command TEXT NOT NULL,
+            args TEXT NOT NULL,
+            cwd TEXT,
+            created_at INTEGER NOT NULL,
+            ended_at INTEGER,
+            exit_code INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_terminal_sessions_created ON terminal_sessions(created_at DESC);
+        CREATE TABLE IF NOT EXISTS terminal_layout (
+            id INTEGER PRIMARY KEY DEFAULT 1,
+            layout_json TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS terminal_preferences (
+            id INTEGER PRIMARY KEY DEFAULT 1,
+            font_size INTEGER NOT NULL DEFAULT 13,
+            font_family TEXT NOT NULL DEFAULT 'SF Mono',
+            scrollback INTEGER NOT NULL DEFAULT 10000,
+            cursor_blink INTEGER NOT NULL DEFAULT 1,
+            minimap_refresh_ms INTEGER NOT NULL DEFAULT 200,
+            updated_at INTEGER NOT NULL
+        );
+    "#;
+
+    /// The best-effort upgrades older builds ran on every start
+    const LEGACY_ADD_USE_WEBGL: &str =
+        "ALTER TABLE terminal_preferences ADD COLUMN use_webgl INTEGER NOT NULL DEFAULT 1;";
+    const LEGACY_ADD_SHELL_PATH: &str =
+        "ALTER TABLE terminal_preferences ADD COLUMN shell_path TEXT NOT NULL DEFAULT '/bin/zsh';";
+
+    /// Layout history tables, created unversioned just before migrations
+    /// were introduced
+    const LEGACY_HISTORY_SCHEMA: &str = r#"
+        CREATE TABLE IF NOT EXISTS layout_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workspace TEXT NOT NULL,
+            layout_json TEXT NOT NULL,
+            killed_sessions TEXT NOT NULL DEFAULT '[]',
+            undone INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_layout_history_workspace ON layout_history(workspace, id DESC);
+        CREATE TABLE IF NOT EXISTS layout_snapshots (
+            workspace TEXT NOT NULL,
+            name TEXT NOT NULL,
+            layout_json TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (workspace, name)
+        );
+    "#;
+
+    /// Unversioned databases as older builds left them: a name, the version
+    /// they should be adopted at and the SQL that built them, in order
+    const LEGACY_FIXTURES: &[(&str, u32, &[&str])] = &[
+        ("first release", 1, &[LEGACY_V1_SCHEMA]),
+        ("webgl added", 2, &[LEGACY_V1_SCHEMA, LEGACY_ADD_USE_WEBGL]),
+        (
+            "upgraded to shell_path",
+            3,
+            &[
+                LEGACY_V1_SCHEMA,
+                LEGACY_ADD_USE_WEBGL,
+                LEGACY_ADD_SHELL_PATH,
+            ],
+        ),
+        ("created with shell_path", 3, &[LEGACY_SCHEMA]),
+        ("layout history", 4, &[LEGACY_SCHEMA, LEGACY_HISTORY_SCHEMA]),
+    ];
+
+    /// Schema the app created before migrations were versioned
+    const LEGACY_SCHEMA: &str = r#"
+        CREATE TABLE IF NOT EXISTS terminal_sessions (
+            id TEXT PRIMARY KEY,
+            command TEXT NOT NULL,
+            args TEXT NOT NULL,
+            cwd TEXT,
+            created_at INTEGER NOT NULL,
+            ended_at INTEGER,
+            exit_code INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_terminal_sessions_created ON terminal_sessions(created_at DESC);
+        CREATE TABLE IF NOT EXISTS terminal_layout (
+            id INTEGER PRIMARY KEY DEFAULT 1,
+            layout_json TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS terminal_preferences (
+            id INTEGER PRIMARY KEY DEFAULT 1,
+            font_size INTEGER NOT NULL DEFAULT 13,
+            font_family TEXT NOT NULL DEFAULT 'SF Mono',
+            scrollback INTEGER NOT NULL DEFAULT 10000,
+            cursor_blink INTEGER NOT NULL DEFAULT 1,
+            minimap_refresh_ms INTEGER NOT NULL DEFAULT 200,
+            use_webgl INTEGER NOT NULL DEFAULT 1,
+            shell_path TEXT NOT NULL DEFAULT '/bin/zsh',
+            updated_at INTEGER NOT NULL
+        );
+    "#;
+
+    fn insert_session(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO terminal_sessions (id, command, args, cwd, created_at)
+             VALUES ('kept', '/bin/sh', '[]', '/tmp', 1)",
+            [],
+        )
+        .unwrap();
+    }
+
+    fn assert_session_kept(conn: &Connection) {
+        let command: String = conn
+            .query_row(
+                "SELECT command FROM terminal_sessions WHERE id = 'kept'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(command, "/bin/sh");
+    }
+
+    /// A versioned database as it was at `version`: every migration up to it
+    /// applied (only migrations ever created versioned databases)
+    fn schema_at(version: u32) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            conn.execute_batch(migration.sql).unwrap();
+        }
+        conn.pragma_update(None, "user_version", version).unwrap();
+        conn
+    }
+
+    /// Tables and indexes with their columns (sorted, since `ALTER TABLE`
+    /// appends where a fresh `CREATE TABLE` may not), comparable across
+    /// databases
+    fn schema_of(conn: &Connection) -> Vec<(String, String, Vec<String>)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT type, name FROM sqlite_master
+                 WHERE name NOT LIKE 'sqlite_%' ORDER BY type, name",
+            )
+            .unwrap();
+        let objects: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        objects
+            .into_iter()
+            .map(|(kind, name)| {
+                let columns = if kind == "table" {
+                    let mut stmt = conn
+                        .prepare(&format!("PRAGMA table_info({})", name))
+                        .unwrap();
+                    let mut columns: Vec<String> = stmt
+                        .query_map([], |row| {
+                            Ok(format!(
+                                "{} {} {:?}",
+                                row.get::<_, String>(1)?,
+                                row.get::<_, String>(2)?,
+                                row.get::<_, Option<String>>(4)?
+                            ))
+                        })
+                        .unwrap()
+                        .collect::<Result<_, _>>()
+                        .unwrap();
+                    columns.sort();
+                    columns
+                } else {
+                    Vec::new()
+                };
+                (kind, name, columns)
+            })
+            .collect()
+    }
+
+    fn latest_schema() -> Vec<(String, String, Vec<String>)> {
+        let path = TempDbPath::new("fresh");
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, &path.0).unwrap();
+        schema_of(&conn)
+    }
+
+    #[test]
+    fn migrations_are_numbered_in_order() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as u32 + 1);
+        }
+        assert_eq!(latest_version(), 16);
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest() {
+        let path = TempDbPath::new("empty");
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, &path.0).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert!(table_exists(&conn, "terminal_preferences").unwrap());
+    }
+
+    #[test]
+    fn every_versioned_schema_migrates_to_latest() {
+        let expected = latest_schema();
+        for version in 1..=latest_version() {
+            let path = TempDbPath::new("versioned");
+            let mut conn = schema_at(version);
+            insert_session(&conn);
+            migrate(&mut conn, &path.0)
+                .unwrap_or_else(|e| panic!("migrating from v{}: {}", version, e));
+            assert_eq!(schema_version(&conn).unwrap(), latest_version());
+            assert_eq!(
+                schema_of(&conn),
+                expected,
+                "schema migrated from v{}",
+                version
+            );
+            assert_session_kept(&conn);
+        }
+    }
+
+    #[test]
+    fn unversioned_legacy_schemas_are_adopted_and_migrated() {
+        let expected = latest_schema();
+        for (name, version, statements) in LEGACY_FIXTURES {
+            let path = TempDbPath::new("legacy");
+            let mut conn = Connection::open_in_memory().unwrap();
+            for sql in statements.iter() {
+                conn.execute_batch(sql).unwrap();
+            }
+            insert_session(&conn);
+            conn.execute_batch(
+                r#"INSERT INTO terminal_preferences (id, font_size, updated_at) VALUES (1, 15, 1);
+                   INSERT INTO terminal_layout (id, layout_json, updated_at)
+                   VALUES (1, '{"version":2,"tabs":[]}', 1);"#,
+            )
+            .unwrap();
+            assert_eq!(detect_legacy_version(&conn).unwrap(), *version, "{}", name);
+
+            migrate(&mut conn, &path.0)
+                .unwrap_or_else(|e| panic!("migrating legacy {}: {}", name, e));
+            assert_eq!(schema_version(&conn).unwrap(), latest_version());
+            assert_eq!(
+                schema_of(&conn),
+                expected,
+                "schema migrated from legacy {}",
+                name
+            );
+            assert_session_kept(&conn);
+
+            let font_size: i64 = conn
+                .query_row(
+                    "SELECT font_size FROM terminal_preferences WHERE id = 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(font_size, 15, "{}", name);
+            let layout: String = conn
+                .query_row(
+                    "SELECT layout_json FROM workspaces WHERE workspace = 'default'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(layout, r#"{"version":2,"tabs":[]}"#, "{}", name);
+        }
+    }
+
+    #[test]
+    fn pre_migration_database_is_adopted_and_migrated() {
+        let path = TempDbPath::new("baseline");
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LEGACY_SCHEMA).unwrap();
+        insert_session(&conn);
+        assert_eq!(detect_legacy_version(&conn).unwrap(), 3);
+
+        migrate(&mut conn, &path.0).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert_eq!(schema_of(&conn), latest_schema());
+        assert_session_kept(&conn);
+    }
+
+    #[test]
+    fn saved_shell_path_is_kept() {
+        let path = TempDbPath::new("shell");
+        let mut conn = schema_at(8);
+        conn.execute(
+            "INSERT INTO terminal_preferences (id, shell_path, updated_at) VALUES (1, '/bin/zsh', 1)",
+            [],
+        )
+        .unwrap();
+
+        migrate(&mut conn, &path.0).unwrap();
+        let shell: String = conn
+            .query_row(
+                "SELECT shell_path FROM terminal_preferences WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(shell, "/bin/zsh");
+    }
+
+    #[test]
+    fn existing_databases_are_backed_up_first() {
+        let path = TempDbPath::new("backup");
+        let mut conn = schema_at(3);
+        migrate(&mut conn, &path.0).unwrap();
+        let mut backup = path.0.as_os_str().to_owned();
+        backup.push(".v3.bak");
+        let backup = Connection::open(PathBuf::from(backup)).unwrap();
+        assert_eq!(schema_version(&backup).unwrap(), 3);
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let path = TempDbPath::new("newer");
+        let mut conn = schema_at(latest_version());
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+        match migrate(&mut conn, &path.0) {
+            Err(MigrationError::NewerSchema { found, supported }) => {
+                assert_eq!(found, latest_version() + 1);
+                assert_eq!(supported, latest_version());
+            }
+            other => panic!("expected NewerSchema, got {:?}", other),
+        }
+        assert_eq!(schema_version(&conn).unwrap(), latest_version() + 1);
+    }
+
+    #[test]
+    fn failed_migration_rolls_back() {
+        let migrations = [
+            Migration {
+                version: 1,
+                description: "first",
+                sql: "CREATE TABLE first (id INTEGER PRIMARY KEY);",
+            },
+            Migration {
+                version: 2,
+                description: "breaks halfway",
+                sql: "CREATE TABLE second (id INTEGER PRIMARY KEY);
+                      ALTER TABLE missing ADD COLUMN name TEXT;",
+            },
+        ];
+        let path = TempDbPath::new("rollback");
+        let mut conn = Connection::open_in_memory().unwrap();
+
+        match run_migrations(&mut conn, &path.0, &migrations) {
+            Err(MigrationError::Failed { version, .. }) => assert_eq!(version, 2),
+            other => panic!("expected Failed, got {:?}", other),
+        }
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        assert!(table_exists(&conn, "first").unwrap());
+        assert!(!table_exists(&conn, "second").unwrap());
+    }
+}
@@ -0,0 +1,189 @@
+use super::import::import_file;
+use super::scheme::{
+    builtin_scheme, builtin_schemes, ColorScheme, ColorSchemeSummary, Palette, SchemeVariant,
+    DEFAULT_SCHEME,
+};
+use crate::config::broadcast_preferences;
+use crate::storage::database::ColorSchemeRecord;
+use crate::AppState;
+use std::path::Path;
+use tauri::AppHandle;
+
+pub(crate) fn scheme_from_record(record: ColorSchemeRecord) -> Result<ColorScheme, String> {
+    let palette: Palette = serde_json::from_str(&record.palette_json)
+        .map_err(|e| format!("Corrupt palette for {}: {}", record.name, e))?;
+
+    Ok(ColorScheme {
+        variant: SchemeVariant::parse(&record.variant)
+            .unwrap_or_else(|| SchemeVariant::from_background(&palette.background)),
+        name: record.name,
+        palette,
+        source: record.source,
+        builtin: false,
+    })
+}
+
+/// Look up a scheme by name, built-ins first
+pub(crate) fn find_scheme(state: &AppState, name: &str) -> Result<Option<ColorScheme>, String> {
+    if let Some(scheme) = builtin_scheme(name) {
+        return Ok(Some(scheme));
+    }
+
+    state
+        .db
+        .get_color_scheme(name)
+        .map_err(|e| format!("Failed to get color scheme: {}", e))?
+        .map(scheme_from_record)
+        .transpose()
+}
+
+/// Validate and store a user scheme
+pub(crate) fn store_scheme(state: &AppState, scheme: &mut ColorScheme) -> Result<(), String> {
+    scheme.normalize().map_err(|e| e.to_string())?;
+    if builtin_scheme(&scheme.name).is_some() {
+        return Err(format!("{} is a built-in color scheme and cannot be replaced", scheme.name));
+    }
+    scheme.builtin = false;
+
+    let palette_json = serde_json::to_string(&scheme.palette).map_err(|e| e.to_string())?;
+    state
+        .db
+        .save_color_scheme(
+            &scheme.name,
+            scheme.variant.as_str(),
+            &palette_json,
+            scheme.source.as_deref(),
+        )
+        .map_err(|e| format!("Failed to save color scheme: {}", e))
+}
+
+/// List built-in and stored schemes (palettes omitted)
+#[tauri::command]
+pub async fn theme_list(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ColorSchemeSummary>, String> {
+    let mut schemes: Vec<ColorSchemeSummary> =
+        builtin_schemes().iter().map(ColorScheme::summary).collect();
+
+    let records = state
+        .db
+        .list_color_schemes()
+        .map_err(|e| format!("Failed to list color schemes: {}", e))?;
+    for record in records {
+        schemes.push(scheme_from_record(record)?.summary());
+    }
+
+    Ok(schemes)
+}
+
+#[tauri::command]
+pub async fn theme_get(
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<ColorScheme, String> {
+    find_scheme(&state, &name)?.ok_or_else(|| format!("Color scheme not found: {}", name))
+}
+
+/// Create or update a custom scheme
+#[tauri::command]
+pub async fn theme_save(
+    state: tauri::State<'_, AppState>,
+    mut scheme: ColorScheme,
+) -> Result<ColorScheme, String> {
+    store_scheme(&state, &mut scheme)?;
+    Ok(scheme)
+}
+
+/// Delete a custom scheme. Preferences pointing at it fall back to the default.
+#[tauri::command]
+pub async fn theme_delete(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    name: String,
+) -> Result<bool, String> {
+    if builtin_scheme(&name).is_some() {
+        return Err(format!("{} is a built-in color scheme and cannot be deleted", name));
+    }
+
+    let deleted = state
+        .db
+        .delete_color_scheme(&name)
+        .map_err(|e| format!("Failed to delete color scheme: {}", e))?;
+
+    if !deleted {
+        return Ok(false);
+    }
+
+    let mut prefs = state
+        .db
+        .get_terminal_preferences()
+        .map_err(|e| format!("Failed to get preferences: {}", e))?;
+    let mut changed = false;
+    if prefs.color_scheme == name {
+        prefs.color_scheme = DEFAULT_SCHEME.to_string();
+        changed = true;
+    }
+    if prefs.color_scheme_light.as_deref() == Some(name.as_str()) {
+        prefs.color_scheme_light = None;
+        changed = true;
+    }
+    if changed {
+        state
+            .db
+            .save_terminal_preferences(&prefs)
+            .map_err(|e| format!("Failed to save preferences: {}", e))?;
+        broadcast_preferences(&app, &state)?;
+    }
+
+    Ok(true)
+}
+
+/// Import every scheme from an iTerm2, Alacritty, WezTerm, Windows Terminal
+/// or base16 file. Names that clash with built-ins get an " (imported)" suffix.
+#[tauri::command]
+pub async fn theme_import(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<Vec<ColorScheme>, String> {
+    let expanded = shellexpand::tilde(&path).to_string();
+    let mut schemes = import_file(Path::new(&expanded)).map_err(|e| e.to_string())?;
+
+    for scheme in &mut schemes {
+        if builtin_scheme(&scheme.name).is_some() {
+            scheme.name = format!("{} (imported)", scheme.name);
+        }
+        store_scheme(&state, scheme)?;
+        tracing::info!("Imported color scheme {} from {}", scheme.name, path);
+    }
+
+    Ok(schemes)
+}
+
+/// Make a scheme active. `variant: "light"` sets the scheme used when the
+/// system appearance is light; otherwise the main (dark) scheme is set.
+#[tauri::command]
+pub async fn theme_set(
+    state: tauri::State<'_, AppState>,
+    app: AppHandle,
+    name: String,
+    variant: Option<SchemeVariant>,
+) -> Result<(), String> {
+    if find_scheme(&state, &name)?.is_none() {
+        return Err(format!("Color scheme not found: {}", name));
+    }
+
+    let mut prefs = state
+        .db
+        .get_terminal_preferences()
+        .map_err(|e| format!("Failed to get preferences: {}", e))?;
+    match variant {
+        Some(SchemeVariant::Light) => prefs.color_scheme_light = Some(name),
+        _ => prefs.color_scheme = name,
+    }
+    state
+        .db
+        .save_terminal_preferences(&prefs)
+        .map_err(|e| format!("Failed to save preferences: {}", e))?;
+
+    broadcast_preferences(&app, &state)
+}
@@ -0,0 +1,604 @@
+use super::scheme::{
+    format_color, normalize_color, parse_color, ColorScheme, Palette, SchemeVariant, ThemeError,
+};
+use std::path::Path;
+
+/// ANSI color names in palette order, as used by Alacritty and Windows Terminal
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// base16 slot for each ANSI color (base16-shell mapping)
+const BASE16_ANSI: [&str; 16] = [
+    "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05", "base03",
+    "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeFormat {
+    Iterm2,
+    Alacritty,
+    Wezterm,
+    WindowsTerminal,
+    Base16,
+}
+
+impl SchemeFormat {
+    pub fn id(&self) -> &'static str {
+        match self {
+            SchemeFormat::Iterm2 => "iterm2",
+            SchemeFormat::Alacritty => "alacritty",
+            SchemeFormat::Wezterm => "wezterm",
+            SchemeFormat::WindowsTerminal => "windows-terminal",
+            SchemeFormat::Base16 => "base16",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            SchemeFormat::Iterm2 => "iTerm2",
+            SchemeFormat::Alacritty => "Alacritty",
+            SchemeFormat::Wezterm => "WezTerm",
+            SchemeFormat::WindowsTerminal => "Windows Terminal",
+            SchemeFormat::Base16 => "base16",
+        }
+    }
+}
+
+/// Collects colors as they are found, then checks nothing required is missing
+struct PaletteBuilder {
+    format: SchemeFormat,
+    foreground: Option<String>,
+    background: Option<String>,
+    cursor: Option<String>,
+    selection: Option<String>,
+    ansi: [Option<String>; 16],
+}
+
+impl PaletteBuilder {
+    fn new(format: SchemeFormat) -> Self {
+        Self {
+            format,
+            foreground: None,
+            background: None,
+            cursor: None,
+            selection: None,
+            ansi: Default::default(),
+        }
+    }
+
+    fn color(&self, field: &str, value: &str) -> Result<String, ThemeError> {
+        normalize_color(&format!("{} {}", self.format.label(), field), value)
+    }
+
+    fn missing(&self, field: impl Into<String>) -> ThemeError {
+        ThemeError::Missing {
+            format: self.format.label(),
+            field: field.into(),
+        }
+    }
+
+    /// Cursor falls back to the foreground and selection to a translucent
+    /// foreground, since several formats treat them as optional
+    fn finish(self) -> Result<Palette, ThemeError> {
+        let foreground = self.foreground.clone().ok_or_else(|| self.missing("foreground"))?;
+        let background = self.background.clone().ok_or_else(|| self.missing("background"))?;
+
+        let mut ansi: [String; 16] = Default::default();
+        for (i, color) in self.ansi.iter().enumerate() {
+            ansi[i] = color
+                .clone()
+                .ok_or_else(|| self.missing(format!("ANSI color {}", i)))?;
+        }
+
+        let cursor = self.cursor.unwrap_or_else(|| foreground.clone());
+        let selection = self.selection.unwrap_or_else(|| {
+            let (r, g, b, _) = parse_color(&foreground).unwrap_or((255, 255, 255, 255));
+            format_color(r, g, b, 0x4d)
+        });
+
+        Ok(Palette {
+            foreground,
+            background,
+            cursor,
+            selection,
+            ansi,
+        })
+    }
+
+    fn into_scheme(self, name: String, variant: Option<SchemeVariant>) -> Result<ColorScheme, ThemeError> {
+        let source = Some(self.format.id().to_string());
+        let palette = self.finish()?;
+        Ok(ColorScheme {
+            name,
+            variant: variant.unwrap_or_else(|| SchemeVariant::from_background(&palette.background)),
+            palette,
+            source,
+            builtin: false,
+        })
+    }
+}
+
+fn parse_error(format: SchemeFormat, reason: impl ToString) -> ThemeError {
+    ThemeError::Parse {
+        format: format.label(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Read a color scheme file, detecting the format from its extension (and
+/// contents for TOML). Windows Terminal settings files may hold several schemes.
+pub fn import_file(path: &Path) -> Result<Vec<ColorScheme>, ThemeError> {
+    let display = path.display().to_string();
+    let contents = std::fs::read(path).map_err(|source| ThemeError::Read {
+        path: display.clone(),
+        source,
+    })?;
+
+    let fallback_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported".to_string());
+
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let schemes = match extension.as_str() {
+        "itermcolors" => vec![parse_iterm2(&contents, &fallback_name)?],
+        "toml" => {
+            let text = String::from_utf8_lossy(&contents);
+            vec![parse_toml(&text, &fallback_name)?]
+        }
+        "json" => parse_windows_terminal(&String::from_utf8_lossy(&contents), &fallback_name)?,
+        "yaml" | "yml" => vec![parse_base16(&String::from_utf8_lossy(&contents), &fallback_name)?],
+        _ => return Err(ThemeError::UnknownFormat(display)),
+    };
+
+    if schemes.is_empty() {
+        return Err(ThemeError::Empty);
+    }
+    Ok(schemes)
+}
+
+/// iTerm2 `.itermcolors`: a plist dict of `<Name> Color` dicts with float
+/// RGB components in 0..1
+pub fn parse_iterm2(contents: &[u8], name: &str) -> Result<ColorScheme, ThemeError> {
+    let format = SchemeFormat::Iterm2;
+    let value = plist::Value::from_reader(std::io::Cursor::new(contents))
+        .map_err(|e| parse_error(format, e))?;
+    let dict = value
+        .as_dictionary()
+        .ok_or_else(|| parse_error(format, "top level is not a dictionary"))?;
+
+    let color = |key: &str| -> Result<Option<String>, ThemeError> {
+        let Some(entry) = dict.get(key) else {
+            return Ok(None);
+        };
+        let components = entry
+            .as_dictionary()
+            .ok_or_else(|| parse_error(format, format!("{} is not a dictionary", key)))?;
+        let component = |name: &str, default: f64| {
+            components
+                .get(name)
+                .and_then(|v| v.as_real().or_else(|| v.as_signed_integer().map(|i| i as f64)))
+                .unwrap_or(default)
+        };
+        let to_byte = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Ok(Some(format_color(
+            to_byte(component("Red Component", 0.0)),
+            to_byte(component("Green Component", 0.0)),
+            to_byte(component("Blue Component", 0.0)),
+            to_byte(component("Alpha Component", 1.0)),
+        )))
+    };
+
+    let mut builder = PaletteBuilder::new(format);
+    builder.foreground = color("Foreground Color")?;
+    builder.background = color("Background Color")?;
+    builder.cursor = color("Cursor Color")?;
+    builder.selection = color("Selection Color")?;
+    for i in 0..16 {
+        builder.ansi[i] = color(&format!("Ansi {} Color", i))?;
+    }
+    builder.into_scheme(name.to_string(), None)
+}
+
+/// Alacritty (`[colors.primary]`, `[colors.normal]`, ...) or WezTerm
+/// (`[colors]` with `ansi`/`brights` arrays) TOML
+pub fn parse_toml(text: &str, name: &str) -> Result<ColorScheme, ThemeError> {
+    let value: toml::Value =
+        toml::from_str(text).map_err(|e| parse_error(SchemeFormat::Alacritty, e))?;
+    let colors = value
+        .get("colors")
+        .ok_or_else(|| parse_error(SchemeFormat::Alacritty, "no [colors] table"))?;
+
+    if colors.get("ansi").is_some() || colors.get("brights").is_some() {
+        parse_wezterm(&value, colors, name)
+    } else {
+        parse_alacritty(colors, name)
+    }
+}
+
+fn parse_alacritty(colors: &toml::Value, name: &str) -> Result<ColorScheme, ThemeError> {
+    let mut builder = PaletteBuilder::new(SchemeFormat::Alacritty);
+    let get = |table: &str, key: &str| {
+        colors
+            .get(table)
+            .and_then(|t| t.get(key))
+            .and_then(|v| v.as_str())
+    };
+
+    if let Some(v) = get("primary", "foreground") {
+        builder.foreground = Some(builder.color("primary.foreground", v)?);
+    }
+    if let Some(v) = get("primary", "background") {
+        builder.background = Some(builder.color("primary.background", v)?);
+    }
+    if let Some(v) = get("cursor", "cursor") {
+        builder.cursor = Some(builder.color("cursor.cursor", v)?);
+    }
+    if let Some(v) = get("selection", "background") {
+        builder.selection = Some(builder.color("selection.background", v)?);
+    }
+    for (i, color) in ANSI_NAMES.iter().enumerate() {
+        if let Some(v) = get("normal", color) {
+            builder.ansi[i] = Some(builder.color(&format!("normal.{}", color), v)?);
+        }
+        if let Some(v) = get("bright", color) {
+            builder.ansi[i + 8] = Some(builder.color(&format!("bright.{}", color), v)?);
+        }
+    }
+    builder.into_scheme(name.to_string(), None)
+}
+
+fn parse_wezterm(root: &toml::Value, colors: &toml::Value, name: &str) -> Result<ColorScheme, ThemeError> {
+    let mut builder = PaletteBuilder::new(SchemeFormat::Wezterm);
+    let get = |key: &str| colors.get(key).and_then(|v| v.as_str());
+
+    if let Some(v) = get("foreground") {
+        builder.foreground = Some(builder.color("foreground", v)?);
+    }
+    if let Some(v) = get("background") {
+        builder.background = Some(builder.color("background", v)?);
+    }
+    if let Some(v) = get("cursor_bg") {
+        builder.cursor = Some(builder.color("cursor_bg", v)?);
+    }
+    if let Some(v) = get("selection_bg") {
+        builder.selection = Some(builder.color("selection_bg", v)?);
+    }
+    for (key, offset) in [("ansi", 0), ("brights", 8)] {
+        if let Some(list) = colors.get(key).and_then(|v| v.as_array()) {
+            for (i, v) in list.iter().take(8).enumerate() {
+                let v = v.as_str().unwrap_or_default();
+                builder.ansi[offset + i] = Some(builder.color(&format!("{}[{}]", key, i), v)?);
+            }
+        }
+    }
+
+    let name = root
+        .get("metadata")
+        .and_then(|m| m.get("name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(name);
+    builder.into_scheme(name.to_string(), None)
+}
+
+/// Windows Terminal: a single scheme object, an array of them, or a full
+/// settings.json with a `schemes` array
+pub fn parse_windows_terminal(text: &str, name: &str) -> Result<Vec<ColorScheme>, ThemeError> {
+    let format = SchemeFormat::WindowsTerminal;
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| parse_error(format, e))?;
+
+    let objects: Vec<&serde_json::Value> = if let Some(schemes) = value.get("schemes").and_then(|v| v.as_array()) {
+        schemes.iter().collect()
+    } else if let Some(list) = value.as_array() {
+        list.iter().collect()
+    } else {
+        vec![&value]
+    };
+
+    objects
+        .into_iter()
+        .map(|scheme| {
+            let mut builder = PaletteBuilder::new(format);
+            let get = |key: &str| scheme.get(key).and_then(|v| v.as_str());
+
+            if let Some(v) = get("foreground") {
+                builder.foreground = Some(builder.color("foreground", v)?);
+            }
+            if let Some(v) = get("background") {
+                builder.background = Some(builder.color("background", v)?);
+            }
+            if let Some(v) = get("cursorColor") {
+                builder.cursor = Some(builder.color("cursorColor", v)?);
+            }
+            if let Some(v) = get("selectionBackground") {
+                builder.selection = Some(builder.color("selectionBackground", v)?);
+            }
+            for (i, color) in ANSI_NAMES.iter().enumerate() {
+                // Windows Terminal calls magenta "purple"
+                let key = if *color == "magenta" { "purple" } else { color };
+                if let Some(v) = get(key) {
+                    builder.ansi[i] = Some(builder.color(key, v)?);
+                }
+                let bright = format!("bright{}{}", key[..1].to_uppercase(), &key[1..]);
+                if let Some(v) = get(&bright) {
+                    builder.ansi[i + 8] = Some(builder.color(&bright, v)?);
+                }
+            }
+
+            builder.into_scheme(get("name").unwrap_or(name).to_string(), None)
+        })
+        .collect()
+}
+
+/// base16 YAML, either the classic flat form (`scheme`, `base00`..`base0F`)
+/// or the newer tinted-theming form (`name`, `variant`, `palette: { base00 }`)
+pub fn parse_base16(text: &str, name: &str) -> Result<ColorScheme, ThemeError> {
+    let format = SchemeFormat::Base16;
+    let value: serde_yaml::Value = serde_yaml::from_str(text).map_err(|e| parse_error(format, e))?;
+    let palette = value.get("palette").unwrap_or(&value);
+
+    let slot = |key: &str| -> Result<String, ThemeError> {
+        // Slot names appear both as base0B and base0b in the wild
+        let raw = palette
+            .get(key)
+            .or_else(|| palette.get(key.to_lowercase()))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ThemeError::Missing {
+                format: format.label(),
+                field: key.to_string(),
+            })?;
+        normalize_color(&format!("base16 {}", key), raw)
+    };
+
+    let mut builder = PaletteBuilder::new(format);
+    builder.background = Some(slot("base00")?);
+    builder.foreground = Some(slot("base05")?);
+    builder.cursor = Some(slot("base05")?);
+    builder.selection = Some(slot("base02")?);
+    for (i, key) in BASE16_ANSI.iter().enumerate() {
+        builder.ansi[i] = Some(slot(key)?);
+    }
+
+    let name = value
+        .get("name")
+        .or_else(|| value.get("scheme"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(name);
+    let variant = value
+        .get("variant")
+        .and_then(|v| v.as_str())
+        .and_then(SchemeVariant::parse);
+    builder.into_scheme(name.to_string(), variant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ansi(colors: [&str; 16]) -> [String; 16] {
+        colors.map(String::from)
+    }
+
+    /// One `<key>` entry of an `.itermcolors` dict, components in 0..1
+    fn iterm2_color(key: &str, red: f64, green: f64, blue: f64, alpha: Option<f64>) -> String {
+        let alpha = alpha
+            .map(|a| format!("<key>Alpha Component</key><real>{}</real>", a))
+            .unwrap_or_default();
+        format!(
+            "<key>{}</key><dict>{}<key>Blue Component</key><real>{}</real>\
+             <key>Color Space</key><string>sRGB</string>\
+             <key>Green Component</key><real>{}</real>\
+             <key>Red Component</key><real>{}</real></dict>",
+            key, alpha, blue, green, red
+        )
+    }
+
+    #[test]
+    fn iterm2_maps_named_colors_and_alpha() {
+        // Ansi N is red N/15, i.e. 0x11 * N
+        let mut entries: String = (0..16)
+            .map(|i| iterm2_color(&format!("Ansi {} Color", i), i as f64 / 15.0, 0.0, 0.0, None))
+            .collect();
+        entries.push_str(&iterm2_color("Foreground Color", 1.0, 1.0, 1.0, None));
+        entries.push_str(&iterm2_color("Background Color", 0.0, 0.0, 0.0, None));
+        entries.push_str(&iterm2_color("Cursor Color", 1.0, 0.5, 0.0, None));
+        entries.push_str(&iterm2_color("Selection Color", 0.0, 0.0, 1.0, Some(0.5)));
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0"><dict>{}</dict></plist>"#,
+            entries
+        );
+
+        let scheme = parse_iterm2(plist.as_bytes(), "Fixture").unwrap();
+        assert_eq!(scheme.name, "Fixture");
+        assert_eq!(scheme.source.as_deref(), Some("iterm2"));
+        assert_eq!(scheme.variant, SchemeVariant::Dark);
+        assert_eq!(scheme.palette.foreground, "#ffffff");
+        assert_eq!(scheme.palette.background, "#000000");
+        assert_eq!(scheme.palette.cursor, "#ff8000");
+        assert_eq!(scheme.palette.selection, "#0000ff80");
+        for (i, color) in scheme.palette.ansi.iter().enumerate() {
+            assert_eq!(*color, format!("#{:02x}0000", i * 0x11));
+        }
+    }
+
+    #[test]
+    fn alacritty_maps_tables() {
+        let toml = r##"
+            [colors.primary]
+            background = "0x1d1f21"
+            foreground = "#c5c8c6"
+
+            [colors.cursor]
+            text = "#1d1f21"
+            cursor = "#ffffff"
+
+            [colors.selection]
+            background = "#373b41"
+
+            [colors.normal]
+            black = "#000000"
+            red = "#cc6666"
+            green = "#b5bd68"
+            yellow = "#f0c674"
+            blue = "#81a2be"
+            magenta = "#b294bb"
+            cyan = "#8abeb7"
+            white = "#c5c8c6"
+
+            [colors.bright]
+            black = "#666"
+            red = "#d54e53"
+            green = "#b9ca4a"
+            yellow = "#e7c547"
+            blue = "#7aa6da"
+            magenta = "#c397d8"
+            cyan = "#70c0b1"
+            white = "#fff"
+        "##;
+
+        let scheme = parse_toml(toml, "Tomorrow Night").unwrap();
+        assert_eq!(scheme.name, "Tomorrow Night");
+        assert_eq!(scheme.source.as_deref(), Some("alacritty"));
+        assert_eq!(scheme.variant, SchemeVariant::Dark);
+        assert_eq!(scheme.palette.foreground, "#c5c8c6");
+        assert_eq!(scheme.palette.background, "#1d1f21");
+        assert_eq!(scheme.palette.cursor, "#ffffff");
+        assert_eq!(scheme.palette.selection, "#373b41");
+        assert_eq!(
+            scheme.palette.ansi,
+            ansi([
+                "#000000", "#cc6666", "#b5bd68", "#f0c674", "#81a2be", "#b294bb", "#8abeb7",
+                "#c5c8c6", "#666666", "#d54e53", "#b9ca4a", "#e7c547", "#7aa6da", "#c397d8",
+                "#70c0b1", "#ffffff",
+            ])
+        );
+    }
+
+    #[test]
+    fn wezterm_maps_arrays_and_metadata_name() {
+        let toml = r##"
+            [colors]
+            foreground = "#383a42"
+            background = "#fafafa"
+            cursor_bg = "#526eff"
+            selection_bg = "#e5e5e6"
+            ansi = ["#000000", "#e45649", "#50a14f", "#c18401", "#0184bc", "#a626a4", "#0997b3", "#fafafa"]
+            brights = ["#4f525e", "#e06c75", "#98c379", "#e5c07b", "#61afef", "#c678dd", "#56b6c2", "#ffffff"]
+
+            [metadata]
+            name = "One Light"
+        "##;
+
+        let scheme = parse_toml(toml, "one-light").unwrap();
+        assert_eq!(scheme.name, "One Light");
+        assert_eq!(scheme.source.as_deref(), Some("wezterm"));
+        assert_eq!(scheme.variant, SchemeVariant::Light);
+        assert_eq!(scheme.palette.foreground, "#383a42");
+        assert_eq!(scheme.palette.background, "#fafafa");
+        assert_eq!(scheme.palette.cursor, "#526eff");
+        assert_eq!(scheme.palette.selection, "#e5e5e6");
+        assert_eq!(
+            scheme.palette.ansi,
+            ansi([
+                "#000000", "#e45649", "#50a14f", "#c18401", "#0184bc", "#a626a4", "#0997b3",
+                "#fafafa", "#4f525e", "#e06c75", "#98c379", "#e5c07b", "#61afef", "#c678dd",
+                "#56b6c2", "#ffffff",
+            ])
+        );
+    }
+
+    #[test]
+    fn windows_terminal_maps_purple_to_magenta() {
+        let json = r##"{
+            "schemes": [{
+                "name": "Campbell",
+                "foreground": "#CCCCCC",
+                "background": "#0C0C0C",
+                "cursorColor": "#FFFFFF",
+                "selectionBackground": "#3A96DD",
+                "black": "#0C0C0C",
+                "red": "#C50F1F",
+                "green": "#13A10E",
+                "yellow": "#C19C00",
+                "blue": "#0037DA",
+                "purple": "#881798",
+                "cyan": "#3A96DD",
+                "white": "#CCCCCC",
+                "brightBlack": "#767676",
+                "brightRed": "#E74856",
+                "brightGreen": "#16C60C",
+                "brightYellow": "#F9F1A5",
+                "brightBlue": "#3B78FF",
+                "brightPurple": "#B4009E",
+                "brightCyan": "#61D6D6",
+                "brightWhite": "#F2F2F2"
+            }]
+        }"##;
+
+        let schemes = parse_windows_terminal(json, "settings").unwrap();
+        assert_eq!(schemes.len(), 1);
+        let scheme = &schemes[0];
+        assert_eq!(scheme.name, "Campbell");
+        assert_eq!(scheme.source.as_deref(), Some("windows-terminal"));
+        assert_eq!(scheme.palette.foreground, "#cccccc");
+        assert_eq!(scheme.palette.background, "#0c0c0c");
+        assert_eq!(scheme.palette.cursor, "#ffffff");
+        assert_eq!(scheme.palette.selection, "#3a96dd");
+        assert_eq!(
+            scheme.palette.ansi,
+            ansi([
+                "#0c0c0c", "#c50f1f", "#13a10e", "#c19c00", "#0037da", "#881798", "#3a96dd",
+                "#cccccc", "#767676", "#e74856", "#16c60c", "#f9f1a5", "#3b78ff", "#b4009e",
+                "#61d6d6", "#f2f2f2",
+            ])
+        );
+    }
+
+    #[test]
+    fn base16_maps_slots() {
+        let yaml = r#"
+scheme: "Default Dark"
+author: "Chris Kempson"
+base00: "181818"
+base01: "282828"
+base02: "383838"
+base03: "585858"
+base04: "b8b8b8"
+base05: "d8d8d8"
+base06: "e8e8e8"
+base07: "f8f8f8"
+base08: "ab4642"
+base09: "dc9656"
+base0A: "f7ca88"
+base0B: "a1b56c"
+base0C: "86c1b9"
+base0D: "7cafc2"
+base0E: "ba8baf"
+base0F: "a16946"
+"#;
+
+        let scheme = parse_base16(yaml, "default-dark").unwrap();
+        assert_eq!(scheme.name, "Default Dark");
+        assert_eq!(scheme.source.as_deref(), Some("base16"));
+        assert_eq!(scheme.variant, SchemeVariant::Dark);
+        assert_eq!(scheme.palette.foreground, "#d8d8d8");
+        assert_eq!(scheme.palette.background, "#181818");
+        assert_eq!(scheme.palette.cursor, "#d8d8d8");
+        assert_eq!(scheme.palette.selection, "#383838");
+        assert_eq!(
+            scheme.palette.ansi,
+            ansi([
+                "#181818", "#ab4642", "#a1b56c", "#f7ca88", "#7cafc2", "#ba8baf", "#86c1b9",
+                "#d8d8d8", "#585858", "#ab4642", "#a1b56c", "#f7ca88", "#7cafc2", "#ba8baf",
+                "#86c1b9", "#f8f8f8",
+            ])
+        );
+    }
+}
@@ -0,0 +1,6 @@
+pub mod commands;
+pub mod import;
+pub mod scheme;
+
+pub use commands::*;
+pub use scheme::*;
@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Name of the scheme used when none has been chosen
+pub const DEFAULT_SCHEME: &str = "wiz-term Dark";
+
+#[derive(Debug, Error)]
+pub enum ThemeError {
+    #[error("Failed to read {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Unrecognized color scheme format for {0} (expected .itermcolors, .toml, .json, .yaml or .yml)")]
+    UnknownFormat(String),
+    #[error("Failed to parse {format} color scheme: {reason}")]
+    Parse {
+        format: &'static str,
+        reason: String,
+    },
+    #[error("{format} color scheme is missing {field}")]
+    Missing {
+        format: &'static str,
+        field: String,
+    },
+    #[error("Invalid color {value:?} for {field}")]
+    InvalidColor { field: String, value: String },
+    #[error("No color schemes found in file")]
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemeVariant {
+    Light,
+    Dark,
+}
+
+impl SchemeVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemeVariant::Light => "light",
+            SchemeVariant::Dark => "dark",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "light" => Some(SchemeVariant::Light),
+            "dark" => Some(SchemeVariant::Dark),
+            _ => None,
+        }
+    }
+
+    /// Guess from the background's relative luminance
+    pub fn from_background(background: &str) -> Self {
+        match parse_color(background) {
+            Some((r, g, b, _)) => {
+                let luminance = 0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64;
+                if luminance > 127.5 {
+                    SchemeVariant::Light
+                } else {
+                    SchemeVariant::Dark
+                }
+            }
+            None => SchemeVariant::Dark,
+        }
+    }
+}
+
+/// Normalized palette. All colors are `#rrggbb` (or `#rrggbbaa` when
+/// translucent). `ansi` holds the 8 normal colors followed by the 8 bright ones.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub foreground: String,
+    pub background: String,
+    pub cursor: String,
+    pub selection: String,
+    pub ansi: [String; 16],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorScheme {
+    pub name: String,
+    pub variant: SchemeVariant,
+    pub palette: Palette,
+    /// Format the scheme was imported from, e.g. "iterm2" (None for custom/built-in)
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// Scheme list entry without the palette
+#[derive(Debug, Clone, Serialize)]
+pub struct ColorSchemeSummary {
+    pub name: String,
+    pub variant: SchemeVariant,
+    pub source: Option<String>,
+    pub builtin: bool,
+}
+
+impl ColorScheme {
+    pub fn summary(&self) -> ColorSchemeSummary {
+        ColorSchemeSummary {
+            name: self.name.clone(),
+            variant: self.variant,
+            source: self.source.clone(),
+            builtin: self.builtin,
+        }
+    }
+
+    /// Check the name and normalize every color in place
+    pub fn normalize(&mut self) -> Result<(), ThemeError> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err(ThemeError::Missing {
+                format: "Custom",
+                field: "name".to_string(),
+            });
+        }
+
+        let palette = &mut self.palette;
+        palette.foreground = normalize_color("foreground", &palette.foreground)?;
+        palette.background = normalize_color("background", &palette.background)?;
+        palette.cursor = normalize_color("cursor", &palette.cursor)?;
+        palette.selection = normalize_color("selection", &palette.selection)?;
+        for (i, color) in palette.ansi.iter_mut().enumerate() {
+            *color = normalize_color(&format!("ansi[{}]", i), color)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse `#rgb`, `#rrggbb`, `#rrggbbaa`, `0xrrggbb` or bare `rrggbb` into RGBA
+pub fn parse_color(value: &str) -> Option<(u8, u8, u8, u8)> {
+    let hex = value.trim();
+    let hex = hex
+        .strip_prefix('#')
+        .or_else(|| hex.strip_prefix("0x"))
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(hex);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => {
+            let short = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+            Some((short(0)?, short(1)?, short(2)?, 255))
+        }
+        6 => Some((channel(0)?, channel(2)?, channel(4)?, 255)),
+        8 => Some((channel(0)?, channel(2)?, channel(4)?, channel(6)?)),
+        _ => None,
+    }
+}
+
+pub fn format_color(r: u8, g: u8, b: u8, a: u8) -> String {
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
+pub fn normalize_color(field: &str, value: &str) -> Result<String, ThemeError> {
+    parse_color(value)
+        .map(|(r, g, b, a)| format_color(r, g, b, a))
+        .ok_or_else(|| ThemeError::InvalidColor {
+            field: field.to_string(),
+            value: value.to_string(),
+        })
+}
+
+fn palette(foreground: &str, background: &str, cursor: &str, selection: &str, ansi: [&str; 16]) -> Palette {
+    Palette {
+        foreground: foreground.to_string(),
+        background: background.to_string(),
+        cursor: cursor.to_string(),
+        selection: selection.to_string(),
+        ansi: ansi.map(String::from),
+    }
+}
+
+/// Schemes shipped with the app (cannot be overwritten or deleted)
+pub fn builtin_schemes() -> Vec<ColorScheme> {
+    vec![
+        ColorScheme {
+            name: DEFAULT_SCHEME.to_string(),
+            variant: SchemeVariant::Dark,
+            palette: palette(
+                "#e2e8f0",
+                "#19191f",
+                "#22c55e",
+                "#da77564d",
+                [
+                    "#0a0a0f", "#ef4444", "#22c55e", "#eab308", "#3b82f6", "#a855f7", "#06b6d4",
+                    "#e2e8f0", "#64748b", "#f87171", "#4ade80", "#facc15", "#60a5fa", "#c084fc",
+                    "#22d3ee", "#f8fafc",
+                ],
+            ),
+            source: None,
+            builtin: true,
+        },
+        ColorScheme {
+            name: "wiz-term Light".to_string(),
+            variant: SchemeVariant::Light,
+            palette: palette(
+                "#1e293b",
+                "#f8fafc",
+                "#16a34a",
+                "#da77564d",
+                [
+                    "#1e293b", "#dc2626", "#16a34a", "#ca8a04", "#2563eb", "#9333ea", "#0891b2",
+                    "#cbd5e1", "#64748b", "#ef4444", "#22c55e", "#eab308", "#3b82f6", "#a855f7",
+                    "#06b6d4", "#f1f5f9",
+                ],
+            ),
+            source: None,
+            builtin: true,
+        },
+    ]
+}
+
+pub fn builtin_scheme(name: &str) -> Option<ColorScheme> {
+    builtin_schemes().into_iter().find(|s| s.name == name)
+}
@@ -0,0 +1,332 @@
+use super::console::console_script;
+use super::devtools::devtools_enabled;
+use super::manager::WebviewState;
+use super::policy::{NavigationPolicy, WebviewNavigationBlocked};
+use super::profiles::{apply_profile, DEFAULT_PROFILE};
+use super::user_agent::{resolve_user_agent, set_user_agent, user_agent_setting};
+use crate::config::effective_preferences;
+use crate::layout::record_webview_page;
+use crate::workspace::workspace_for_window;
+use crate::AppState;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, WebviewUrl, Window};
+
+/// Sent when a webview starts a top-level navigation (`webview-navigated`)
+#[derive(Debug, Clone, Serialize)]
+pub struct WebviewNavigated {
+    pub id: String,
+    pub url: String,
+}
+
+/// Sent when a webview's document title changes (`webview-title-changed`)
+#[derive(Debug, Clone, Serialize)]
+pub struct WebviewTitleChanged {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebviewLoadPhase {
+    Started,
+    Finished,
+}
+
+/// Sent when a page starts or finishes loading (`webview-load-state`)
+#[derive(Debug, Clone, Serialize)]
+pub struct WebviewLoadState {
+    pub id: String,
+    pub url: String,
+    pub state: WebviewLoadPhase,
+}
+
+fn emit_webview_event<S: Serialize + Clone>(
+    app: &AppHandle,
+    window: &str,
+    event: &str,
+    payload: S,
+) {
+    if let Err(e) = app.emit_to(window, event, payload) {
+        tracing::error!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// Save a pane's current page to its workspace layout off the event loop
+fn remember_page(
+    app: &AppHandle,
+    workspace: &str,
+    node_id: &str,
+    url: Option<String>,
+    title: Option<String>,
+) {
+    let app = app.clone();
+    let workspace = workspace.to_string();
+    let node_id = node_id.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        if let Err(e) = record_webview_page(
+            &state,
+            &workspace,
+            &node_id,
+            url.as_deref(),
+            title.as_deref(),
+        ) {
+            tracing::warn!("Failed to record webview page: {}", e);
+        }
+    });
+}
+
+/// Create a child webview in the calling window, on a browser profile's store
+/// (the shared default one unless named) or, if `private`, on a throwaway one.
+/// Navigation, load and title changes are sent to the window as events; with
+/// `node_id`, the page's URL and title are also kept up to date in that layout
+/// node (not for private webviews). Only URLs the navigation policy allows
+/// can be opened, and pages are kept from leaving for any other. The user
+/// agent follows the `webview_user_agent` preference and the `[browser]`
+/// config overrides for the profile and each page's domain. With
+/// `webview_console_log`, the page's console goes to the console log.
+#[tauri::command]
+pub async fn create_webview(
+    window: Window,
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+    url: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    node_id: Option<String>,
+    profile: Option<String>,
+    private: Option<bool>,
+) -> Result<(), String> {
+    let profile = profile.unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    let private = private.unwrap_or(false);
+    let node_id = node_id.filter(|_| !private);
+    let app = window.app_handle().clone();
+    let target = window.label().to_string();
+    let workspace = workspace_for_window(&target);
+    let url: tauri::Url = url.parse().map_err(|e| format!("Invalid URL: {}", e))?;
+    let app_state = app.state::<AppState>();
+    NavigationPolicy::current(&app_state)
+        .check(&url)
+        .map_err(|blocked| blocked.to_string())?;
+    let user_agent = resolve_user_agent(&user_agent_setting(&app_state, &profile, url.host_str()));
+    let console_log = effective_preferences(&app_state)?.webview_console_log;
+
+    let on_navigation = {
+        let (app, target, id, profile) = (app.clone(), target.clone(), id.clone(), profile.clone());
+        let applied_user_agent = Mutex::new(user_agent.clone());
+        move |url: &tauri::Url| {
+            let state = app.state::<AppState>();
+            if let Err(blocked) = NavigationPolicy::current(&state).check(url) {
+                tracing::warn!("Webview {}: {}", id, blocked);
+                let event = WebviewNavigationBlocked {
+                    id: id.clone(),
+                    url: url.to_string(),
+                    reason: blocked.reason,
+                    message: blocked.message,
+                };
+                emit_webview_event(&app, &target, "webview-navigation-blocked", event);
+                return false;
+            }
+            // Switch user agents when the page moves to a domain with its own
+            let user_agent =
+                resolve_user_agent(&user_agent_setting(&state, &profile, url.host_str()));
+            if let Ok(mut applied) = applied_user_agent.lock() {
+                if *applied != user_agent {
+                    if let Some(webview) = app.get_webview(&id) {
+                        set_user_agent(&webview, user_agent.clone());
+                    }
+                    *applied = user_agent;
+                }
+            }
+            let navigated = WebviewNavigated {
+                id: id.clone(),
+                url: url.to_string(),
+            };
+            emit_webview_event(&app, &target, "webview-navigated", navigated);
+            true
+        }
+    };
+
+    let on_page_load = {
+        let (app, target, workspace, id, node_id) = (
+            app.clone(),
+            target.clone(),
+            workspace.clone(),
+            id.clone(),
+            node_id.clone(),
+        );
+        move |_webview: tauri::Webview, payload: tauri::webview::PageLoadPayload<'_>| {
+            let url = payload.url().to_string();
+            let phase = match payload.event() {
+                PageLoadEvent::Started => WebviewLoadPhase::Started,
+                PageLoadEvent::Finished => WebviewLoadPhase::Finished,
+            };
+            // The final URL, after any redirects
+            if phase == WebviewLoadPhase::Finished {
+                if let Some(node_id) = &node_id {
+                    remember_page(&app, &workspace, node_id, Some(url.clone()), None);
+                }
+            }
+            let load_state = WebviewLoadState {
+                id: id.clone(),
+                url,
+                state: phase,
+            };
+            emit_webview_event(&app, &target, "webview-load-state", load_state);
+        }
+    };
+
+    let on_title_changed = {
+        let (app, target, workspace, id, node_id) = (
+            app.clone(),
+            target.clone(),
+            workspace.clone(),
+            id.clone(),
+            node_id.clone(),
+        );
+        move |_webview: tauri::Webview, title: String| {
+            if let Some(node_id) = &node_id {
+                remember_page(&app, &workspace, node_id, None, Some(title.clone()));
+            }
+            let changed = WebviewTitleChanged {
+                id: id.clone(),
+                title,
+            };
+            emit_webview_event(&app, &target, "webview-title-changed", changed);
+        }
+    };
+
+    // Webviews on the same profile share cookies like tabs of one browser
+    let mut builder = tauri::webview::WebviewBuilder::new(&id, WebviewUrl::External(url))
+        .devtools(devtools_enabled(&app_state))
+        .on_navigation(on_navigation)
+        .on_page_load(on_page_load)
+        .on_document_title_changed(on_title_changed);
+    if let Some(user_agent) = &user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if console_log {
+        builder = builder.initialization_script(&console_script());
+    }
+    let webview = window
+        .add_child(
+            apply_profile(builder, &profile, private)?,
+            LogicalPosition::new(x, y),
+            LogicalSize::new(width, height),
+        )
+        .map_err(|e| format!("Failed to create webview: {}", e))?;
+
+    // Store the webview reference
+    let mut manager = state.manager.lock().unwrap();
+    manager.add(id.clone(), webview, (!private).then_some(profile));
+
+    tracing::info!("Created child webview: {} at ({}, {}) {}x{}", id, x, y, width, height);
+    Ok(())
+}
+
+/// Update position and size of a child webview
+#[tauri::command]
+pub async fn update_webview(
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Result<(), String> {
+    let manager = state.manager.lock().unwrap();
+    let webview = manager.get(&id).ok_or("Webview not found")?;
+
+    webview
+        .set_position(LogicalPosition::new(x, y))
+        .map_err(|e| format!("Failed to set position: {}", e))?;
+
+    webview
+        .set_size(LogicalSize::new(width, height))
+        .map_err(|e| format!("Failed to set size: {}", e))?;
+
+    Ok(())
+}
+
+/// Show or hide a child webview (webviews in background tabs stay loaded)
+#[tauri::command]
+pub async fn set_webview_visible(
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+    visible: bool,
+) -> Result<(), String> {
+    let manager = state.manager.lock().unwrap();
+    let webview = manager.get(&id).ok_or("Webview not found")?;
+
+    if visible {
+        webview.show()
+    } else {
+        webview.hide()
+    }
+    .map_err(|e| format!("Failed to change webview visibility: {}", e))
+}
+
+/// Close and remove a child webview
+#[tauri::command]
+pub async fn close_webview(
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+) -> Result<(), String> {
+    let mut manager = state.manager.lock().unwrap();
+
+    if let Ok(mut evals) = state.evals.lock() {
+        evals.remove_webview(&id);
+    }
+    if let Some(webview) = manager.remove(&id) {
+        webview
+            .close()
+            .map_err(|e| format!("Failed to close webview: {}", e))?;
+        tracing::info!("Closed child webview: {}", id);
+    }
+
+    Ok(())
+}
+
+/// Navigate a webview to a new URL, if the navigation policy allows it
+#[tauri::command]
+pub async fn navigate_webview(
+    state: tauri::State<'_, WebviewState>,
+    app_state: tauri::State<'_, AppState>,
+    id: String,
+    url: String,
+) -> Result<(), String> {
+    let url: tauri::Url = url.parse().map_err(|e| format!("Invalid URL: {}", e))?;
+    NavigationPolicy::current(&app_state)
+        .check(&url)
+        .map_err(|blocked| blocked.to_string())?;
+
+    let manager = state.manager.lock().unwrap();
+    let webview = manager.get(&id).ok_or("Webview not found")?;
+
+    webview
+        .navigate(url)
+        .map_err(|e| format!("Failed to navigate: {}", e))?;
+
+    Ok(())
+}
+
+/// Execute JavaScript in a webview
+#[tauri::command]
+pub async fn eval_webview(
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+    script: String,
+) -> Result<(), String> {
+    let manager = state.manager.lock().unwrap();
+    let webview = manager.get(&id).ok_or("Webview not found")?;
+
+    webview
+        .eval(&script)
+        .map_err(|e| format!("Failed to eval: {}", e))?;
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use super::protocol::protocol_url;
+use chrono::Utc;
+use serde::Deserialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Scheme pages post console messages to, served by `console_protocol`.
+/// A request rather than a navigation, so it can't cancel one the page makes.
+pub const CONSOLE_SCHEME: &str = "wiz-console";
+/// The log is moved to `.1` once it grows past this
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+/// Longest message kept, in characters (the rest is cut)
+const MAX_MESSAGE_CHARS: usize = 8 * 1024;
+/// Messages a page buffers between flushes before dropping new ones
+const MAX_BATCH: usize = 200;
+const FLUSH_MS: u32 = 250;
+
+#[derive(Debug, Deserialize)]
+struct ConsoleEntry {
+    level: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConsoleBatch {
+    entries: Vec<ConsoleEntry>,
+    #[serde(default)]
+    dropped: usize,
+}
+
+/// File console messages of every webview are appended to, for `tail -F`
+pub fn console_log_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("wiz-term")
+        .join("logs")
+        .join("webview-console.log")
+}
+
+/// Initialization script that copies the page's console messages, uncaught
+/// errors and unhandled rejections to the app in batches. Only the top frame
+/// reports.
+pub fn console_script() -> String {
+    format!(
+        r#"(() => {{
+  if (window.top !== window || window.__wizConsole) return;
+  window.__wizConsole = true;
+  let entries = [];
+  let dropped = 0;
+  let timer = null;
+  const flush = () => {{
+    timer = null;
+    if (!entries.length && !dropped) return;
+    const body = JSON.stringify({{ entries, dropped }});
+    entries = [];
+    dropped = 0;
+    // A page whose CSP refuses the request just isn't logged
+    fetch('{endpoint}', {{ method: 'POST', body }}).catch(() => {{}});
+  }};
+  const format = (arg) => {{
+    if (typeof arg === 'string') return arg;
+    if (arg instanceof Error) return arg.stack || `${{arg.name}}: ${{arg.message}}`;
+    try {{
+      const json = JSON.stringify(arg);
+      return json === undefined ? String(arg) : json;
+    }} catch (e) {{
+      return String(arg);
+    }}
+  }};
+  const push = (level, args) => {{
+    if (entries.length >= {max_batch}) {{
+      dropped += 1;
+    }} else {{
+      entries.push({{ level, message: Array.from(args, format).join(' ').slice(0, {max_chars}) }});
+    }}
+    if (timer === null) timer = setTimeout(flush, {flush_ms});
+  }};
+  for (const level of ['log', 'info', 'warn', 'error', 'debug']) {{
+    const original = console[level];
+    console[level] = function (...args) {{
+      push(level, args);
+      return original.apply(this, args);
+    }};
+  }}
+  window.addEventListener('error', (e) => push('error', [e.error || e.message]));
+  window.addEventListener('unhandledrejection', (e) =>
+    push('error', ['Unhandled rejection:', e.reason])
+  );
+}})();"#,
+        endpoint = protocol_url(CONSOLE_SCHEME, "log"),
+        max_batch = MAX_BATCH,
+        max_chars = MAX_MESSAGE_CHARS,
+        flush_ms = FLUSH_MS,
+    )
+}
+
+/// Append a batch a webview posted (JSON) to the console log
+pub fn append_console_batch(webview: &str, body: &[u8]) {
+    let Ok(batch) = serde_json::from_slice::<ConsoleBatch>(body) else {
+        warn!("Ignoring unreadable console batch from webview {}", webview);
+        return;
+    };
+
+    let timestamp = Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
+    let mut lines = String::new();
+    for entry in &batch.entries {
+        let message: String = entry.message.chars().take(MAX_MESSAGE_CHARS).collect();
+        lines.push_str(&format!(
+            "{} {} {}: {}\n",
+            timestamp,
+            webview,
+            entry.level,
+            // Indent continuation lines so every entry starts with a timestamp
+            message.replace('\n', "\n    ")
+        ));
+    }
+    if batch.dropped > 0 {
+        lines.push_str(&format!(
+            "{} {} warn: {} console messages dropped\n",
+            timestamp, webview, batch.dropped
+        ));
+    }
+
+    if let Err(e) = append_to_log(&lines) {
+        warn!("Failed to write webview console log: {}", e);
+    }
+}
+
+fn append_to_log(lines: &str) -> std::io::Result<()> {
+    let path = console_log_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::metadata(&path).is_ok_and(|meta| meta.len() > MAX_LOG_BYTES) {
+        std::fs::rename(&path, path.with_extension("log.1"))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?
+        .write_all(lines.as_bytes())
+}
+
+/// Path of the webview console log, for tailing in a terminal
+#[tauri::command]
+pub async fn webview_console_log_path() -> Result<String, String> {
+    Ok(console_log_path().to_string_lossy().to_string())
+}
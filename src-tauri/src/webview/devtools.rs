@@ -0,0 +1,44 @@
+use super::manager::WebviewState;
+use crate::config::effective_preferences;
+use crate::AppState;
+
+/// Developer tools are always available in debug builds; release builds need
+/// the `webview_devtools` preference
+pub fn devtools_enabled(state: &AppState) -> bool {
+    cfg!(debug_assertions)
+        || effective_preferences(state)
+            .map(|prefs| prefs.webview_devtools)
+            .unwrap_or(false)
+}
+
+/// Open the inspector for a child webview
+#[tauri::command]
+pub async fn open_webview_devtools(
+    state: tauri::State<'_, WebviewState>,
+    app_state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    if !devtools_enabled(&app_state) {
+        return Err(
+            "Developer tools are turned off; enable webview_devtools in settings".to_string(),
+        );
+    }
+    let manager = state.manager.lock().unwrap();
+    let webview = manager.get(&id).ok_or("Webview not found")?;
+
+    webview.open_devtools();
+    tracing::info!("Opened developer tools for webview {}", id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn close_webview_devtools(
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+) -> Result<(), String> {
+    let manager = state.manager.lock().unwrap();
+    let webview = manager.get(&id).ok_or("Webview not found")?;
+
+    webview.close_devtools();
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use super::manager::WebviewState;
+use super::protocol::protocol_url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// Scheme pages post eval results to, served by `eval_protocol`. A request
+/// rather than a navigation, so the page's own navigations are left alone.
+pub const EVAL_RESULT_SCHEME: &str = "wiz-eval";
+pub const DEFAULT_EVAL_TIMEOUT_MS: u64 = 5_000;
+pub const MAX_EVAL_TIMEOUT_MS: u64 = 60_000;
+/// Largest result a page may send back, as UTF-8 JSON
+const MAX_RESULT_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalErrorKind {
+    /// The script threw or its promise rejected
+    Exception,
+    /// The result can't be turned into JSON (cycles, BigInt, ...)
+    NotSerializable,
+    TooLarge,
+    Timeout,
+    /// The webview closed while waiting
+    Closed,
+    /// The page sent back something that isn't a result
+    InvalidReply,
+}
+
+/// What `eval_webview_with_result` returns: the script's JSON result or why
+/// there isn't one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum EvalOutcome {
+    Ok {
+        value: Value,
+    },
+    Error {
+        kind: EvalErrorKind,
+        message: String,
+    },
+}
+
+impl EvalOutcome {
+    fn error(kind: EvalErrorKind, message: impl Into<String>) -> Self {
+        Self::Error {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+struct PendingEval {
+    webview: String,
+    reply: oneshot::Sender<EvalOutcome>,
+}
+
+/// Scripts waiting for their page to send back a result
+#[derive(Default)]
+pub struct EvalRegistry {
+    pending: HashMap<String, PendingEval>,
+}
+
+impl EvalRegistry {
+    fn register(&mut self, webview: &str) -> (String, oneshot::Receiver<EvalOutcome>) {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let (reply, receiver) = oneshot::channel();
+        self.pending.insert(
+            id.clone(),
+            PendingEval {
+                webview: webview.to_string(),
+                reply,
+            },
+        );
+        (id, receiver)
+    }
+
+    fn cancel(&mut self, id: &str) {
+        self.pending.remove(id);
+    }
+
+    /// Stop waiting on a closing webview; its callers get a `closed` error
+    pub fn remove_webview(&mut self, webview: &str) {
+        self.pending.retain(|_, pending| pending.webview != webview);
+    }
+
+    /// Hand a result `webview` posted for request `id` (JSON) to the caller
+    /// waiting on it. Results for another webview's script are ignored.
+    pub fn deliver(&mut self, webview: &str, id: &str, body: &[u8]) {
+        let expected = self
+            .pending
+            .get(id)
+            .is_some_and(|pending| pending.webview == webview);
+        if !expected {
+            warn!("Ignoring unexpected eval result from webview {}", webview);
+            return;
+        }
+        let Some(pending) = self.pending.remove(id) else {
+            return;
+        };
+
+        let outcome = serde_json::from_slice(body).unwrap_or_else(|_| {
+            EvalOutcome::error(EvalErrorKind::InvalidReply, "Unreadable result from page")
+        });
+        // The caller may have timed out in the meantime
+        let _ = pending.reply.send(outcome);
+    }
+}
+
+/// Wrap a script so it runs as the body of an async function and its result
+/// (or error) is posted back to `wiz-eval:`
+fn bridge_script(request_id: &str, script: &str) -> String {
+    format!(
+        r#"(async () => {{
+  const send = (json) => {{
+    const size = new TextEncoder().encode(json).length;
+    if (size > {max}) {{
+      json = JSON.stringify({{
+        status: 'error',
+        kind: 'too_large',
+        message: `Result is ${{size}} bytes, over the {max} byte limit`
+      }});
+    }}
+    fetch('{endpoint}', {{ method: 'POST', body: json }}).catch(() => {{}});
+  }};
+  const fail = (kind, e) => send(JSON.stringify({{
+    status: 'error',
+    kind,
+    message: e instanceof Error ? `${{e.name}}: ${{e.message}}` : String(e)
+  }}));
+  let value;
+  try {{
+    value = await (async () => {{
+{script}
+    }})();
+  }} catch (e) {{
+    fail('exception', e);
+    return;
+  }}
+  let json;
+  try {{
+    json = JSON.stringify(value);
+  }} catch (e) {{
+    fail('not_serializable', e);
+    return;
+  }}
+  send('{{"status":"ok","value":' + (json === undefined ? 'null' : json) + '}}');
+}})();"#,
+        max = MAX_RESULT_BYTES,
+        endpoint = protocol_url(EVAL_RESULT_SCHEME, &format!("result/{}", request_id)),
+        script = script,
+    )
+}
+
+/// Run a script in a webview and wait for its result. The script is the body
+/// of an async function: `return` (or resolve to) a JSON-serializable value.
+#[tauri::command]
+pub async fn eval_webview_with_result(
+    state: tauri::State<'_, WebviewState>,
+    id: String,
+    script: String,
+    timeout_ms: Option<u64>,
+) -> Result<EvalOutcome, String> {
+    let timeout_ms = timeout_ms
+        .unwrap_or(DEFAULT_EVAL_TIMEOUT_MS)
+        .clamp(1, MAX_EVAL_TIMEOUT_MS);
+
+    let (request_id, reply) = {
+        let manager = state
+            .manager
+            .lock()
+            .map_err(|e| format!("Failed to lock webviews: {}", e))?;
+        let webview = manager.get(&id).ok_or("Webview not found")?;
+
+        let mut evals = state
+            .evals
+            .lock()
+            .map_err(|e| format!("Failed to lock webview evals: {}", e))?;
+        let (request_id, reply) = evals.register(&id);
+        if let Err(e) = webview.eval(&bridge_script(&request_id, &script)) {
+            evals.cancel(&request_id);
+            return Err(format!("Failed to eval: {}", e));
+        }
+        (request_id, reply)
+    };
+
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), reply).await {
+        Ok(Ok(outcome)) => Ok(outcome),
+        Ok(Err(_)) => Ok(EvalOutcome::error(
+            EvalErrorKind::Closed,
+            "The webview closed before the script finished",
+        )),
+        Err(_) => {
+            if let Ok(mut evals) = state.evals.lock() {
+                evals.cancel(&request_id);
+            }
+            Ok(EvalOutcome::error(
+                EvalErrorKind::Timeout,
+                format!("No result after {} ms", timeout_ms),
+            ))
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use super::eval::EvalRegistry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A child webview and the browser profile it was created with
+struct ManagedWebview {
+    webview: tauri::Webview,
+    /// `None` for a private webview
+    profile: Option<String>,
+}
+
+/// Manages child webviews across all windows
+pub struct WebviewManager {
+    webviews: HashMap<String, ManagedWebview>,
+}
+
+impl WebviewManager {
+    pub fn new() -> Self {
+        Self {
+            webviews: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, id: String, webview: tauri::Webview, profile: Option<String>) {
+        self.webviews
+            .insert(id, ManagedWebview { webview, profile });
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<tauri::Webview> {
+        self.webviews.remove(id).map(|managed| managed.webview)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&tauri::Webview> {
+        self.webviews.get(id).map(|managed| &managed.webview)
+    }
+
+    /// Open (non-private) webviews using a browser profile
+    pub fn in_profile(&self, profile: &str) -> Vec<&tauri::Webview> {
+        self.webviews
+            .values()
+            .filter(|managed| managed.profile.as_deref() == Some(profile))
+            .map(|managed| &managed.webview)
+            .collect()
+    }
+
+    /// Forget the webviews of a window that is closing (they are destroyed
+    /// along with it)
+    pub fn remove_window(&mut self, label: &str) -> usize {
+        let before = self.webviews.len();
+        self.webviews
+            .retain(|_, managed| managed.webview.window().label() != label);
+        before - self.webviews.len()
+    }
+}
+
+pub struct WebviewState {
+    pub manager: Mutex<WebviewManager>,
+    /// Scripts from `eval_webview_with_result` waiting on their page
+    pub evals: Mutex<EvalRegistry>,
+}
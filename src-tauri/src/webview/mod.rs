@@ -0,0 +1,19 @@
+pub mod commands;
+pub mod console;
+pub mod devtools;
+pub mod eval;
+pub mod manager;
+pub mod policy;
+pub mod profiles;
+pub mod protocol;
+pub mod user_agent;
+
+pub use commands::*;
+pub use console::*;
+pub use devtools::*;
+pub use eval::*;
+pub use manager::*;
+pub use policy::*;
+pub use profiles::*;
+pub use protocol::*;
+pub use user_agent::*;
@@ -0,0 +1,157 @@
+use crate::AppState;
+use serde::Serialize;
+use std::fmt;
+use std::net::IpAddr;
+use tauri::Url;
+
+/// Schemes any page may be loaded from
+const ALLOWED_SCHEMES: &[&str] = &["http", "https"];
+
+/// Why a webview was kept from loading a URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NavigationBlockReason {
+    /// Not http(s) (or `about:`)
+    Scheme,
+    /// A loopback host without `[browser] allow_localhost`
+    Localhost,
+    /// A `file:` URL without `[browser] allow_file`
+    File,
+    /// The host is in `[browser] blocked_domains`
+    BlockedDomain,
+    /// `[browser] allowed_domains` is set and doesn't include the host
+    NotAllowedDomain,
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockedNavigation {
+    pub reason: NavigationBlockReason,
+    pub message: String,
+}
+
+impl fmt::Display for BlockedNavigation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Sent when a page tries to go somewhere the policy doesn't allow
+/// (`webview-navigation-blocked`). The page stays where it was.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebviewNavigationBlocked {
+    pub id: String,
+    pub url: String,
+    pub reason: NavigationBlockReason,
+    pub message: String,
+}
+
+/// Whether `host` is `domain` or one of its subdomains (a leading `.` on
+/// `domain` is ignored)
+pub fn host_matches(host: &str, domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.');
+    host.eq_ignore_ascii_case(domain)
+        || (host.len() > domain.len()
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+            && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain))
+}
+
+fn is_localhost(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    host_matches(host, "localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Where webviews may go, from the `[browser]` config
+#[derive(Debug, Clone, Default)]
+pub struct NavigationPolicy {
+    pub allow_localhost: bool,
+    pub allow_file: bool,
+    pub allowed_domains: Vec<String>,
+    pub blocked_domains: Vec<String>,
+}
+
+impl NavigationPolicy {
+    pub fn current(state: &AppState) -> Self {
+        let Ok(config) = state.config.lock() else {
+            return Self::default();
+        };
+        let Some(browser) = config.file.as_ref().map(|file| &file.browser) else {
+            return Self::default();
+        };
+        Self {
+            allow_localhost: browser.allow_localhost.unwrap_or(false),
+            allow_file: browser.allow_file.unwrap_or(false),
+            allowed_domains: browser.allowed_domains.clone(),
+            blocked_domains: browser.blocked_domains.clone(),
+        }
+    }
+
+    /// Check a top-level navigation. Domain lists don't apply to localhost,
+    /// which has its own switch.
+    pub fn check(&self, url: &Url) -> Result<(), BlockedNavigation> {
+        let blocked = |reason, message: String| {
+            Err(BlockedNavigation {
+                reason,
+                message: format!("{} blocked: {}", url, message),
+            })
+        };
+
+        match url.scheme() {
+            "about" => return Ok(()),
+            "file" if self.allow_file => return Ok(()),
+            "file" => {
+                return blocked(
+                    NavigationBlockReason::File,
+                    "file URLs need [browser] allow_file = true".to_string(),
+                )
+            }
+            scheme if !ALLOWED_SCHEMES.contains(&scheme) => {
+                return blocked(
+                    NavigationBlockReason::Scheme,
+                    format!("{}: URLs can't be opened in a browser pane", scheme),
+                )
+            }
+            _ => {}
+        }
+
+        if is_localhost(url) {
+            if self.allow_localhost {
+                return Ok(());
+            }
+            return blocked(
+                NavigationBlockReason::Localhost,
+                "localhost needs [browser] allow_localhost = true".to_string(),
+            );
+        }
+
+        let host = url.host_str().unwrap_or_default();
+        if let Some(domain) = self
+            .blocked_domains
+            .iter()
+            .find(|domain| host_matches(host, domain))
+        {
+            return blocked(
+                NavigationBlockReason::BlockedDomain,
+                format!("{} is in [browser] blocked_domains", domain),
+            );
+        }
+        if !self.allowed_domains.is_empty()
+            && !self
+                .allowed_domains
+                .iter()
+                .any(|domain| host_matches(host, domain))
+        {
+            return blocked(
+                NavigationBlockReason::NotAllowedDomain,
+                format!("{} is not in [browser] allowed_domains", host),
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use super::manager::WebviewState;
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::webview::WebviewBuilder;
+use tauri::{LogicalPosition, LogicalSize, WebviewUrl, Window};
+use tracing::info;
+
+/// Profile of webviews that don't name one: the engine's default store,
+/// shared like tabs of one browser
+pub const DEFAULT_PROFILE: &str = "default";
+const MAX_PROFILE_NAME_CHARS: usize = 64;
+/// Random identifier of a profile's store (macOS keys stores by UUID)
+const STORE_ID_FILE: &str = "store-id";
+
+/// A browser profile as listed for the profile menu
+#[derive(Debug, Clone, Serialize)]
+pub struct BrowserProfile {
+    pub name: String,
+    /// Webviews currently using it
+    pub open_webviews: usize,
+}
+
+/// Directory holding one subdirectory per named profile
+pub fn profiles_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("wiz-term")
+        .join("browser-profiles")
+}
+
+/// Profile names become directory names, so keep them to `[A-Za-z0-9_-]`
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.chars().count() > MAX_PROFILE_NAME_CHARS {
+        return Err(format!(
+            "Profile name must be 1 to {} characters",
+            MAX_PROFILE_NAME_CHARS
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+    {
+        return Err(format!(
+            "Profile name {:?} may only use letters, digits, '_' and '-'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Create a profile's directory and store identifier if they don't exist
+fn ensure_profile(name: &str) -> Result<PathBuf, String> {
+    let dir = profiles_dir().join(name);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create profile {}: {}", name, e))?;
+    let id_file = dir.join(STORE_ID_FILE);
+    if !id_file.exists() {
+        std::fs::write(&id_file, uuid::Uuid::new_v4().to_string())
+            .map_err(|e| format!("Failed to create profile {}: {}", name, e))?;
+    }
+    Ok(dir)
+}
+
+#[cfg(target_os = "macos")]
+fn store_identifier(dir: &std::path::Path) -> Result<[u8; 16], String> {
+    let id = std::fs::read_to_string(dir.join(STORE_ID_FILE))
+        .map_err(|e| format!("Failed to read profile store ID: {}", e))?;
+    uuid::Uuid::parse_str(id.trim())
+        .map(|id| *id.as_bytes())
+        .map_err(|e| format!("Invalid profile store ID: {}", e))
+}
+
+/// Remove a named profile's WebKit data store. macOS keeps it outside the
+/// profile directory, keyed by the profile's identifier, so deleting the
+/// directory alone would leave cookies and storage behind.
+#[cfg(target_os = "macos")]
+async fn remove_data_store(window: &Window, name: &str) -> Result<(), String> {
+    use objc2_foundation::{MainThreadMarker, NSError, NSUUID};
+    use objc2_web_kit::WKWebsiteDataStore;
+
+    let id = store_identifier(&profiles_dir().join(name))?;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    window
+        .run_on_main_thread(move || {
+            let Some(mtm) = MainThreadMarker::new() else {
+                let _ = tx.send(Err("not on the main thread".to_string()));
+                return;
+            };
+            let identifier = NSUUID::from_bytes(id);
+            let done = block2::RcBlock::new(move |error: *mut NSError| {
+                let result = match unsafe { error.as_ref() } {
+                    Some(error) => Err(error.localizedDescription().to_string()),
+                    None => Ok(()),
+                };
+                let _ = tx.send(result);
+            });
+            unsafe {
+                WKWebsiteDataStore::removeDataStoreForIdentifier_completionHandler(
+                    &identifier,
+                    &done,
+                    mtm,
+                );
+            }
+        })
+        .map_err(|e| format!("Failed to delete profile {}: {}", name, e))?;
+
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("WebKit did not answer".to_string()))
+        .map_err(|e| format!("Failed to delete profile {}: {}", name, e))
+}
+
+/// Point a webview at its profile's store: the default store, or the named
+/// profile's own data directory (macOS: a data store keyed by the profile's
+/// identifier). Private webviews get a throwaway store instead.
+pub fn apply_profile<R: tauri::Runtime>(
+    builder: WebviewBuilder<R>,
+    profile: &str,
+    private: bool,
+) -> Result<WebviewBuilder<R>, String> {
+    if private {
+        return Ok(builder.incognito(true));
+    }
+    if profile == DEFAULT_PROFILE {
+        return Ok(builder);
+    }
+    validate_profile_name(profile)?;
+    let dir = ensure_profile(profile)?;
+    #[cfg(target_os = "macos")]
+    let builder = builder.data_store_identifier(store_identifier(&dir)?);
+    Ok(builder.data_directory(dir))
+}
+
+/// Named profiles with their data on disk, plus the default one
+#[tauri::command]
+pub async fn browser_profile_list(
+    state: tauri::State<'_, WebviewState>,
+) -> Result<Vec<BrowserProfile>, String> {
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    if let Ok(entries) = std::fs::read_dir(profiles_dir()) {
+        let mut found: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name != DEFAULT_PROFILE && validate_profile_name(name).is_ok())
+            .collect();
+        found.sort();
+        names.extend(found);
+    }
+
+    let manager = state
+        .manager
+        .lock()
+        .map_err(|e| format!("Failed to lock webviews: {}", e))?;
+    Ok(names
+        .into_iter()
+        .map(|name| BrowserProfile {
+            open_webviews: manager.in_profile(&name).len(),
+            name,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn browser_profile_create(name: String) -> Result<BrowserProfile, String> {
+    let name = name.trim().to_string();
+    if name != DEFAULT_PROFILE {
+        validate_profile_name(&name)?;
+        ensure_profile(&name)?;
+    }
+    Ok(BrowserProfile {
+        name,
+        open_webviews: 0,
+    })
+}
+
+/// Delete a profile's cookies, storage and cache. This goes through a webview
+/// on the profile's store, borrowing a hidden one in the calling window if
+/// none is open.
+#[tauri::command]
+pub async fn browser_profile_clear(
+    window: Window,
+    state: tauri::State<'_, WebviewState>,
+    name: String,
+) -> Result<(), String> {
+    if name != DEFAULT_PROFILE {
+        validate_profile_name(&name)?;
+        if !profiles_dir().join(&name).is_dir() {
+            return Err(format!("Browser profile not found: {}", name));
+        }
+    }
+
+    {
+        let manager = state
+            .manager
+            .lock()
+            .map_err(|e| format!("Failed to lock webviews: {}", e))?;
+        if let Some(webview) = manager.in_profile(&name).first() {
+            webview
+                .clear_all_browsing_data()
+                .map_err(|e| format!("Failed to clear profile {}: {}", name, e))?;
+            info!("Cleared browser profile {}", name);
+            return Ok(());
+        }
+    }
+
+    let label = format!("profile-clear-{}", uuid::Uuid::new_v4().simple());
+    let blank = "about:blank"
+        .parse()
+        .map_err(|e| format!("Invalid URL: {}", e))?;
+    let builder = apply_profile(
+        WebviewBuilder::new(&label, WebviewUrl::External(blank)),
+        &name,
+        false,
+    )?;
+    let webview = window
+        .add_child(
+            builder,
+            LogicalPosition::new(0.0, 0.0),
+            LogicalSize::new(1.0, 1.0),
+        )
+        .map_err(|e| format!("Failed to open profile {}: {}", name, e))?;
+    let result = webview
+        .hide()
+        .and_then(|_| webview.clear_all_browsing_data())
+        .map_err(|e| format!("Failed to clear profile {}: {}", name, e));
+    let _ = webview.close();
+    result?;
+    info!("Cleared browser profile {}", name);
+    Ok(())
+}
+
+/// Clear a named profile and remove it (on macOS, its WebKit data store too).
+/// Fails while webviews use it.
+#[tauri::command]
+pub async fn browser_profile_delete(
+    window: Window,
+    state: tauri::State<'_, WebviewState>,
+    name: String,
+) -> Result<(), String> {
+    if name == DEFAULT_PROFILE {
+        return Err("The default profile can't be deleted".to_string());
+    }
+    validate_profile_name(&name)?;
+    let open = state
+        .manager
+        .lock()
+        .map_err(|e| format!("Failed to lock webviews: {}", e))?
+        .in_profile(&name)
+        .len();
+    if open > 0 {
+        return Err(format!(
+            "Browser profile {} is used by {} open webviews",
+            name, open
+        ));
+    }
+
+    // macOS removes the whole store; elsewhere the data lives in the profile
+    // directory, cleared through the engine first so nothing it caches
+    // survives
+    #[cfg(target_os = "macos")]
+    remove_data_store(&window, &name).await?;
+    #[cfg(not(target_os = "macos"))]
+    browser_profile_clear(window, state, name.clone()).await?;
+    std::fs::remove_dir_all(profiles_dir().join(&name))
+        .map_err(|e| format!("Failed to delete profile {}: {}", name, e))?;
+    info!("Deleted browser profile {}", name);
+    Ok(())
+}
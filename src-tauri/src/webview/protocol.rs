@@ -0,0 +1,71 @@
+use super::console::append_console_batch;
+use super::manager::WebviewState;
+use crate::config::effective_preferences;
+use crate::AppState;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{Manager, UriSchemeContext, Wry};
+
+/// URL a page fetches to reach one of the app's schemes. WebView2 serves
+/// custom schemes from an `http://<scheme>.localhost` host instead.
+pub fn protocol_url(scheme: &str, path: &str) -> String {
+    if cfg!(windows) {
+        format!("http://{}.localhost/{}", scheme, path)
+    } else {
+        format!("{}://localhost/{}", scheme, path)
+    }
+}
+
+/// Empty reply any page may read, so the browser doesn't report a CORS error
+fn reply(status: StatusCode) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Vec::new())
+        .unwrap_or_default()
+}
+
+/// Only POSTs carry data; anything else (a CORS preflight, a link someone
+/// followed) is answered without acting on it
+fn posted(request: &Request<Vec<u8>>) -> Result<&[u8], Response<Vec<u8>>> {
+    match *request.method() {
+        Method::POST => Ok(request.body()),
+        Method::OPTIONS => Err(reply(StatusCode::NO_CONTENT)),
+        _ => Err(reply(StatusCode::METHOD_NOT_ALLOWED)),
+    }
+}
+
+/// `wiz-console://localhost/log`: a batch of console messages from a page
+pub fn console_protocol(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let body = match posted(&request) {
+        Ok(body) => body,
+        Err(response) => return response,
+    };
+    // Pages opened while it was on keep sending after it's turned off
+    let state = ctx.app_handle().state::<AppState>();
+    if effective_preferences(&state).is_ok_and(|prefs| prefs.webview_console_log) {
+        append_console_batch(ctx.webview_label(), body);
+    }
+    reply(StatusCode::NO_CONTENT)
+}
+
+/// `wiz-eval://localhost/result/<id>`: the result of an
+/// `eval_webview_with_result` script
+pub fn eval_protocol(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let body = match posted(&request) {
+        Ok(body) => body,
+        Err(response) => return response,
+    };
+    let Some(id) = request.uri().path().strip_prefix("/result/") else {
+        return reply(StatusCode::NOT_FOUND);
+    };
+    if let Ok(mut evals) = ctx.app_handle().state::<WebviewState>().evals.lock() {
+        evals.deliver(ctx.webview_label(), id, body);
+    }
+    reply(StatusCode::NO_CONTENT)
+}
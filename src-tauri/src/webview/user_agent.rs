@@ -0,0 +1,118 @@
+use super::policy::host_matches;
+use crate::config::effective_preferences;
+use crate::AppState;
+use tracing::{debug, error};
+
+/// `webview_user_agent` value for a Chrome user agent matching the platform
+pub const CHROME_USER_AGENT: &str = "chrome";
+/// `webview_user_agent` value that leaves the engine's own user agent
+/// (Safari on macOS, WebKitGTK on Linux, Edge on Windows). Any other value is
+/// sent as the user agent itself.
+pub const NATIVE_USER_AGENT: &str = "native";
+const CHROME_VERSION: &str = "131.0.0.0";
+
+/// Chrome's user agent on the platform we're running on
+pub fn chrome_user_agent() -> String {
+    #[cfg(target_os = "macos")]
+    let platform = "Macintosh; Intel Mac OS X 10_15_7";
+    #[cfg(target_os = "windows")]
+    let platform = "Windows NT 10.0; Win64; x64";
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let platform = "X11; Linux x86_64";
+    format!(
+        "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{} Safari/537.36",
+        platform, CHROME_VERSION
+    )
+}
+
+/// What's wrong with a user agent setting, if anything. It ends up in an
+/// HTTP header, so control characters aren't allowed.
+pub fn check_user_agent(value: &str) -> Option<String> {
+    if value.trim().is_empty() {
+        Some("cannot be empty".to_string())
+    } else if value.chars().any(char::is_control) {
+        Some("cannot contain control characters".to_string())
+    } else {
+        None
+    }
+}
+
+/// User agent string for a setting, or `None` for the engine's own
+pub fn resolve_user_agent(setting: &str) -> Option<String> {
+    match setting.trim() {
+        CHROME_USER_AGENT => Some(chrome_user_agent()),
+        NATIVE_USER_AGENT => None,
+        custom => Some(custom.to_string()),
+    }
+}
+
+/// User agent setting for a page: the most specific `[browser.user_agents]`
+/// domain matching `host`, else the profile's `user_agent`, else the
+/// `webview_user_agent` preference
+pub fn user_agent_setting(state: &AppState, profile: &str, host: Option<&str>) -> String {
+    let mut setting = effective_preferences(state)
+        .map(|prefs| prefs.webview_user_agent)
+        .unwrap_or_else(|e| {
+            error!("{}", e);
+            CHROME_USER_AGENT.to_string()
+        });
+
+    let Ok(config) = state.config.lock() else {
+        return setting;
+    };
+    let Some(browser) = config.file.as_ref().map(|file| &file.browser) else {
+        return setting;
+    };
+    if let Some(user_agent) = browser
+        .profiles
+        .get(profile)
+        .and_then(|profile| profile.user_agent.as_ref())
+    {
+        setting = user_agent.clone();
+    }
+    if let Some(host) = host {
+        if let Some((_, user_agent)) = browser
+            .user_agents
+            .iter()
+            .filter(|(domain, _)| host_matches(host, domain))
+            .max_by_key(|(domain, _)| domain.len())
+        {
+            setting = user_agent.clone();
+        }
+    }
+    setting
+}
+
+/// Change the user agent of an open webview (`None` restores the engine's
+/// own). Later requests use it; Windows keeps the one it was created with.
+pub fn set_user_agent(webview: &tauri::Webview, user_agent: Option<String>) {
+    #[cfg(target_os = "macos")]
+    let result = webview.with_webview(move |platform| unsafe {
+        let view: &objc2_web_kit::WKWebView = &*platform.inner().cast();
+        let user_agent = user_agent
+            .as_deref()
+            .map(objc2_foundation::NSString::from_str);
+        view.setCustomUserAgent(user_agent.as_deref());
+    });
+    #[cfg(target_os = "linux")]
+    let result = webview.with_webview(move |platform| {
+        use webkit2gtk::{SettingsExt, WebViewExt};
+        if let Some(settings) = platform.inner().settings() {
+            settings.set_user_agent(user_agent.as_deref());
+        }
+    });
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let result: tauri::Result<()> = {
+        debug!(
+            "Not changing user agent of {} to {:?}: unsupported on this platform",
+            webview.label(),
+            user_agent
+        );
+        Ok(())
+    };
+
+    match result {
+        Ok(()) => debug!("Updated user agent of webview {}", webview.label()),
+        Err(e) => error!("Failed to set webview user agent: {}", e),
+    }
+}
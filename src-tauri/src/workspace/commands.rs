@@ -0,0 +1,27 @@
+use super::window::{open_new_window, workspace_for_window};
+use crate::storage::database::WorkspaceRecord;
+use crate::AppState;
+use tauri::{AppHandle, Window};
+
+/// Open another terminal window with its own workspace
+#[tauri::command]
+pub async fn window_new(app: AppHandle) -> Result<String, String> {
+    open_new_window(&app)
+}
+
+/// Workspace shown by the calling window
+#[tauri::command]
+pub async fn window_workspace(window: Window) -> Result<String, String> {
+    Ok(workspace_for_window(window.label()))
+}
+
+/// Every saved workspace with its window geometry
+#[tauri::command]
+pub async fn window_list_workspaces(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<WorkspaceRecord>, String> {
+    state
+        .db
+        .list_workspaces()
+        .map_err(|e| format!("Failed to list workspaces: {}", e))
+}
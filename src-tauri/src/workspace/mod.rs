@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod window;
+
+pub use commands::*;
+pub use window::*;
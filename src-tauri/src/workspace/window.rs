@@ -0,0 +1,345 @@
+use crate::layout::DEFAULT_WORKSPACE;
+use crate::storage::database::WindowGeometry;
+use crate::webview::WebviewState;
+use crate::AppState;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{
+    AppHandle, Emitter, LogicalPosition, LogicalSize, Manager, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, Window, WindowEvent,
+};
+use tracing::{error, info};
+
+/// Window created from tauri.conf.json; its workspace is `DEFAULT_WORKSPACE`
+pub const MAIN_WINDOW: &str = "main";
+/// Additional windows are labelled `workspace-<id>` and use the label as
+/// their workspace name
+const WINDOW_LABEL_PREFIX: &str = "workspace-";
+
+// Match the main window in tauri.conf.json
+const DEFAULT_WIDTH: f64 = 1200.0;
+const DEFAULT_HEIGHT: f64 = 800.0;
+const MIN_WIDTH: f64 = 600.0;
+const MIN_HEIGHT: f64 = 400.0;
+/// New windows open this far down and right of the focused one
+const CASCADE_OFFSET: f64 = 24.0;
+/// Geometry is saved once a window has been still for this long, rather
+/// than on every move or resize event of a drag
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Windows whose geometry changed and when to save it
+#[derive(Debug, Default)]
+pub struct PendingGeometry {
+    due: HashMap<String, Instant>,
+    saver_running: bool,
+}
+
+/// Workspace whose layout a window shows
+pub fn workspace_for_window(label: &str) -> String {
+    if label == MAIN_WINDOW {
+        DEFAULT_WORKSPACE.to_string()
+    } else {
+        label.to_string()
+    }
+}
+
+fn window_for_workspace(workspace: &str) -> String {
+    if workspace == DEFAULT_WORKSPACE {
+        MAIN_WINDOW.to_string()
+    } else {
+        workspace.to_string()
+    }
+}
+
+/// Terminal windows (as opposed to the minimap or other auxiliary windows)
+pub fn is_workspace_window(label: &str) -> bool {
+    label == MAIN_WINDOW || label.starts_with(WINDOW_LABEL_PREFIX)
+}
+
+/// The focused terminal window, falling back to the main window. Menu
+/// actions are sent here rather than to every window.
+pub fn focused_window(app: &AppHandle) -> Option<WebviewWindow> {
+    let windows = app.webview_windows();
+    windows
+        .values()
+        .find(|w| is_workspace_window(w.label()) && w.is_focused().unwrap_or(false))
+        .or_else(|| windows.get(MAIN_WINDOW))
+        .cloned()
+}
+
+/// Open a window for `workspace`, at `geometry` if it is still on screen
+fn build_window(
+    app: &AppHandle,
+    workspace: &str,
+    geometry: Option<WindowGeometry>,
+) -> Result<WebviewWindow, String> {
+    let label = window_for_workspace(workspace);
+    let mut builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App("/".into()))
+        .title("WizTerm")
+        .min_inner_size(MIN_WIDTH, MIN_HEIGHT)
+        .resizable(true);
+
+    #[cfg(target_os = "macos")]
+    {
+        builder = builder
+            .title_bar_style(tauri::TitleBarStyle::Overlay)
+            .hidden_title(true);
+    }
+
+    match geometry {
+        Some(geometry) => {
+            builder = builder.inner_size(geometry.width, geometry.height);
+            if on_screen(app, &geometry) {
+                builder = builder.position(geometry.x, geometry.y);
+            }
+            if geometry.maximized {
+                builder = builder.maximized(true);
+            }
+        }
+        None => {
+            builder = builder.inner_size(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+            let origin = focused_window(app)
+                .as_ref()
+                .and_then(|window| current_geometry(&window.as_ref().window()));
+            if let Some(origin) = origin {
+                builder = builder.position(origin.x + CASCADE_OFFSET, origin.y + CASCADE_OFFSET);
+            }
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))
+}
+
+/// Open another terminal window with a new, empty workspace. Returns the
+/// workspace name.
+pub fn open_new_window(app: &AppHandle) -> Result<String, String> {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let workspace = format!("{}{}", WINDOW_LABEL_PREFIX, &id[..8]);
+    let window = build_window(app, &workspace, None)?;
+
+    // Record the workspace now so the window comes back after a restart even
+    // if it is never moved
+    persist_geometry(&window.as_ref().window());
+    info!("Opened window for workspace {}", workspace);
+    Ok(workspace)
+}
+
+/// Put the main window back where it was and reopen the other windows that
+/// were open when the app quit
+pub fn restore_windows(app: &AppHandle) {
+    let workspaces = match app.state::<AppState>().db.list_workspaces() {
+        Ok(workspaces) => workspaces,
+        Err(e) => {
+            error!("Failed to list workspaces: {}", e);
+            return;
+        }
+    };
+
+    for record in workspaces {
+        if record.workspace == DEFAULT_WORKSPACE {
+            if let (Some(geometry), Some(window)) =
+                (record.geometry, app.get_webview_window(MAIN_WINDOW))
+            {
+                apply_geometry(app, &window.as_ref().window(), &geometry);
+            }
+        } else if is_workspace_window(&record.workspace) {
+            if let Err(e) = build_window(app, &record.workspace, record.geometry) {
+                error!("Failed to restore workspace {}: {}", record.workspace, e);
+            }
+        }
+    }
+}
+
+fn apply_geometry(app: &AppHandle, window: &Window, geometry: &WindowGeometry) {
+    let _ = window.set_size(LogicalSize::new(geometry.width, geometry.height));
+    if on_screen(app, geometry) {
+        let _ = window.set_position(LogicalPosition::new(geometry.x, geometry.y));
+    }
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Whether the window's title bar would land on a connected monitor
+fn on_screen(app: &AppHandle, geometry: &WindowGeometry) -> bool {
+    let (x, y) = (geometry.x + 40.0, geometry.y + 10.0);
+    app.available_monitors()
+        .map(|monitors| {
+            monitors.iter().any(|monitor| {
+                let scale = monitor.scale_factor();
+                let position = monitor.position().to_logical::<f64>(scale);
+                let size = monitor.size().to_logical::<f64>(scale);
+                x >= position.x
+                    && y >= position.y
+                    && x < position.x + size.width
+                    && y < position.y + size.height
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Outer position and inner size in logical pixels
+fn current_geometry(window: &Window) -> Option<WindowGeometry> {
+    let scale = window.scale_factor().ok()?;
+    let position = window.outer_position().ok()?.to_logical::<f64>(scale);
+    let size = window.inner_size().ok()?.to_logical::<f64>(scale);
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().unwrap_or(false),
+    })
+}
+
+fn persist_geometry(window: &Window) {
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let state = window.state::<AppState>();
+    let workspace = workspace_for_window(window.label());
+
+    // A maximized window keeps its restored bounds so un-maximizing after a
+    // restart goes back to the right size
+    let geometry = if window.is_maximized().unwrap_or(false) {
+        let saved = state
+            .db
+            .get_workspace(&workspace)
+            .ok()
+            .flatten()
+            .and_then(|record| record.geometry);
+        match saved {
+            Some(saved) => WindowGeometry {
+                maximized: true,
+                ..saved
+            },
+            None => match current_geometry(window) {
+                Some(geometry) => geometry,
+                None => return,
+            },
+        }
+    } else {
+        match current_geometry(window) {
+            Some(geometry) => geometry,
+            None => return,
+        }
+    };
+
+    if let Err(e) = state.db.save_window_geometry(&workspace, &geometry) {
+        error!("Failed to save window geometry: {}", e);
+    }
+}
+
+/// Save the window's geometry once it stops moving. One thread serves every
+/// window and exits when nothing is pending.
+fn schedule_geometry_save(window: &Window) {
+    let Ok(mut pending) = window.state::<AppState>().pending_geometry.lock() else {
+        return;
+    };
+    pending.due.insert(
+        window.label().to_string(),
+        Instant::now() + GEOMETRY_SAVE_DELAY,
+    );
+    if !pending.saver_running {
+        pending.saver_running = true;
+        let app = window.app_handle().clone();
+        std::thread::spawn(move || run_geometry_saver(app));
+    }
+}
+
+fn run_geometry_saver(app: AppHandle) {
+    let state = app.state::<AppState>();
+    loop {
+        let (due, next) = {
+            let Ok(mut pending) = state.pending_geometry.lock() else {
+                return;
+            };
+            if pending.due.is_empty() {
+                pending.saver_running = false;
+                return;
+            }
+            let now = Instant::now();
+            let due: Vec<String> = pending
+                .due
+                .iter()
+                .filter(|(_, at)| **at <= now)
+                .map(|(label, _)| label.clone())
+                .collect();
+            for label in &due {
+                pending.due.remove(label);
+            }
+            let next = pending.due.values().min().copied();
+            (due, next.map(|at| at.saturating_duration_since(now)))
+        };
+
+        for label in due {
+            if let Some(window) = app.get_window(&label) {
+                persist_geometry(&window);
+            }
+        }
+        if let Some(next) = next {
+            std::thread::sleep(next);
+        }
+    }
+}
+
+/// Save now, dropping any pending save (the window is about to close)
+fn flush_geometry(window: &Window) {
+    if let Ok(mut pending) = window.state::<AppState>().pending_geometry.lock() {
+        pending.due.remove(window.label());
+    }
+    persist_geometry(window);
+}
+
+/// Closing an additional window ends its sessions and forgets its
+/// workspace. The main window keeps its workspace for next launch.
+fn close_workspace(window: &Window) {
+    let label = window.label();
+    let state = window.state::<AppState>();
+
+    let killed = match state.pty_manager.lock() {
+        Ok(mut manager) => manager.kill_window_sessions(label),
+        Err(e) => {
+            error!("Failed to lock PTY manager: {}", e);
+            Vec::new()
+        }
+    };
+    if let Ok(mut manager) = window.state::<WebviewState>().manager.lock() {
+        manager.remove_window(label);
+    }
+
+    let workspace = workspace_for_window(label);
+    if let Err(e) = state.db.delete_workspace(&workspace) {
+        error!("Failed to delete workspace {}: {}", workspace, e);
+    }
+    info!("Closed window {} ({} sessions ended)", label, killed.len());
+}
+
+/// Track geometry of terminal windows and clean up when one closes
+pub fn handle_window_event(window: &Window, event: &WindowEvent) {
+    if !is_workspace_window(window.label()) {
+        return;
+    }
+    match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => schedule_geometry_save(window),
+        WindowEvent::CloseRequested { .. } => {
+            flush_geometry(window);
+            if window.label() != MAIN_WINDOW {
+                close_workspace(window);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Send a menu action to the focused terminal window
+pub fn emit_to_focused(app: &AppHandle, event: &str, payload: &str) {
+    let target = focused_window(app)
+        .map(|window| window.label().to_string())
+        .unwrap_or_else(|| MAIN_WINDOW.to_string());
+    if let Err(e) = app.emit_to(target.as_str(), event, payload) {
+        error!("Failed to emit {}: {}", event, e);
+    }
+}
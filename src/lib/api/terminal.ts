@@ -1,11 +1,50 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import type {
 	TerminalSession,
 	CreateSessionOptions,
 	TerminalOutput,
 	TerminalExit,
-	TerminalPreferences
+	TerminalPreferences,
+	TerminalLayout,
+	RestoredLayout,
+	LayoutHistoryEntry,
+	LayoutSnapshot,
+	ConfigStatus,
+	ColorScheme,
+	ColorSchemeSummary,
+	SchemeVariant,
+	Keymap,
+	ImportMode,
+	SettingsSummary,
+	TerminalSessionRecord,
+	DbStats,
+	AvailableShell,
+	LoginEnvStatus,
+	SessionResourceStats,
+	ProcessNode,
+	ProcessSignal,
+	SignalTarget,
+	DownloadPrompt,
+	DownloadRecord,
+	ClipboardPrompt,
+	ClipboardChange,
+	ClipboardBlocked,
+	ClipboardPromptDismissed,
+	Attachment,
+	WebviewNavigated,
+	WebviewTitleChanged,
+	WebviewLoadState,
+	WebviewNavigationBlocked,
+	WebviewEvalOutcome,
+	BrowserProfile,
+	WorkspaceRecord,
+	MaintenanceReport,
+	SessionHistoryEntry,
+	SessionHistoryFilter,
+	SessionHistoryPage,
+	SessionStats
 } from '$lib/types/terminal';
 
 export async function createSession(options?: CreateSessionOptions): Promise<TerminalSession> {
@@ -16,8 +55,15 @@ export async function writeToSession(sessionId: string, data: Uint8Array): Promi
 	return invoke('pty_write', { sessionId, data: Array.from(data) });
 }
 
-export async function resizeSession(sessionId: string, cols: number, rows: number): Promise<void> {
-	return invoke('pty_resize', { sessionId, cols, rows });
+/** Pixel dimensions are the text area size, for image tools that query TIOCGWINSZ */
+export async function resizeSession(
+	sessionId: string,
+	cols: number,
+	rows: number,
+	pixelWidth?: number,
+	pixelHeight?: number
+): Promise<void> {
+	return invoke('pty_resize', { sessionId, cols, rows, pixelWidth, pixelHeight });
 }
 
 export async function killSession(sessionId: string): Promise<void> {
@@ -32,14 +78,19 @@ export async function getSession(sessionId: string): Promise<TerminalSession | n
 	return invoke('pty_get_session', { sessionId });
 }
 
+// Session events are sent only to the window that owns the session
 export async function onTerminalOutput(
 	callback: (output: TerminalOutput) => void
 ): Promise<UnlistenFn> {
-	return listen<TerminalOutput>('terminal-output', (event) => callback(event.payload));
+	return getCurrentWebviewWindow().listen<TerminalOutput>('terminal-output', (event) =>
+		callback(event.payload)
+	);
 }
 
 export async function onTerminalExit(callback: (exit: TerminalExit) => void): Promise<UnlistenFn> {
-	return listen<TerminalExit>('terminal-exit', (event) => callback(event.payload));
+	return getCurrentWebviewWindow().listen<TerminalExit>('terminal-exit', (event) =>
+		callback(event.payload)
+	);
 }
 
 export async function saveLayout(layoutJson: string): Promise<void> {
@@ -50,6 +101,74 @@ export async function getLayout(): Promise<string | null> {
 	return invoke('pty_get_layout');
 }
 
+/** Saved layout with terminals for dead or unknown sessions removed */
+export async function restoreLayout(): Promise<TerminalLayout | null> {
+	return invoke('layout_restore');
+}
+
+/** Point saved terminal nodes at new session IDs (old ID -> new ID) */
+export async function remapLayoutSessions(mapping: Record<string, string>): Promise<number> {
+	return invoke('layout_remap_sessions', { mapping });
+}
+
+export async function getLayoutSessionIds(): Promise<string[]> {
+	return invoke('layout_session_ids');
+}
+
+/** Add an empty tab after the active one and make it active */
+export async function newTab(title?: string): Promise<TerminalLayout> {
+	return invoke('layout_tab_new', { title });
+}
+
+/** Close a tab, ending the sessions it holds */
+export async function closeTab(tabId: string): Promise<TerminalLayout> {
+	return invoke('layout_tab_close', { tabId });
+}
+
+export async function moveTab(tabId: string, index: number): Promise<TerminalLayout> {
+	return invoke('layout_tab_move', { tabId, index });
+}
+
+/** An empty title goes back to the automatic one */
+export async function renameTab(tabId: string, title: string): Promise<TerminalLayout> {
+	return invoke('layout_tab_rename', { tabId, title });
+}
+
+export async function activateTab(tabId: string): Promise<TerminalLayout> {
+	return invoke('layout_tab_activate', { tabId });
+}
+
+export async function undoLayout(workspace?: string): Promise<RestoredLayout | null> {
+	return invoke('layout_undo', { workspace });
+}
+
+export async function redoLayout(workspace?: string): Promise<RestoredLayout | null> {
+	return invoke('layout_redo', { workspace });
+}
+
+export async function getLayoutHistory(workspace?: string): Promise<LayoutHistoryEntry[]> {
+	return invoke('layout_history', { workspace });
+}
+
+export async function saveLayoutSnapshot(name: string, workspace?: string): Promise<void> {
+	return invoke('layout_snapshot_save', { workspace, name });
+}
+
+export async function listLayoutSnapshots(workspace?: string): Promise<LayoutSnapshot[]> {
+	return invoke('layout_snapshot_list', { workspace });
+}
+
+export async function restoreLayoutSnapshot(
+	name: string,
+	workspace?: string
+): Promise<RestoredLayout> {
+	return invoke('layout_snapshot_restore', { workspace, name });
+}
+
+export async function deleteLayoutSnapshot(name: string, workspace?: string): Promise<boolean> {
+	return invoke('layout_snapshot_delete', { workspace, name });
+}
+
 export async function savePreferences(preferences: TerminalPreferences): Promise<void> {
 	return invoke('pty_save_preferences', { preferences });
 }
@@ -58,13 +177,381 @@ export async function getPreferences(): Promise<TerminalPreferences> {
 	return invoke('pty_get_preferences');
 }
 
+/** Fired in every window when preferences change (settings panel or config.toml) */
+export async function onPreferencesChanged(
+	callback: (preferences: TerminalPreferences) => void
+): Promise<UnlistenFn> {
+	return listen<TerminalPreferences>('preferences-changed', (event) => callback(event.payload));
+}
+
+/** Fired when config.toml fails to parse or validate; the last good config stays active */
+export async function onConfigError(callback: (message: string) => void): Promise<UnlistenFn> {
+	return listen<string>('config-error', (event) => callback(event.payload));
+}
+
+export async function getConfigStatus(): Promise<ConfigStatus> {
+	return invoke('config_get_status');
+}
+
+export async function reloadConfig(): Promise<ConfigStatus> {
+	return invoke('config_reload');
+}
+
+export async function listThemes(): Promise<ColorSchemeSummary[]> {
+	return invoke('theme_list');
+}
+
+export async function getTheme(name: string): Promise<ColorScheme> {
+	return invoke('theme_get', { name });
+}
+
+export async function saveTheme(scheme: ColorScheme): Promise<ColorScheme> {
+	return invoke('theme_save', { scheme });
+}
+
+export async function deleteTheme(name: string): Promise<boolean> {
+	return invoke('theme_delete', { name });
+}
+
+/** Import .itermcolors, Alacritty/WezTerm .toml, Windows Terminal .json or base16 .yaml */
+export async function importTheme(path: string): Promise<ColorScheme[]> {
+	return invoke('theme_import', { path });
+}
+
+/** Activate a scheme; pass 'light' to set the scheme used in light mode */
+export async function setTheme(name: string, variant?: SchemeVariant): Promise<void> {
+	return invoke('theme_set', { name, variant });
+}
+
+export async function getKeybindings(): Promise<Keymap> {
+	return invoke('keybindings_get');
+}
+
+/** Bind an action to a chord; an empty chord unbinds it */
+export async function setKeybinding(action: string, chord: string): Promise<Keymap> {
+	return invoke('keybindings_set', { action, chord });
+}
+
+/** Restore the default chord for one action, or all actions */
+export async function resetKeybindings(action?: string): Promise<Keymap> {
+	return invoke('keybindings_reset', { action });
+}
+
+/** Fired when bindings change (settings, config.toml); menus are already rebuilt */
+export async function onKeybindingsChanged(
+	callback: (keymap: Keymap) => void
+): Promise<UnlistenFn> {
+	return listen<Keymap>('keybindings-changed', (event) => callback(event.payload));
+}
+
+/** Fired in the focused window when a native menu item (or its accelerator) is triggered */
+export async function onKeybindingAction(
+	callback: (action: string) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<string>('keybinding-action', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Open another window with its own workspace; returns the workspace name */
+export async function newWindow(): Promise<string> {
+	return invoke('window_new');
+}
+
+/** Workspace shown by this window */
+export async function getWindowWorkspace(): Promise<string> {
+	return invoke('window_workspace');
+}
+
+export async function listWorkspaces(): Promise<WorkspaceRecord[]> {
+	return invoke('window_list_workspaces');
+}
+
+export async function listSessionHistory(
+	filter?: SessionHistoryFilter,
+	offset?: number,
+	limit?: number
+): Promise<SessionHistoryPage> {
+	return invoke('history_list_sessions', { filter, offset, limit });
+}
+
+export async function getSessionHistory(id: string): Promise<SessionHistoryEntry | null> {
+	return invoke('history_get_session', { id });
+}
+
+export async function getSessionStats(filter?: SessionHistoryFilter): Promise<SessionStats> {
+	return invoke('history_stats', { filter });
+}
+
+/** Start a new session with the command, arguments and directory of a past one */
+export async function relaunchSession(
+	record: TerminalSessionRecord,
+	cols?: number,
+	rows?: number
+): Promise<TerminalSession> {
+	return createSession({
+		command: record.command,
+		args: record.args,
+		cwd: record.cwd ?? undefined,
+		cols,
+		rows
+	});
+}
+
+/** Export settings to a .json or .toml bundle */
+export async function exportSettings(path: string): Promise<SettingsSummary> {
+	return invoke('settings_export', { path });
+}
+
 /**
- * Save image data to a temp file and return the path.
- * Used for pasting/dropping images into the terminal for Claude Code.
- * @param data - Base64 encoded image data or data URL (data:image/png;base64,...)
- * @param filename - Optional original filename to preserve extension
- * @returns The path to the saved temp file
+ * Import a settings bundle. Everything is validated before anything is written;
+ * pass dryRun to preview what would change.
  */
-export async function saveImageToTemp(data: string, filename?: string): Promise<string> {
-	return invoke('save_temp_image', { data, filename });
+export async function importSettings(
+	path: string,
+	mode: ImportMode,
+	dryRun = false
+): Promise<SettingsSummary> {
+	return invoke('settings_import', { path, mode, dryRun });
+}
+
+/** Shells listed in /etc/shells, for the shell picker */
+export async function listAvailableShells(): Promise<AvailableShell[]> {
+	return invoke('pty_list_available_shells');
+}
+
+/** Reject a shell path that doesn't exist, isn't executable or isn't in /etc/shells */
+export async function validateShell(path: string): Promise<void> {
+	return invoke('pty_validate_shell', { path });
+}
+
+/** Re-read the login-shell environment used for new sessions */
+export async function refreshLoginEnv(): Promise<LoginEnvStatus> {
+	return invoke('pty_refresh_login_env');
+}
+
+export async function getLoginEnvStatus(): Promise<LoginEnvStatus | null> {
+	return invoke('pty_get_login_env_status');
+}
+
+/** CPU, memory and threads of this window's sessions (or one session), including child processes */
+export async function getSessionResourceStats(sessionId?: string): Promise<SessionResourceStats[]> {
+	return invoke('pty_session_stats', { sessionId });
+}
+
+/** Every process under a session's shell, rooted at the shell */
+export async function getProcessTree(sessionId: string): Promise<ProcessNode> {
+	return invoke('pty_process_tree', { sessionId });
+}
+
+/** Signal a process or process group in a session; rejected if it isn't part of the session */
+export async function signalProcess(
+	sessionId: string,
+	target: SignalTarget,
+	signal: ProcessSignal
+): Promise<void> {
+	return invoke('pty_signal', { sessionId, target, signal });
+}
+
+/** Periodic resource stats for this window's sessions (see session_stats_interval_ms) */
+export async function onSessionResourceStats(
+	callback: (stats: SessionResourceStats[]) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<SessionResourceStats[]>('session-stats', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired in the session's window when a program sends a file and the policy is 'ask' */
+export async function onDownloadPrompt(
+	callback: (prompt: DownloadPrompt) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<DownloadPrompt>('download-prompt', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired when a file transfer is saved, denied, rejected or fails */
+export async function onDownloadFinished(
+	callback: (download: DownloadRecord) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<DownloadRecord>('download-finished', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Save (accept) or discard a prompted file transfer */
+export async function respondToDownload(id: string, accept: boolean): Promise<void> {
+	return invoke('download_respond', { id, accept });
+}
+
+/** Recent file transfers, newest first */
+export async function listDownloads(limit?: number): Promise<DownloadRecord[]> {
+	return invoke('download_list', { limit });
+}
+
+/** Fired in the session's window when a program uses OSC 52 and the policy is 'ask' */
+export async function onClipboardPrompt(
+	callback: (prompt: ClipboardPrompt) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<ClipboardPrompt>('clipboard-prompt', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired after a program set the clipboard */
+export async function onClipboardChanged(
+	callback: (change: ClipboardChange) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<ClipboardChange>('clipboard-changed', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired when a clipboard request is refused or too large */
+export async function onClipboardBlocked(
+	callback: (blocked: ClipboardBlocked) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<ClipboardBlocked>('clipboard-blocked', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired when a clipboard prompt expires or its session ends */
+export async function onClipboardPromptDismissed(
+	callback: (dismissed: ClipboardPromptDismissed) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<ClipboardPromptDismissed>(
+		'clipboard-prompt-dismissed',
+		(event) => callback(event.payload)
+	);
+}
+
+/** Allow or refuse a prompted clipboard request */
+export async function respondToClipboard(id: string, allow: boolean): Promise<void> {
+	return invoke('clipboard_respond', { id, allow });
+}
+
+/** Database file size and per-table rows/bytes */
+export async function getDbStats(): Promise<DbStats> {
+	return invoke('db_stats');
+}
+
+/** Run retention cleanup, integrity check and VACUUM now */
+export async function compactDatabase(): Promise<MaintenanceReport> {
+	return invoke('db_compact');
+}
+
+/** Listen for a failed integrity check (the database was not vacuumed) */
+export async function onDbIntegrityError(
+	callback: (problems: string[]) => void
+): Promise<UnlistenFn> {
+	return listen<string[]>('db-integrity-error', (event) => callback(event.payload));
+}
+
+/**
+ * Save a pasted file for a session so its path can be typed into the terminal
+ * (e.g. an image for Claude Code). Deleted when the session ends.
+ * @param data - Base64 encoded file data or data URL (data:image/png;base64,...)
+ * @param name - Optional original filename (sanitized; the type is detected from the contents)
+ */
+export async function saveAttachment(
+	sessionId: string,
+	data: string,
+	name?: string
+): Promise<Attachment> {
+	return invoke('attachment_save', { sessionId, data, name });
+}
+
+/** A session's attachments, or all of them, newest first */
+export async function listAttachments(sessionId?: string): Promise<Attachment[]> {
+	return invoke('attachment_list', { sessionId });
+}
+
+/** Delete an attachment before its session ends */
+export async function deleteAttachment(id: string): Promise<boolean> {
+	return invoke('attachment_delete', { id });
+}
+
+/** Fired when a child webview in this window starts navigating */
+export async function onWebviewNavigated(
+	callback: (navigated: WebviewNavigated) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<WebviewNavigated>('webview-navigated', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired when a child webview's document title changes */
+export async function onWebviewTitleChanged(
+	callback: (changed: WebviewTitleChanged) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<WebviewTitleChanged>('webview-title-changed', (event) =>
+		callback(event.payload)
+	);
+}
+
+/** Fired when a page in a child webview starts or finishes loading */
+export async function onWebviewLoadState(
+	callback: (loadState: WebviewLoadState) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<WebviewLoadState>('webview-load-state', (event) =>
+		callback(event.payload)
+	);
+}
+
+export async function onWebviewNavigationBlocked(
+	callback: (blocked: WebviewNavigationBlocked) => void
+): Promise<UnlistenFn> {
+	return getCurrentWebviewWindow().listen<WebviewNavigationBlocked>(
+		'webview-navigation-blocked',
+		(event) => callback(event.payload)
+	);
+}
+
+/**
+ * Run a script in a child webview and wait for its result. The script is the
+ * body of an async function: `return` a JSON-serializable value.
+ * @param timeoutMs - How long to wait (default 5000, at most 60000)
+ */
+export async function evalWebviewWithResult(
+	id: string,
+	script: string,
+	timeoutMs?: number
+): Promise<WebviewEvalOutcome> {
+	return invoke('eval_webview_with_result', { id, script, timeoutMs });
+}
+
+/** Browser profiles: the default one, then named ones alphabetically */
+export async function listBrowserProfiles(): Promise<BrowserProfile[]> {
+	return invoke('browser_profile_list');
+}
+
+/** Create a named browser profile (letters, digits, '_' and '-') */
+export async function createBrowserProfile(name: string): Promise<BrowserProfile> {
+	return invoke('browser_profile_create', { name });
+}
+
+/** Open the developer tools for a child webview (release builds need webview_devtools) */
+export async function openWebviewDevtools(id: string): Promise<void> {
+	return invoke('open_webview_devtools', { id });
+}
+
+export async function closeWebviewDevtools(id: string): Promise<void> {
+	return invoke('close_webview_devtools', { id });
+}
+
+/** File webview console messages are appended to when webview_console_log is on */
+export async function getWebviewConsoleLogPath(): Promise<string> {
+	return invoke('webview_console_log_path');
+}
+
+/** Delete a browser profile's cookies, storage and cache */
+export async function clearBrowserProfile(name: string): Promise<void> {
+	return invoke('browser_profile_clear', { name });
+}
+
+/** Clear and remove a named browser profile that no webview is using */
+export async function deleteBrowserProfile(name: string): Promise<void> {
+	return invoke('browser_profile_delete', { name });
 }
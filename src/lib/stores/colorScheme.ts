@@ -0,0 +1,69 @@
+import { derived, readable } from 'svelte/store';
+import type { ITheme } from '@xterm/xterm';
+import type { ColorScheme, Palette } from '$lib/types/terminal';
+import { getTheme } from '$lib/api/terminal';
+import { settings } from './settings';
+
+// Follows the system appearance, for `color_scheme_light`
+const systemLight = readable(false, (set) => {
+	if (typeof window === 'undefined') return;
+	const query = window.matchMedia('(prefers-color-scheme: light)');
+	set(query.matches);
+	const onChange = (e: MediaQueryListEvent) => set(e.matches);
+	query.addEventListener('change', onChange);
+	return () => query.removeEventListener('change', onChange);
+});
+
+// Name of the scheme in effect: the light one when the system is light and
+// one is set, otherwise the main one
+const activeName = derived([settings, systemLight], ([$settings, $light]) =>
+	$light && $settings.terminal.color_scheme_light
+		? $settings.terminal.color_scheme_light
+		: $settings.terminal.color_scheme
+);
+
+// The active color scheme, or null until it has loaded (or if it can't be)
+export const activeScheme = derived<typeof activeName, ColorScheme | null>(
+	activeName,
+	(name, set) => {
+		let current = true;
+		getTheme(name)
+			.then((scheme) => {
+				if (current) set(scheme);
+			})
+			.catch((e) => console.error(`Failed to load color scheme ${name}:`, e));
+		return () => {
+			current = false;
+		};
+	},
+	null
+);
+
+// xterm.js theme for a palette (`ansi` is 8 normal then 8 bright colors)
+export function xtermTheme(palette: Palette): ITheme {
+	const [black, red, green, yellow, blue, magenta, cyan, white] = palette.ansi;
+	const bright = palette.ansi.slice(8);
+	return {
+		background: palette.background,
+		foreground: palette.foreground,
+		cursor: palette.cursor,
+		cursorAccent: palette.background,
+		selectionBackground: palette.selection,
+		black,
+		red,
+		green,
+		yellow,
+		blue,
+		magenta,
+		cyan,
+		white,
+		brightBlack: bright[0],
+		brightRed: bright[1],
+		brightGreen: bright[2],
+		brightYellow: bright[3],
+		brightBlue: bright[4],
+		brightMagenta: bright[5],
+		brightCyan: bright[6],
+		brightWhite: bright[7]
+	};
+}
@@ -0,0 +1,97 @@
+import { writable, get } from 'svelte/store';
+import type { Keybinding } from '$lib/types/terminal';
+import { getKeybindings } from '$lib/api/terminal';
+
+type ActionHandler = (action: string) => void;
+
+const handlers = new Set<ActionHandler>();
+
+// Key names accepted in chords that differ from KeyboardEvent.key
+const KEY_ALIASES: Record<string, string> = {
+	plus: '+',
+	minus: '-',
+	equal: '=',
+	comma: ',',
+	period: '.',
+	slash: '/',
+	backslash: '\\',
+	bracketleft: '[',
+	bracketright: ']',
+	space: ' ',
+	esc: 'escape',
+	return: 'enter',
+	up: 'arrowup',
+	down: 'arrowdown',
+	left: 'arrowleft',
+	right: 'arrowright'
+};
+
+function chordMatches(chord: string, e: KeyboardEvent): boolean {
+	const parts = chord.split('+').map((p) => p.trim().toLowerCase());
+	// A trailing '+' key splits into two empty parts
+	const key = parts[parts.length - 1] === '' ? '+' : parts[parts.length - 1];
+	const modifiers = new Set(parts.slice(0, parts[parts.length - 1] === '' ? -2 : -1));
+
+	const cmdOrCtrl = modifiers.has('cmdorctrl') || modifiers.has('commandorcontrol');
+	const wantMeta =
+		modifiers.has('cmd') ||
+		modifiers.has('command') ||
+		modifiers.has('super') ||
+		modifiers.has('meta');
+	const wantCtrl = modifiers.has('ctrl') || modifiers.has('control');
+	const wantAlt = modifiers.has('alt') || modifiers.has('option');
+	const wantShift = modifiers.has('shift');
+
+	if (cmdOrCtrl) {
+		if (!(e.metaKey || e.ctrlKey)) return false;
+	} else if (e.metaKey !== wantMeta || e.ctrlKey !== wantCtrl) {
+		return false;
+	}
+	if (e.altKey !== wantAlt || e.shiftKey !== wantShift) return false;
+
+	return e.key.toLowerCase() === (KEY_ALIASES[key] ?? key);
+}
+
+// Backend-owned keybindings. Menu-backed actions arrive as `keybinding-action`
+// events; the rest are matched here against keydown events.
+function createKeybindingsStore() {
+	const { subscribe, set } = writable<Keybinding[]>([]);
+
+	return {
+		subscribe,
+
+		async load() {
+			try {
+				const keymap = await getKeybindings();
+				set(keymap.bindings);
+			} catch (e) {
+				console.error('Failed to load keybindings:', e);
+			}
+		},
+
+		apply(bindings: Keybinding[]) {
+			set(bindings);
+		},
+
+		// Action bound to this key event, if the frontend is responsible for it
+		match(e: KeyboardEvent): string | null {
+			for (const binding of get({ subscribe })) {
+				if (binding.menu || binding.conflict || !binding.chord) continue;
+				if (chordMatches(binding.chord, e)) return binding.action;
+			}
+			return null;
+		},
+
+		// Subscribe to dispatched actions; returns an unsubscribe function
+		onAction(handler: ActionHandler): () => void {
+			handlers.add(handler);
+			return () => handlers.delete(handler);
+		},
+
+		dispatch(action: string) {
+			handlers.forEach((handler) => handler(action));
+		}
+	};
+}
+
+export const keybindings = createKeybindingsStore();
@@ -1,9 +1,10 @@
 import { writable, get } from 'svelte/store';
 import type { TerminalPreferences, DEFAULT_TERMINAL_PREFERENCES } from '$lib/types/terminal';
-import { getPreferences, savePreferences } from '$lib/api/terminal';
+import { getConfigStatus, getPreferences, savePreferences } from '$lib/api/terminal';
 
 interface Settings {
-	terminal: TerminalPreferences;
+	terminal: TerminalPreferences; // effective: config.toml merged over SQLite
+	overrides: (keyof TerminalPreferences)[]; // fields set in config.toml (read-only)
 }
 
 const defaultSettings: Settings = {
@@ -14,13 +15,39 @@ const defaultSettings: Settings = {
 		cursor_blink: true,
 		minimap_refresh_ms: 200,
 		use_webgl: true,
-		shell_path: '/bin/zsh'
-	}
+		shell_path: '',
+		color_scheme: 'wiz-term Dark',
+		color_scheme_light: null,
+		session_retention_days: 7,
+		temp_file_retention_days: 1,
+		maintenance_interval_hours: 24,
+		session_stats_interval_ms: 0,
+		downloads_dir: '',
+		download_max_mb: 1024,
+		download_policy: 'ask',
+		clipboard_write: 'allow',
+		clipboard_read: 'deny',
+		clipboard_max_kb: 1024,
+		attachment_max_mb: 100,
+		webview_user_agent: 'chrome',
+		webview_devtools: false,
+		webview_console_log: false
+	},
+	overrides: []
 };
 
 function createSettingsStore() {
 	const { subscribe, set, update } = writable<Settings>(defaultSettings);
 
+	async function loadOverrides() {
+		try {
+			const status = await getConfigStatus();
+			update((s) => ({ ...s, overrides: status.overrides }));
+		} catch (e) {
+			console.error('Failed to load config status:', e);
+		}
+	}
+
 	return {
 		subscribe,
 
@@ -31,10 +58,23 @@ function createSettingsStore() {
 			} catch (e) {
 				console.error('Failed to load preferences:', e);
 			}
+			await loadOverrides();
 		},
 
+		// Apply preferences broadcast by the backend (no save). config.toml
+		// changes arrive this way too, so its overrides are re-read.
+		apply(prefs: TerminalPreferences) {
+			update((s) => ({ ...s, terminal: prefs }));
+			loadOverrides();
+		},
+
+		// Fields config.toml overrides keep their saved value in SQLite (the
+		// backend drops them)
 		async updateTerminal(prefs: Partial<TerminalPreferences>) {
 			const current = get({ subscribe });
+			for (const field of current.overrides) {
+				delete prefs[field];
+			}
 			const newPrefs = { ...current.terminal, ...prefs };
 			update((s) => ({ ...s, terminal: newPrefs }));
 			try {
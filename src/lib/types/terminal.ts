@@ -4,8 +4,12 @@ export interface TerminalSession {
 	args: string[];
 	cwd: string | null;
 	created_at: string;
+	window: string; // Label of the window that owns the session
 	cols: number;
 	rows: number;
+	pixel_width: number; // Text area size reported via TIOCGWINSZ (0 = unknown)
+	pixel_height: number;
+	profile: string | null; // config.toml profile the session was started with
 	is_alive: boolean;
 }
 
@@ -15,6 +19,9 @@ export interface CreateSessionOptions {
 	cwd?: string;
 	cols?: number;
 	rows?: number;
+	pixel_width?: number;
+	pixel_height?: number;
+	profile?: string; // config.toml profile: fills unset command/args/cwd, adds its env
 }
 
 export interface TerminalOutput {
@@ -43,6 +50,8 @@ export interface WebviewNode {
 	id: string;
 	url: string;
 	title?: string;
+	profile?: string; // Browser profile for cookies and storage; unset uses the default one
+	private?: boolean; // Throwaway storage, gone when the pane closes
 }
 
 export interface SplitNode {
@@ -55,11 +64,286 @@ export interface SplitNode {
 
 export type LayoutNode = TerminalNode | WebviewNode | SplitNode;
 
-export interface TerminalLayout {
+// A tab holding its own split tree
+export interface LayoutTab {
+	id: string;
+	title?: string; // User-given; unset shows the focused pane's title
 	root: LayoutNode | null;
+}
+
+export interface TerminalLayout {
+	tabs: LayoutTab[]; // Display order
+	activeTab: string | null;
 	version: number;
 }
 
+// Past session record (from the terminal_sessions table)
+export interface TerminalSessionRecord {
+	id: string;
+	command: string;
+	args: string[];
+	cwd: string | null;
+	created_at: number;
+	ended_at: number | null;
+	exit_code: number | null;
+}
+
+// Session history browser
+export interface SessionHistoryEntry extends TerminalSessionRecord {
+	is_alive: boolean;
+	duration_secs: number | null;
+}
+
+export interface SessionHistoryFilter {
+	command?: string; // Case-insensitive substring
+	cwd_prefix?: string; // "~" is expanded
+	exit_code?: number;
+	since?: number; // Unix seconds, inclusive
+	until?: number; // Unix seconds, exclusive
+}
+
+export interface SessionHistoryPage {
+	sessions: SessionHistoryEntry[];
+	total: number;
+	offset: number;
+	limit: number;
+}
+
+export interface SessionStats {
+	total_sessions: number;
+	average_lifetime_secs: number | null;
+	sessions_per_day: { day: string; count: number }[];
+	top_directories: { value: string; count: number }[];
+	top_commands: { value: string; count: number }[];
+}
+
+// Shell listed in /etc/shells
+export interface AvailableShell {
+	path: string;
+	name: string;
+	is_login_shell: boolean;
+}
+
+// Cached login-shell environment applied to spawned commands
+export interface LoginEnvStatus {
+	shell: string;
+	variables: number;
+	captured_at: string; // RFC 3339
+	duration_ms: number;
+}
+
+// Resource usage of one process in a session's tree
+export interface ProcessStats {
+	pid: number;
+	name: string;
+	cpu_percent: number; // Percent of one core
+	memory_bytes: number; // Resident set size
+}
+
+// Resource usage of a session's shell and all of its descendants
+export interface SessionResourceStats {
+	session_id: string;
+	pid: number | null; // null once the shell has exited
+	cpu_percent: number;
+	memory_bytes: number;
+	threads: number | null; // Only reported on Linux
+	process_count: number;
+	top_processes: ProcessStats[]; // Busiest first
+}
+
+// A process under a session's shell (pty_process_tree)
+export interface ProcessNode {
+	pid: number;
+	ppid: number | null;
+	pgid: number | null; // Process group; Unix only
+	name: string;
+	command: string[];
+	state: string; // Runnable, Sleeping, Stopped, ...
+	started_at: string | null; // RFC 3339
+	children: ProcessNode[];
+}
+
+export type ProcessSignal = 'INT' | 'TERM' | 'KILL' | 'STOP' | 'CONT';
+
+// One process, or every process in a group led by a process in the session
+export type SignalTarget = { pid: number } | { group: number };
+
+// What to do with files programs send via OSC 1337 (iTerm2 File=, inline=0)
+export type DownloadPolicy = 'ask' | 'accept' | 'deny';
+
+// A file transfer waiting for the user to save or discard it
+export interface DownloadPrompt {
+	id: string;
+	session_id: string;
+	file_name: string;
+	size_bytes: number | null; // Size announced by the sender
+}
+
+export type DownloadStatus = 'pending' | 'completed' | 'denied' | 'too_large' | 'failed';
+
+// A file transfer and its outcome (download-finished event and download_list)
+export interface DownloadRecord {
+	id: string;
+	session_id: string;
+	file_name: string;
+	path: string | null; // Saved file once completed
+	size_bytes: number;
+	status: DownloadStatus;
+	error: string | null;
+	created_at: number;
+	completed_at: number | null;
+}
+
+// What to do when a program sets or reads the clipboard with OSC 52
+export type ClipboardPolicy = 'allow' | 'ask' | 'deny';
+
+export type ClipboardAccess = 'read' | 'write';
+
+// A clipboard request waiting for the user to allow or refuse it
+export interface ClipboardPrompt {
+	id: string;
+	session_id: string;
+	access: ClipboardAccess;
+	size_bytes: number | null; // Text to copy, for writes
+	preview: string | null;
+}
+
+// A program set the clipboard
+export interface ClipboardChange {
+	session_id: string;
+	size_bytes: number;
+}
+
+// A clipboard request refused by policy, over the size limit, or sent while
+// too many prompts were waiting
+export interface ClipboardBlocked {
+	session_id: string;
+	access: ClipboardAccess;
+	reason: 'denied' | 'too_large' | 'too_many';
+}
+
+// A clipboard prompt that expired or whose session ended
+export interface ClipboardPromptDismissed {
+	id: string;
+	session_id: string;
+}
+
+// A file pasted into a session, deleted when the session ends
+export interface Attachment {
+	id: string;
+	session_id: string;
+	file_name: string;
+	mime_type: string; // Detected from the contents
+	path: string;
+	size_bytes: number;
+	created_at: number;
+}
+
+// A child webview started a top-level navigation
+export interface WebviewNavigated {
+	id: string; // Webview ID, not the layout node ID
+	url: string;
+}
+
+// A child webview's document title changed
+export interface WebviewTitleChanged {
+	id: string;
+	title: string;
+}
+
+// A page in a child webview started or finished loading
+export interface WebviewLoadState {
+	id: string;
+	url: string; // After redirects, once finished
+	state: 'started' | 'finished';
+}
+
+// A child webview was kept from loading a URL by the navigation policy
+export interface WebviewNavigationBlocked {
+	id: string;
+	url: string;
+	reason: 'scheme' | 'localhost' | 'file' | 'blocked_domain' | 'not_allowed_domain';
+	message: string;
+}
+
+// A browser profile: webviews on it share cookies and storage
+export interface BrowserProfile {
+	name: string; // 'default' for the shared default store
+	open_webviews: number;
+}
+
+// Result of eval_webview_with_result
+export type WebviewEvalOutcome =
+	| { status: 'ok'; value: unknown }
+	| {
+			status: 'error';
+			kind: 'exception' | 'not_serializable' | 'too_large' | 'timeout' | 'closed' | 'invalid_reply';
+			message: string;
+	  };
+
+// Database size information
+export interface DbStats {
+	path: string;
+	file_bytes: number;
+	wal_bytes: number;
+	page_size: number;
+	page_count: number;
+	freelist_count: number; // Unused pages VACUUM would reclaim
+	schema_version: number;
+	last_maintenance: number | null; // Unix seconds
+	tables: { name: string; rows: number; bytes: number | null }[];
+}
+
+// Result of retention cleanup + integrity check + VACUUM
+export interface MaintenanceReport {
+	sessions_deleted: number;
+	temp_files_deleted: number;
+	integrity: string[]; // ['ok'] when healthy
+	vacuumed: boolean;
+	bytes_before: number;
+	bytes_after: number;
+	duration_ms: number;
+}
+
+// Layout brought back by undo/redo/snapshot restore
+export interface RestoredLayout {
+	layout: TerminalLayout;
+	respawn: TerminalSessionRecord[]; // Sessions that are no longer running
+}
+
+export interface LayoutHistoryEntry {
+	id: number;
+	workspace: string;
+	layout_json: string;
+	killed_sessions: TerminalSessionRecord[];
+	undone: boolean;
+	created_at: number;
+}
+
+// Window bounds in logical pixels
+export interface WindowGeometry {
+	x: number;
+	y: number;
+	width: number;
+	height: number;
+	maximized: boolean;
+}
+
+// Each window shows one workspace ('default' for the main window)
+export interface WorkspaceRecord {
+	workspace: string;
+	geometry: WindowGeometry | null;
+	created_at: number;
+	updated_at: number;
+}
+
+export interface LayoutSnapshot {
+	workspace: string;
+	name: string;
+	layout_json: string;
+	created_at: number;
+}
+
 export interface DraggedTerminal {
 	sessionId: string;
 	sourceNodeId: string;
@@ -73,7 +357,23 @@ export interface TerminalPreferences {
 	cursor_blink: boolean;
 	minimap_refresh_ms: number;
 	use_webgl: boolean; // false = canvas renderer (may look sharper on some displays)
-	shell_path: string; // Path to shell executable
+	shell_path: string; // Path to shell executable ('' = login shell)
+	color_scheme: string; // Active color scheme (dark mode, or always if no light scheme)
+	color_scheme_light: string | null; // Scheme used when the system appearance is light
+	session_retention_days: number; // Delete ended sessions after this many days (0 = keep)
+	temp_file_retention_days: number; // Delete attachments after this even if their session runs (0 = keep)
+	maintenance_interval_hours: number; // Integrity check + VACUUM interval (0 = off)
+	session_stats_interval_ms: number; // session-stats event interval (0 = off, min 250)
+	downloads_dir: string; // OSC 1337 file transfers are saved here ('' = Downloads folder)
+	download_max_mb: number; // Largest file transfer accepted (0 = no limit)
+	download_policy: DownloadPolicy;
+	clipboard_write: ClipboardPolicy; // Programs setting the clipboard via OSC 52
+	clipboard_read: ClipboardPolicy; // Programs reading the clipboard via OSC 52
+	clipboard_max_kb: number; // Largest clipboard text copied or sent back (0 = no limit)
+	attachment_max_mb: number; // Largest file pasted into a terminal (0 = no limit)
+	webview_user_agent: string; // 'chrome', 'native' (the engine's own) or a literal user agent
+	webview_devtools: boolean; // Allow webview developer tools in release builds
+	webview_console_log: boolean; // Append webview console messages to the console log
 }
 
 export const DEFAULT_TERMINAL_PREFERENCES: TerminalPreferences = {
@@ -83,9 +383,91 @@ export const DEFAULT_TERMINAL_PREFERENCES: TerminalPreferences = {
 	cursor_blink: true,
 	minimap_refresh_ms: 200,
 	use_webgl: true, // WebGL is faster but canvas may look sharper
-	shell_path: '/bin/zsh' // Default shell on macOS
+	shell_path: '', // Login shell from the passwd database
+	color_scheme: 'wiz-term Dark',
+	color_scheme_light: null,
+	session_retention_days: 7,
+	temp_file_retention_days: 1,
+	maintenance_interval_hours: 24,
+	session_stats_interval_ms: 0,
+	downloads_dir: '',
+	download_max_mb: 1024,
+	download_policy: 'ask',
+	clipboard_write: 'allow',
+	clipboard_read: 'deny',
+	clipboard_max_kb: 1024,
+	attachment_max_mb: 100,
+	webview_user_agent: 'chrome',
+	webview_devtools: false,
+	webview_console_log: false
 };
 
+// config.toml status (values set there override the preferences above)
+export interface ConfigStatus {
+	path: string;
+	exists: boolean;
+	error: string | null;
+	warnings: string[]; // Paths missing on this machine; the file is still used
+	overrides: (keyof TerminalPreferences)[];
+}
+
+// Color schemes (built-in, custom or imported from other terminals)
+export type SchemeVariant = 'light' | 'dark';
+
+export interface Palette {
+	foreground: string;
+	background: string;
+	cursor: string;
+	selection: string;
+	ansi: string[]; // 8 normal colors followed by 8 bright colors
+}
+
+export interface ColorScheme {
+	name: string;
+	variant: SchemeVariant;
+	palette: Palette;
+	source: string | null; // Import format, e.g. 'iterm2'
+	builtin: boolean;
+}
+
+export type ColorSchemeSummary = Omit<ColorScheme, 'palette'>;
+
+// Keybindings (resolved by the backend from defaults, SQLite and config.toml)
+export interface Keybinding {
+	action: string;
+	label: string;
+	chord: string | null; // e.g. 'CmdOrCtrl+Shift+D'; null when unbound
+	default_chord: string | null;
+	source: 'default' | 'user' | 'config';
+	menu: boolean; // Handled by a native menu accelerator
+	conflict: boolean; // Clashes with another binding and is inactive
+}
+
+export interface KeybindingConflict {
+	chord: string;
+	actions: string[];
+	reserved_by: string | null;
+}
+
+export interface Keymap {
+	bindings: Keybinding[];
+	conflicts: KeybindingConflict[];
+}
+
+// Settings bundle export/import
+export type ImportMode = 'merge' | 'replace';
+
+export interface SettingsSummary {
+	path: string;
+	preferences: boolean;
+	themes: number;
+	keybindings: number;
+	workspaces: number; // Layout snapshots
+	config: boolean; // config.toml (including profiles)
+	config_file: string | null; // Where an imported config was written
+	warnings: string[];
+}
+
 // Common monospace fonts for terminal
 export const TERMINAL_FONTS = [
 	'JetBrains Mono',
@@ -3,11 +3,15 @@ import type {
 	TerminalNode,
 	WebviewNode,
 	SplitNode,
+	LayoutTab,
 	TerminalLayout,
 	SplitDirection,
 	DropZone
 } from '$lib/types/terminal';
 
+// Matches LAYOUT_VERSION in src-tauri/src/layout/model.rs
+export const LAYOUT_VERSION = 2;
+
 // Generate unique IDs
 export function generateId(): string {
 	return crypto.randomUUID().slice(0, 8);
@@ -32,19 +36,30 @@ export function createWebviewNode(url: string, title?: string): WebviewNode {
 	};
 }
 
-// Create empty layout
+// Create an empty, untitled tab
+export function createTab(title?: string): LayoutTab {
+	return {
+		id: generateId(),
+		title,
+		root: null
+	};
+}
+
+// Create empty layout with one empty tab
 export function createEmptyLayout(): TerminalLayout {
+	const tab = createTab();
 	return {
-		root: null,
-		version: 1
+		tabs: [tab],
+		activeTab: tab.id,
+		version: LAYOUT_VERSION
 	};
 }
 
-// Create layout with a single terminal
-export function createLayoutWithTerminal(sessionId: string): TerminalLayout {
+// Create tab with a single terminal
+export function createTabWithTerminal(tab: LayoutTab, sessionId: string): LayoutTab {
 	return {
-		root: createTerminalNode(sessionId),
-		version: 1
+		...tab,
+		root: createTerminalNode(sessionId)
 	};
 }
 
@@ -60,17 +75,17 @@ function cloneNode(node: LayoutNode): LayoutNode {
 	};
 }
 
-// Deep clone a layout
-export function cloneLayout(layout: TerminalLayout): TerminalLayout {
+// Deep clone a tab
+export function cloneTab(tab: LayoutTab): LayoutTab {
 	return {
-		root: layout.root ? cloneNode(layout.root) : null,
-		version: layout.version
+		...tab,
+		root: tab.root ? cloneNode(tab.root) : null
 	};
 }
 
 // Find a node by its ID
-export function findNodeById(layout: TerminalLayout, nodeId: string): LayoutNode | null {
-	if (!layout.root) return null;
+export function findNodeById(tab: LayoutTab, nodeId: string): LayoutNode | null {
+	if (!tab.root) return null;
 
 	function search(node: LayoutNode): LayoutNode | null {
 		if (node.id === nodeId) return node;
@@ -83,15 +98,15 @@ export function findNodeById(layout: TerminalLayout, nodeId: string): LayoutNode
 		return null;
 	}
 
-	return search(layout.root);
+	return search(tab.root);
 }
 
 // Find a node by session ID
 export function findNodeBySessionId(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	sessionId: string
 ): TerminalNode | null {
-	if (!layout.root) return null;
+	if (!tab.root) return null;
 
 	function search(node: LayoutNode): TerminalNode | null {
 		if (node.type === 'terminal' && node.sessionId === sessionId) {
@@ -106,15 +121,15 @@ export function findNodeBySessionId(
 		return null;
 	}
 
-	return search(layout.root);
+	return search(tab.root);
 }
 
 // Find parent of a node
 export function findParent(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	nodeId: string
 ): { parent: SplitNode; index: number } | null {
-	if (!layout.root) return null;
+	if (!tab.root) return null;
 
 	function search(node: LayoutNode): { parent: SplitNode; index: number } | null {
 		if (node.type === 'split') {
@@ -129,11 +144,11 @@ export function findParent(
 		return null;
 	}
 
-	return search(layout.root);
+	return search(tab.root);
 }
 
-// Get all session IDs in the layout (terminal nodes only)
-export function getAllSessionIds(layout: TerminalLayout): string[] {
+// Get all session IDs in the tab (terminal nodes only)
+export function getAllSessionIds(tab: LayoutTab): string[] {
 	const sessionIds: string[] = [];
 
 	function collect(node: LayoutNode) {
@@ -144,15 +159,15 @@ export function getAllSessionIds(layout: TerminalLayout): string[] {
 		}
 	}
 
-	if (layout.root) {
-		collect(layout.root);
+	if (tab.root) {
+		collect(tab.root);
 	}
 
 	return sessionIds;
 }
 
-// Get all webview nodes in the layout
-export function getAllWebviews(layout: TerminalLayout): WebviewNode[] {
+// Get all webview nodes in the tab
+export function getAllWebviews(tab: LayoutTab): WebviewNode[] {
 	const webviews: WebviewNode[] = [];
 
 	function collect(node: LayoutNode) {
@@ -163,16 +178,35 @@ export function getAllWebviews(layout: TerminalLayout): WebviewNode[] {
 		}
 	}
 
-	if (layout.root) {
-		collect(layout.root);
+	if (tab.root) {
+		collect(tab.root);
 	}
 
 	return webviews;
 }
 
+// Get all pane (terminal and webview) node IDs in layout order
+export function getAllPaneIds(tab: LayoutTab): string[] {
+	const paneIds: string[] = [];
+
+	function collect(node: LayoutNode) {
+		if (node.type === 'split') {
+			node.children.forEach(collect);
+		} else {
+			paneIds.push(node.id);
+		}
+	}
+
+	if (tab.root) {
+		collect(tab.root);
+	}
+
+	return paneIds;
+}
+
 // Find webview by URL
-export function findWebviewByUrl(layout: TerminalLayout, url: string): WebviewNode | null {
-	if (!layout.root) return null;
+export function findWebviewByUrl(tab: LayoutTab, url: string): WebviewNode | null {
+	if (!tab.root) return null;
 
 	function search(node: LayoutNode): WebviewNode | null {
 		if (node.type === 'webview' && node.url === url) {
@@ -187,25 +221,27 @@ export function findWebviewByUrl(layout: TerminalLayout, url: string): WebviewNo
 		return null;
 	}
 
-	return search(layout.root);
+	return search(tab.root);
 }
 
-// Update webview URL and/or title
+// Update webview URL, title and/or browser profile (a null profile means the default one)
 export function updateWebview(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	nodeId: string,
-	updates: { url?: string; title?: string }
-): TerminalLayout {
-	if (!layout.root) return layout;
+	updates: { url?: string; title?: string; profile?: string | null; private?: boolean }
+): LayoutTab {
+	if (!tab.root) return tab;
 
-	const newLayout = cloneLayout(layout);
+	const cloned = cloneTab(tab);
 
 	function update(node: LayoutNode): LayoutNode {
 		if (node.type === 'webview' && node.id === nodeId) {
 			return {
 				...node,
 				url: updates.url ?? node.url,
-				title: updates.title ?? node.title
+				title: updates.title ?? node.title,
+				profile: updates.profile === undefined ? node.profile : (updates.profile ?? undefined),
+				private: updates.private ?? node.private
 			};
 		}
 		if (node.type === 'split') {
@@ -218,8 +254,8 @@ export function updateWebview(
 	}
 
 	return {
-		root: update(newLayout.root!) as LayoutNode,
-		version: layout.version
+		...tab,
+		root: update(cloned.root!) as LayoutNode
 	};
 }
 
@@ -233,31 +269,31 @@ export function isNewNodeFirst(zone: DropZone): boolean {
 	return zone === 'left' || zone === 'top';
 }
 
-// Add a terminal to the layout at the root level (flat horizontal layout)
-export function addTerminal(layout: TerminalLayout, sessionId: string): TerminalLayout {
+// Add a terminal to the tab at the root level (flat horizontal layout)
+export function addTerminal(tab: LayoutTab, sessionId: string): LayoutTab {
 	const newNode = createTerminalNode(sessionId);
 
-	if (!layout.root) {
+	if (!tab.root) {
 		return {
-			root: newNode,
-			version: layout.version
+			...tab,
+			root: newNode
 		};
 	}
 
 	// If root is already a horizontal split, add to it
-	if (layout.root.type === 'split' && layout.root.direction === 'horizontal') {
-		const existingRoot = layout.root;
+	if (tab.root.type === 'split' && tab.root.direction === 'horizontal') {
+		const existingRoot = tab.root;
 		const numChildren = existingRoot.children.length;
 		const newSize = 100 / (numChildren + 1);
 		const scaleFactor = numChildren / (numChildren + 1);
 
 		return {
+			...tab,
 			root: {
 				...existingRoot,
 				children: [newNode, ...existingRoot.children.map(cloneNode)],
 				sizes: [newSize, ...existingRoot.sizes.map((s) => s * scaleFactor)]
-			},
-			version: layout.version
+			}
 		};
 	}
 
@@ -266,51 +302,51 @@ export function addTerminal(layout: TerminalLayout, sessionId: string): Terminal
 		type: 'split',
 		id: generateId(),
 		direction: 'horizontal',
-		children: [newNode, cloneNode(layout.root)],
+		children: [newNode, cloneNode(tab.root)],
 		sizes: [50, 50]
 	};
 
 	return {
-		root: newRoot,
-		version: layout.version
+		...tab,
+		root: newRoot
 	};
 }
 
 // Add a webview to the right of a target node (or at root level if no target)
 export function addWebview(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	url: string,
 	title?: string,
 	targetNodeId?: string
-): TerminalLayout {
+): LayoutTab {
 	const newNode = createWebviewNode(url, title);
 
-	if (!layout.root) {
+	if (!tab.root) {
 		return {
-			root: newNode,
-			version: layout.version
+			...tab,
+			root: newNode
 		};
 	}
 
 	// If target specified, insert after that column
 	if (targetNodeId) {
-		return insertNodeAfter(layout, targetNodeId, newNode);
+		return insertNodeAfter(tab, targetNodeId, newNode);
 	}
 
 	// Otherwise add to root level
-	if (layout.root.type === 'split' && layout.root.direction === 'horizontal') {
-		const existingRoot = layout.root;
+	if (tab.root.type === 'split' && tab.root.direction === 'horizontal') {
+		const existingRoot = tab.root;
 		const numChildren = existingRoot.children.length;
 		const newSize = 100 / (numChildren + 1);
 		const scaleFactor = numChildren / (numChildren + 1);
 
 		return {
+			...tab,
 			root: {
 				...existingRoot,
 				children: [...existingRoot.children.map(cloneNode), newNode],
 				sizes: [...existingRoot.sizes.map((s) => s * scaleFactor), newSize]
-			},
-			version: layout.version
+			}
 		};
 	}
 
@@ -319,43 +355,43 @@ export function addWebview(
 		type: 'split',
 		id: generateId(),
 		direction: 'horizontal',
-		children: [cloneNode(layout.root), newNode],
+		children: [cloneNode(tab.root), newNode],
 		sizes: [50, 50]
 	};
 
 	return {
-		root: newRoot,
-		version: layout.version
+		...tab,
+		root: newRoot
 	};
 }
 
 // Generic function to insert a node after another node's column
 function insertNodeAfter(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	targetNodeId: string,
 	newNode: LayoutNode
-): TerminalLayout {
-	if (!layout.root) {
-		return { root: newNode, version: layout.version };
+): LayoutTab {
+	if (!tab.root) {
+		return { ...tab, root: newNode };
 	}
 
 	// If root is a single node (terminal or webview)
-	if (layout.root.type === 'terminal' || layout.root.type === 'webview') {
-		if (layout.root.id === targetNodeId) {
+	if (tab.root.type === 'terminal' || tab.root.type === 'webview') {
+		if (tab.root.id === targetNodeId) {
 			const newRoot: SplitNode = {
 				type: 'split',
 				id: generateId(),
 				direction: 'horizontal',
-				children: [cloneNode(layout.root), newNode],
+				children: [cloneNode(tab.root), newNode],
 				sizes: [50, 50]
 			};
-			return { root: newRoot, version: layout.version };
+			return { ...tab, root: newRoot };
 		}
-		return layout;
+		return tab;
 	}
 
 	// Root is a split - find which column contains the target
-	const rootSplit = layout.root; // Type narrowed to SplitNode
+	const rootSplit = tab.root; // Type narrowed to SplitNode
 	if (rootSplit.direction === 'horizontal') {
 		let targetColumnIndex = -1;
 		for (let i = 0; i < rootSplit.children.length; i++) {
@@ -386,12 +422,12 @@ function insertNodeAfter(
 			const equalSize = 100 / numChildren;
 
 			return {
+				...tab,
 				root: {
 					...rootSplit,
 					children: newChildren.map(cloneNode),
 					sizes: newChildren.map(() => equalSize)
-				},
-				version: layout.version
+				}
 			};
 		}
 	}
@@ -401,55 +437,55 @@ function insertNodeAfter(
 		type: 'split',
 		id: generateId(),
 		direction: 'horizontal',
-		children: [cloneNode(layout.root), newNode],
+		children: [cloneNode(tab.root), newNode],
 		sizes: [50, 50]
 	};
-	return { root: newRoot, version: layout.version };
+	return { ...tab, root: newRoot };
 }
 
 // Insert a new terminal column after the column containing the target node
 // Used for Cmd+D horizontal splits to maintain independent column widths
 export function insertTerminalAfter(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	targetNodeId: string,
 	newSessionId: string
-): TerminalLayout {
-	if (!layout.root) {
-		return createLayoutWithTerminal(newSessionId);
+): LayoutTab {
+	if (!tab.root) {
+		return createTabWithTerminal(tab, newSessionId);
 	}
 
 	const newNode = createTerminalNode(newSessionId);
 
 	// If root is a single terminal
-	if (layout.root.type === 'terminal') {
-		if (layout.root.id === targetNodeId) {
+	if (tab.root.type === 'terminal') {
+		if (tab.root.id === targetNodeId) {
 			// Create horizontal split with new terminal after the original
 			const newRoot: SplitNode = {
 				type: 'split',
 				id: generateId(),
 				direction: 'horizontal',
-				children: [cloneNode(layout.root), newNode],
+				children: [cloneNode(tab.root), newNode],
 				sizes: [50, 50]
 			};
-			return { root: newRoot, version: layout.version };
+			return { ...tab, root: newRoot };
 		}
-		return layout;
+		return tab;
 	}
 
 	// Root must be a split at this point (terminal case handled above)
-	if (layout.root.type !== 'split') {
+	if (tab.root.type !== 'split') {
 		// Fallback: wrap in horizontal split
 		const newRoot: SplitNode = {
 			type: 'split',
 			id: generateId(),
 			direction: 'horizontal',
-			children: [cloneNode(layout.root), newNode],
+			children: [cloneNode(tab.root), newNode],
 			sizes: [50, 50]
 		};
-		return { root: newRoot, version: layout.version };
+		return { ...tab, root: newRoot };
 	}
 
-	const rootSplit = layout.root;
+	const rootSplit = tab.root;
 
 	// Root is a split - find which column contains the target
 	if (rootSplit.direction === 'horizontal') {
@@ -490,12 +526,12 @@ export function insertTerminalAfter(
 			const newSizesArray = newChildren.map(() => equalSize);
 
 			return {
+				...tab,
 				root: {
 					...rootSplit,
 					children: newChildren.map(cloneNode),
 					sizes: newSizesArray
-				},
-				version: layout.version
+				}
 			};
 		}
 	}
@@ -505,24 +541,24 @@ export function insertTerminalAfter(
 		type: 'split',
 		id: generateId(),
 		direction: 'horizontal',
-		children: [cloneNode(layout.root), newNode],
+		children: [cloneNode(tab.root), newNode],
 		sizes: [50, 50]
 	};
-	return { root: newRoot, version: layout.version };
+	return { ...tab, root: newRoot };
 }
 
 // Split a node (creates new split containing the target and new terminal)
 export function splitNode(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	targetNodeId: string,
 	zone: DropZone,
 	newSessionId: string
-): TerminalLayout {
-	if (!layout.root) {
-		return createLayoutWithTerminal(newSessionId);
+): LayoutTab {
+	if (!tab.root) {
+		return createTabWithTerminal(tab, newSessionId);
 	}
 
-	const newLayout = cloneLayout(layout);
+	const cloned = cloneTab(tab);
 	const direction = dropZoneToDirection(zone);
 	const newFirst = isNewNodeFirst(zone);
 	const newTerminal = createTerminalNode(newSessionId);
@@ -549,21 +585,21 @@ export function splitNode(
 	}
 
 	return {
-		root: replaceWithSplit(newLayout.root!) as LayoutNode,
-		version: layout.version
+		...tab,
+		root: replaceWithSplit(cloned.root!) as LayoutNode
 	};
 }
 
-// Remove a node from the layout
-export function removeNode(layout: TerminalLayout, nodeId: string): TerminalLayout {
-	if (!layout.root) return layout;
+// Remove a node from the tab
+export function removeNode(tab: LayoutTab, nodeId: string): LayoutTab {
+	if (!tab.root) return tab;
 
-	// If removing the root terminal, return empty layout
-	if (layout.root.id === nodeId) {
-		return createEmptyLayout();
+	// If removing the root terminal, return empty tab
+	if (tab.root.id === nodeId) {
+		return { ...tab, root: null };
 	}
 
-	const newLayout = cloneLayout(layout);
+	const cloned = cloneTab(tab);
 
 	function remove(node: LayoutNode): LayoutNode | null {
 		// Terminal and webview nodes are leaf nodes - return as-is
@@ -614,29 +650,29 @@ export function removeNode(layout: TerminalLayout, nodeId: string): TerminalLayo
 		return null;
 	}
 
-	const newRoot = remove(newLayout.root!);
+	const newRoot = remove(cloned.root!);
 	return {
-		root: newRoot,
-		version: layout.version
+		...tab,
+		root: newRoot
 	};
 }
 
-// Remove a session from the layout
-export function removeSession(layout: TerminalLayout, sessionId: string): TerminalLayout {
-	const node = findNodeBySessionId(layout, sessionId);
-	if (!node) return layout;
-	return removeNode(layout, node.id);
+// Remove a session from the tab
+export function removeSession(tab: LayoutTab, sessionId: string): LayoutTab {
+	const node = findNodeBySessionId(tab, sessionId);
+	if (!node) return tab;
+	return removeNode(tab, node.id);
 }
 
 // Update sizes for a split node
 export function resizeSplit(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	splitNodeId: string,
 	sizes: number[]
-): TerminalLayout {
-	if (!layout.root) return layout;
+): LayoutTab {
+	if (!tab.root) return tab;
 
-	const newLayout = cloneLayout(layout);
+	const cloned = cloneTab(tab);
 
 	function update(node: LayoutNode): LayoutNode {
 		if (node.id === splitNodeId && node.type === 'split') {
@@ -655,34 +691,34 @@ export function resizeSplit(
 	}
 
 	return {
-		root: update(newLayout.root!) as LayoutNode,
-		version: layout.version
+		...tab,
+		root: update(cloned.root!) as LayoutNode
 	};
 }
 
 // Move a session to a different location (used for center drop zone)
 export function moveSession(
-	layout: TerminalLayout,
+	tab: LayoutTab,
 	sessionId: string,
 	targetNodeId: string
-): TerminalLayout {
+): LayoutTab {
 	// For now, moving to center just swaps positions
 	// This could be enhanced to support tab-like behavior later
-	const sourceNode = findNodeBySessionId(layout, sessionId);
-	const targetNode = findNodeById(layout, targetNodeId);
+	const sourceNode = findNodeBySessionId(tab, sessionId);
+	const targetNode = findNodeById(tab, targetNodeId);
 
 	if (!sourceNode || !targetNode || targetNode.type !== 'terminal') {
-		return layout;
+		return tab;
 	}
 
 	if (sourceNode.id === targetNode.id) {
-		return layout; // Same node, no change
+		return tab; // Same node, no change
 	}
 
 	// Capture as non-null for closure
 	const srcNode = sourceNode;
 	const tgtNode = targetNode;
-	const newLayout = cloneLayout(layout);
+	const cloned = cloneTab(tab);
 
 	// Swap session IDs
 	function swap(node: LayoutNode): LayoutNode {
@@ -704,8 +740,8 @@ export function moveSession(
 	}
 
 	return {
-		root: newLayout.root ? swap(newLayout.root) : null,
-		version: layout.version
+		...tab,
+		root: cloned.root ? swap(cloned.root) : null
 	};
 }
 
@@ -714,12 +750,12 @@ export function serializeLayout(layout: TerminalLayout): string {
 	return JSON.stringify(layout);
 }
 
-// Deserialize layout from JSON string
+// Deserialize layout from JSON string (the backend has already migrated it)
 export function deserializeLayout(json: string): TerminalLayout | null {
 	try {
 		const parsed = JSON.parse(json);
 		// Basic validation
-		if (typeof parsed.version !== 'number') {
+		if (typeof parsed.version !== 'number' || !Array.isArray(parsed.tabs)) {
 			return null;
 		}
 		return parsed as TerminalLayout;
@@ -728,14 +764,14 @@ export function deserializeLayout(json: string): TerminalLayout | null {
 	}
 }
 
-// Count terminals in layout
-export function countTerminals(layout: TerminalLayout): number {
-	return getAllSessionIds(layout).length;
+// Count terminals in tab
+export function countTerminals(tab: LayoutTab): number {
+	return getAllSessionIds(tab).length;
 }
 
 // Get focused node (first terminal found - could be enhanced with focus tracking)
-export function getFirstTerminal(layout: TerminalLayout): TerminalNode | null {
-	if (!layout.root) return null;
+export function getFirstTerminal(tab: LayoutTab): TerminalNode | null {
+	if (!tab.root) return null;
 
 	function find(node: LayoutNode): TerminalNode | null {
 		if (node.type === 'terminal') return node;
@@ -749,21 +785,21 @@ export function getFirstTerminal(layout: TerminalLayout): TerminalNode | null {
 		return null;
 	}
 
-	return find(layout.root);
+	return find(tab.root);
 }
 
 // Find the root-level column ID that contains a given node
 // Returns the ID of the direct child of root horizontal split that contains nodeId
-export function findRootColumnId(layout: TerminalLayout, nodeId: string): string | null {
-	if (!layout.root) return null;
+export function findRootColumnId(tab: LayoutTab, nodeId: string): string | null {
+	if (!tab.root) return null;
 
 	// If root is a terminal or webview and matches, return its id
-	if (layout.root.type === 'terminal' || layout.root.type === 'webview') {
-		return layout.root.id === nodeId ? layout.root.id : null;
+	if (tab.root.type === 'terminal' || tab.root.type === 'webview') {
+		return tab.root.id === nodeId ? tab.root.id : null;
 	}
 
 	// Root is a split
-	const rootSplit = layout.root;
+	const rootSplit = tab.root;
 
 	// If root is not a horizontal split, the whole thing is one "column"
 	if (rootSplit.direction !== 'horizontal') {
@@ -794,3 +830,52 @@ export function findRootColumnId(layout: TerminalLayout, nodeId: string): string
 
 	return null;
 }
+
+// ========== Tabs ==========
+
+// The active tab (falls back to the first tab)
+export function getActiveTab(layout: TerminalLayout): LayoutTab | null {
+	return layout.tabs.find((t) => t.id === layout.activeTab) ?? layout.tabs[0] ?? null;
+}
+
+// Replace a tab by ID
+export function replaceTab(layout: TerminalLayout, tab: LayoutTab): TerminalLayout {
+	return {
+		...layout,
+		tabs: layout.tabs.map((t) => (t.id === tab.id ? tab : t))
+	};
+}
+
+// Tab containing a session's terminal
+export function findTabBySessionId(layout: TerminalLayout, sessionId: string): LayoutTab | null {
+	return layout.tabs.find((t) => findNodeBySessionId(t, sessionId)) ?? null;
+}
+
+// Tab containing a pane (terminal or webview)
+export function findTabByNodeId(layout: TerminalLayout, nodeId: string): LayoutTab | null {
+	return layout.tabs.find((t) => findNodeById(t, nodeId)) ?? null;
+}
+
+// Remove a session from whichever tab holds it
+export function removeLayoutSession(layout: TerminalLayout, sessionId: string): TerminalLayout {
+	const tab = findTabBySessionId(layout, sessionId);
+	return tab ? replaceTab(layout, removeSession(tab, sessionId)) : layout;
+}
+
+// Session IDs across every tab
+export function getLayoutSessionIds(layout: TerminalLayout): string[] {
+	return layout.tabs.flatMap(getAllSessionIds);
+}
+
+// Webviews across every tab
+export function getLayoutWebviews(layout: TerminalLayout): WebviewNode[] {
+	return layout.tabs.flatMap(getAllWebviews);
+}
+
+// Tab `offset` places from the active one (wraps around)
+export function getAdjacentTab(layout: TerminalLayout, offset: number): LayoutTab | null {
+	if (layout.tabs.length === 0) return null;
+	const current = layout.tabs.findIndex((t) => t.id === layout.activeTab);
+	const next = (Math.max(current, 0) + offset + layout.tabs.length) % layout.tabs.length;
+	return layout.tabs[next];
+}